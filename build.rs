@@ -46,9 +46,13 @@ fn generate_sys() {
         .expect("Error: could not write to the rust header file.");
 }
 
-/// Recast nft_*_attributes from u32 to u16 in header string `header`.
+/// Recast nft_*_attributes, as well as the nfnetlink_log and nfnetlink_queue attribute enums,
+/// from u32 to u16 in header string `header`.
 fn reformat_units(header: &str) -> Cow<str> {
-    let re = Regex::new(r"(pub type nft[a-zA-Z_]*_attributes) = u32;").unwrap();
+    let re = Regex::new(
+        r"(pub type (?:nft[a-zA-Z_]*_attributes|nf(?:ulnl|qnl)_[a-z_]*attr[a-z_]*)) = u32;",
+    )
+    .unwrap();
     re.replace_all(header, |captures: &Captures| {
         format!("{} = u16;", &captures[1])
     })
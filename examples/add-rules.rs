@@ -63,7 +63,7 @@ fn main() -> Result<(), Error> {
     let table = Table::new(ProtocolFamily::Inet).with_name(TABLE_NAME);
     // Add the table to the batch with the `MsgType::Add` type, thus instructing netfilter to add
     // this table under its `ProtocolFamily::Inet` ruleset.
-    batch.add(&table, MsgType::Add);
+    batch.add(&table, MsgType::Add)?;
 
     // Create input and output chains under the table we created above.
     // Hook the chains to the input and output event hooks, with highest priority (priority zero).
@@ -80,8 +80,8 @@ fn main() -> Result<(), Error> {
 
     // Add the two chains to the batch with the `MsgType` to tell netfilter to create the chains
     // under the table.
-    batch.add(&out_chain, MsgType::Add);
-    batch.add(&in_chain, MsgType::Add);
+    batch.add(&out_chain, MsgType::Add)?;
+    batch.add(&in_chain, MsgType::Add)?;
 
     // === ADD RULE ALLOWING ALL TRAFFIC TO THE LOOPBACK DEVICE ===
 
@@ -108,7 +108,7 @@ fn main() -> Result<(), Error> {
         .with_expr(Immediate::new_verdict(VerdictKind::Accept));
 
     // Add the rule to the batch.
-    batch.add(&allow_loopback_in_rule, rustables::MsgType::Add);
+    batch.add(&allow_loopback_in_rule, rustables::MsgType::Add)?;
 
     // === ADD A RULE ALLOWING (AND COUNTING) ALL PACKETS TO THE 10.1.0.0/24 NETWORK ===
 
@@ -148,7 +148,7 @@ fn main() -> Result<(), Error> {
 
     // Add the rule to the batch. Without this nothing would be sent over netlink and netfilter,
     // and all the work on `block_out_to_private_net_rule` so far would go to waste.
-    batch.add(&block_out_to_private_net_rule, rustables::MsgType::Add);
+    batch.add(&block_out_to_private_net_rule, rustables::MsgType::Add)?;
 
     // === ADD A RULE ALLOWING ALL OUTGOING ICMPv6 PACKETS WITH TYPE 133 AND CODE 0 ===
 
@@ -170,7 +170,7 @@ fn main() -> Result<(), Error> {
         .with_expr(Cmp::new(CmpOp::Eq, [IcmpCode::NoRoute as u8]))
         .with_expr(Immediate::new_verdict(VerdictKind::Accept));
 
-    batch.add(&allow_router_solicitation, rustables::MsgType::Add);
+    batch.add(&allow_router_solicitation, rustables::MsgType::Add)?;
 
     // === FINALIZE THE TRANSACTION AND SEND THE DATA TO NETFILTER ===
 
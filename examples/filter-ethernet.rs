@@ -39,12 +39,12 @@ fn main() {
     // `add-rules` example.
     let mut batch = Batch::new();
     let table = Table::new(ProtocolFamily::Inet).with_name(TABLE_NAME);
-    batch.add(&table, rustables::MsgType::Add);
+    batch.add(&table, rustables::MsgType::Add).unwrap();
 
     let mut out_chain = Chain::new(&table).with_name(OUT_CHAIN_NAME);
     out_chain.set_hook(Hook::new(HookClass::Out, 3));
     out_chain.set_policy(ChainPolicy::Accept);
-    batch.add(&out_chain, rustables::MsgType::Add);
+    batch.add(&out_chain, rustables::MsgType::Add).unwrap();
 
     // === ADD RULE DROPPING ALL TRAFFIC TO THE MAC ADDRESS IN `BLOCK_THIS_MAC` ===
 
@@ -65,7 +65,7 @@ fn main() {
         .with_value(Immediate::new_verdict(VerdictKind::Drop)),
     );
 
-    batch.add(&block_ethernet_rule, rustables::MsgType::Add);
+    batch.add(&block_ethernet_rule, rustables::MsgType::Add).unwrap();
 
     // === FOR FUN, ADD A PACKET THAT MATCHES 50% OF ALL PACKETS ===
 
@@ -90,7 +90,7 @@ fn main() {
         .with_value(Counter::default()),
     );
 
-    batch.add(&random_rule, rustables::MsgType::Add);
+    batch.add(&random_rule, rustables::MsgType::Add).unwrap();
 
     // === FINALIZE THE TRANSACTION AND SEND THE DATA TO NETFILTER ===
 
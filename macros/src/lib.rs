@@ -409,6 +409,14 @@ pub fn nfnetlink_struct(attrs: TokenStream, item: TokenStream) -> TokenStream {
                 }
             )
         });
+        let check_entries = fields.iter().map(|field| {
+            let field_name = field.name;
+            quote!(
+                if let Some(val) = &self.#field_name {
+                    crate::parser::check_attribute_size(val)?;
+                }
+            )
+        });
         let nested = args.nested;
         quote!(
             impl crate::nlmsg::NfNetlinkAttribute for #name {
@@ -424,6 +432,11 @@ pub fn nfnetlink_struct(attrs: TokenStream, item: TokenStream) -> TokenStream {
                     size
                 }
 
+                fn check_size(&self) -> Result<(), crate::error::BuilderError> {
+                    #(#check_entries) *
+                    Ok(())
+                }
+
                 fn write_payload(&self, mut addr: &mut [u8]) {
                     use crate::nlmsg::NfNetlinkAttribute;
 
@@ -1,18 +1,26 @@
+use std::os::unix::io::{AsRawFd, RawFd};
+
 use libc::{self, c_int};
 
 use thiserror::Error;
 
 use netlink_sys::{AsyncSocket, AsyncSocketExt, Socket};
 
-use crate::error::QueryError;
-use crate::nlmsg::{NfNetlinkObject, NfNetlinkWriter};
+use crate::error::{BuilderError, QueryError};
+use crate::nlmsg::{
+    nft_nlmsg_maxsize, pad_netlink_object_with_variable_size, NfNetlinkAttribute, NfNetlinkObject,
+    NfNetlinkWriter,
+};
+use crate::parser::get_nlmsghdr;
 use crate::query::recv_and_process_async;
-use crate::sys::NFNL_SUBSYS_NFTABLES;
+use crate::set::SetElementList;
+use crate::sys::{nlmsghdr, NETLINK_CAP_ACK, NFNL_SUBSYS_NFTABLES, NLM_F_ACK};
 use crate::{MsgType, ProtocolFamily};
 
 use nix::sys::socket::{
-    self, AddressFamily, MsgFlags, NetlinkAddr, SockAddr, SockFlag, SockProtocol, SockType,
+    self, sockopt, AddressFamily, MsgFlags, NetlinkAddr, SockAddr, SockFlag, SockProtocol, SockType,
 };
+use nix::sys::uio::IoVec;
 
 /// Error while communicating with netlink.
 #[derive(Error, Debug)]
@@ -20,12 +28,14 @@ use nix::sys::socket::{
 pub struct NetlinkError(());
 
 /// A batch of netfilter messages to be performed in one atomic operation.
+///
+/// Messages are written into pages of (at least) the batch page size: a message is never split
+/// across two pages, but it may overrun the end of a page, in which case the next message starts
+/// a new page. The pages are then handed to the kernel as the iovec of a single `sendmsg` call,
+/// so the batch is still processed as a single transaction.
 pub struct Batch {
-    buf: Box<Vec<u8>>,
-    // the 'static lifetime here is a cheat, as the writer can only be used as long
-    // as `self.buf` exists. This is why this member must never be exposed directly to
-    // the rest of the crate (let alone publicly).
-    writer: NfNetlinkWriter<'static>,
+    pages: Vec<Vec<u8>>,
+    page_size: usize,
     seq: u32,
     pub(crate) non_empty: bool,
 }
@@ -35,13 +45,19 @@ impl Batch {
     ///
     /// [default page size]: fn.default_batch_page_size.html
     pub fn new() -> Self {
-        // TODO: use a pinned Box ?
-        let mut buf = Box::new(Vec::with_capacity(default_batch_page_size() as usize));
-        // Safe because we hold onto the buffer for as long as `writer` exists
-        let mut writer = NfNetlinkWriter::new(unsafe {
-            std::mem::transmute(Box::as_mut(&mut buf) as *mut Vec<u8>)
-        });
-        let seq = 0;
+        Self::with_page_size(default_batch_page_size())
+    }
+
+    /// Creates a new nftnl batch whose pages are `page_size` bytes long.
+    pub fn with_page_size(page_size: u32) -> Self {
+        let mut batch = Batch {
+            pages: Vec::new(),
+            page_size: page_size as usize,
+            seq: 0,
+            non_empty: false,
+        };
+        let seq = batch.seq;
+        let mut writer = batch.writer();
         writer.write_header(
             libc::NFNL_MSG_BATCH_BEGIN as u16,
             ProtocolFamily::Unspec,
@@ -50,20 +66,43 @@ impl Batch {
             Some(libc::NFNL_SUBSYS_NFTABLES as u16),
         );
         writer.finalize_writing_object();
-        Batch {
-            buf,
-            writer,
-            seq: seq + 1,
-            non_empty: false,
+        batch.seq += 1;
+        batch
+    }
+
+    /// Returns a writer appending to the current page, after starting a new page if the current
+    /// one is already full.
+    fn writer(&mut self) -> NfNetlinkWriter<'_> {
+        let page_full = match self.pages.last() {
+            Some(page) => page.len() >= self.page_size,
+            None => true,
+        };
+        if page_full {
+            // leave enough room for a message to overrun the page without reallocating
+            self.pages.push(Vec::with_capacity(
+                self.page_size + nft_nlmsg_maxsize() as usize,
+            ));
         }
+        NfNetlinkWriter::new(self.pages.last_mut().unwrap())
     }
 
     /// Adds the given message to this batch.
-    pub fn add<T: NfNetlinkObject>(&mut self, msg: &T, msg_type: MsgType) {
+    ///
+    /// Fails without modifying the batch if one of the attributes of the message is too large to
+    /// be serialized.
+    pub fn add<T: NfNetlinkObject>(
+        &mut self,
+        msg: &T,
+        msg_type: MsgType,
+    ) -> Result<(), BuilderError> {
         trace!("Writing NlMsg with seq {} to batch", self.seq);
-        msg.add_or_remove(&mut self.writer, msg_type, self.seq);
+        // check the size before taking the writer, which may open a new page
+        msg.check_size()?;
+        let seq = self.seq;
+        msg.add_or_remove(&mut self.writer(), msg_type, seq)?;
         self.non_empty = true;
         self.seq += 1;
+        Ok(())
     }
 
     /// Adds all the messages in the given iterator to this batch.
@@ -71,30 +110,46 @@ impl Batch {
         &mut self,
         msg_iter: I,
         msg_type: MsgType,
-    ) {
+    ) -> Result<(), BuilderError> {
         for msg in msg_iter {
-            self.add(&msg, msg_type);
+            self.add(&msg, msg_type)?;
         }
+        Ok(())
     }
 
-    /// Adds the final end message to the batch and returns a [`FinalizedBatch`] that can be used
-    /// to send the messages to netfilter.
-    ///
-    /// Return None if there is no object in the batch (this could block forever).
-    ///
-    /// [`FinalizedBatch`]: struct.FinalizedBatch.html
-    pub fn finalize(mut self) -> Vec<u8> {
-        self.writer.write_header(
+    /// Adds the elements of `elements` to this batch, split over as many messages as needed for
+    /// each of them to respect the netlink attribute size limit.
+    pub fn add_set_elements(
+        &mut self,
+        elements: &SetElementList,
+        msg_type: MsgType,
+    ) -> Result<(), BuilderError> {
+        self.add_iter(elements.split().into_iter(), msg_type)
+    }
+
+    /// Adds the final end message to the batch and returns the pages holding the serialized
+    /// messages, in order.
+    pub fn finalize_pages(mut self) -> Vec<Vec<u8>> {
+        let seq = self.seq;
+        let mut writer = self.writer();
+        writer.write_header(
             libc::NFNL_MSG_BATCH_END as u16,
             ProtocolFamily::Unspec,
             0,
-            self.seq,
+            seq,
             Some(NFNL_SUBSYS_NFTABLES as u16),
         );
-        self.writer.finalize_writing_object();
-        *self.buf
+        writer.finalize_writing_object();
+        self.pages
     }
 
+    /// Adds the final end message to the batch and returns the serialized messages as a single
+    /// buffer.
+    pub fn finalize(self) -> Vec<u8> {
+        self.finalize_pages().concat()
+    }
+
+    /// Sends the batch over `sock` and waits for the kernel to acknowledge it.
     pub fn send(self, sock: &mut Socket) -> anyhow::Result<()> {
         if !self.non_empty {
             // if empty, the socket will receive nothing and block forever
@@ -102,33 +157,129 @@ impl Batch {
             return Ok(());
         }
 
-        use crate::query::{recv_and_process, socket_close_wrapper};
+        use crate::query::recv_and_process;
 
         let max_seq = self.seq - 1;
-
-        let addr = SockAddr::Netlink(NetlinkAddr::new(0, 0));
-
-        let to_send = self.finalize();
-        log::trace!("to send {}", to_send.len());
-        let mut sent = 0;
-        while sent != to_send.len() {
-            sent += sock.send(&to_send[sent..], MsgFlags::empty().bits())?;
-        }
+        let pages = self.finalize_pages_for_sending(max_seq);
+        prepare_socket(sock);
+        send_pages(sock.as_raw_fd(), &pages)?;
 
         Ok(recv_and_process(sock, Some(max_seq), None, &mut ())?)
     }
 
+    /// Sends the batch over `sock` and waits for the kernel to acknowledge it.
     pub async fn send_async<S: AsyncSocket>(self, sock: &mut S) -> anyhow::Result<()> {
         if !self.non_empty {
             return Ok(());
         }
         let max_seq = self.seq - 1;
-        let to_send = self.finalize();
-        sock.send(&to_send).await?;
+        let pages = self.finalize_pages_for_sending(max_seq);
+        prepare_socket(sock.socket_ref());
+        // sending to the kernel never blocks, hence the synchronous call
+        send_pages(sock.socket_ref().as_raw_fd(), &pages)?;
         recv_and_process_async(sock, Some(max_seq), None, &mut ()).await?;
 
         Ok(())
     }
+
+    /// Finalizes the batch, and only requests an acknowledgement for its last message (the one
+    /// with the sequence number `max_seq`).
+    ///
+    /// The kernel processes the whole batch, and queues all of its replies, within the `sendmsg`
+    /// call, so they cannot be read as they come in: with one acknowledgement per message, a
+    /// large batch (say 200k set elements) would overflow the receive buffer whatever its size.
+    /// Errors are reported whether an acknowledgement was requested or not, so this only leaves
+    /// a single ack to wait for on success.
+    pub(crate) fn finalize_pages_for_sending(self, max_seq: u32) -> Vec<Vec<u8>> {
+        let mut pages = self.finalize_pages();
+        for page in pages.iter_mut() {
+            let mut offset = 0;
+            while offset < page.len() {
+                let mut hdr = get_nlmsghdr(&page[offset..])
+                    .expect("The batch pages only hold valid netlink messages");
+                if hdr.nlmsg_seq != max_seq {
+                    hdr.nlmsg_flags &= !(NLM_F_ACK as u16);
+                    unsafe {
+                        std::ptr::write_unaligned(
+                            page[offset..].as_mut_ptr() as *mut nlmsghdr,
+                            hdr,
+                        );
+                    }
+                }
+                offset += pad_netlink_object_with_variable_size(hdr.nlmsg_len as usize);
+            }
+        }
+        pages
+    }
+}
+
+/// Sends `pages` in a single datagram, as nfnetlink only commits a batch whose begin and end
+/// messages were received in the same datagram: the batch cannot be split across several
+/// `sendmsg` calls without splitting the transaction.
+fn send_pages(fd: RawFd, pages: &[Vec<u8>]) -> anyhow::Result<()> {
+    let len: usize = pages.iter().map(Vec::len).sum();
+    grow_send_buffer(fd, len);
+
+    // the kernel rejects iovecs with more than IOV_MAX entries, merge adjacent pages until the
+    // iovec fits
+    let iov_max = unsafe { libc::sysconf(libc::_SC_IOV_MAX) };
+    let merged: Vec<Vec<u8>>;
+    let pages = if iov_max > 0 && pages.len() > iov_max as usize {
+        let pages_per_entry = pages.len().div_ceil(iov_max as usize);
+        merged = pages.chunks(pages_per_entry).map(<[_]>::concat).collect();
+        &merged
+    } else {
+        pages
+    };
+
+    let iov: Vec<IoVec<&[u8]>> = pages.iter().map(|page| IoVec::from_slice(page)).collect();
+    log::trace!("to send {} in {} pages", len, iov.len());
+    let addr = SockAddr::Netlink(NetlinkAddr::new(0, 0));
+    let sent = socket::sendmsg(fd, &iov, &[], MsgFlags::empty(), Some(&addr))?;
+    if sent != len {
+        return Err(QueryError::TruncatedSend.into());
+    }
+    Ok(())
+}
+
+/// Asks the kernel not to echo the offending message in the error replies, so that the errors
+/// of a failed batch (which are sent even when no acknowledgement was requested) stay small.
+fn prepare_socket(sock: &Socket) {
+    let enable: c_int = 1;
+    let res = unsafe {
+        libc::setsockopt(
+            sock.as_raw_fd(),
+            libc::SOL_NETLINK,
+            NETLINK_CAP_ACK as c_int,
+            &enable as *const c_int as *const libc::c_void,
+            std::mem::size_of::<c_int>() as libc::socklen_t,
+        )
+    };
+    if res < 0 {
+        warn!(
+            "Couldn't set NETLINK_CAP_ACK: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// Raises the send buffer so that the kernel can accept a batch of `batch_len` bytes in a single
+/// datagram, otherwise large batches fail with EMSGSIZE.
+///
+/// This is a best effort: `SO_SNDBUFFORCE` requires CAP_NET_ADMIN (which is needed to modify the
+/// ruleset anyway), and we fall back to `SO_SNDBUF`, capped by the `net.core.wmem_max` sysctl,
+/// when it fails.
+fn grow_send_buffer(fd: RawFd, batch_len: usize) {
+    if socket::getsockopt(fd, sockopt::SndBuf).map_or(true, |cur| cur < batch_len) {
+        if let Err(e) = socket::setsockopt(fd, sockopt::SndBufForce, &batch_len)
+            .or_else(|_| socket::setsockopt(fd, sockopt::SndBuf, &batch_len))
+        {
+            warn!(
+                "Couldn't raise the send buffer to {} bytes: {}",
+                batch_len, e
+            );
+        }
+    }
 }
 
 /// Selected batch page is 256 Kbytes long to load ruleset of half a million rules without hitting
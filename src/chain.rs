@@ -2,7 +2,7 @@ use libc::{NF_ACCEPT, NF_DROP};
 use netlink_sys::{AsyncSocket, Socket};
 use rustables_macros::nfnetlink_struct;
 
-use crate::error::{DecodeError, QueryError};
+use crate::error::{BuilderError, DecodeError, QueryError};
 use crate::nlmsg::{NfNetlinkAttribute, NfNetlinkDeserializable, NfNetlinkObject};
 use crate::sys::{
     NFTA_CHAIN_FLAGS, NFTA_CHAIN_HOOK, NFTA_CHAIN_NAME, NFTA_CHAIN_POLICY, NFTA_CHAIN_TABLE,
@@ -75,7 +75,7 @@ impl NfNetlinkDeserializable for ChainPolicy {
         Ok((
             match v {
                 NF_ACCEPT => ChainPolicy::Accept,
-                NF_DROP => ChainPolicy::Accept,
+                NF_DROP => ChainPolicy::Drop,
                 _ => return Err(DecodeError::UnknownChainPolicy),
             },
             remaining_data,
@@ -174,9 +174,9 @@ impl Chain {
     }
 
     /// Appends this chain to `batch`
-    pub fn add_to_batch(self, batch: &mut Batch) -> Self {
-        batch.add(&self, crate::MsgType::Add);
-        self
+    pub fn add_to_batch(self, batch: &mut Batch) -> Result<Self, BuilderError> {
+        batch.add(&self, crate::MsgType::Add)?;
+        Ok(self)
     }
 }
 
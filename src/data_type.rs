@@ -25,6 +25,16 @@ impl DataType for Ipv6Addr {
     }
 }
 
+impl DataType for u32 {
+    const TYPE: u32 = 4;
+    const LEN: u32 = 4;
+
+    // integers are stored in host byte order, as they are in registers
+    fn data(&self) -> Vec<u8> {
+        self.to_ne_bytes().to_vec()
+    }
+}
+
 impl<const N: usize> DataType for [u8; N] {
     const TYPE: u32 = 5;
     const LEN: u32 = N as u32;
@@ -3,7 +3,7 @@ use std::string::FromUtf8Error;
 use nix::errno::Errno;
 use thiserror::Error;
 
-use crate::{sys::nlmsgerr, nlmsg::NetlinkType};
+use crate::{expr::RejectKind, sys::nlmsgerr, nlmsg::NetlinkType};
 
 #[derive(Error, Debug)]
 pub enum DecodeError {
@@ -16,7 +16,7 @@ pub enum DecodeError {
     #[error("The message holds unexpected data")]
     InvalidDataSize,
 
-    #[error("Invalid subsystem, expected NFTABLES")]
+    #[error("Invalid subsystem for the message")]
     InvalidSubsystem(u8),
 
     #[error("Invalid version, expected NFNETLINK_V0")]
@@ -43,6 +43,9 @@ pub enum DecodeError {
     #[error("Invalid policy for a chain")]
     UnknownChainPolicy,
 
+    #[error("Invalid level for a log expression")]
+    UnknownLogLevel(u32),
+
     #[error("Unknown type for a Meta expression")]
     UnknownMetaType(u32),
 
@@ -52,6 +55,12 @@ pub enum DecodeError {
     #[error("Unsupported value for an icmp code in a reject expression")]
     UnknownIcmpCode(u8),
 
+    #[error("Unsupported value for an ICMP code")]
+    UnknownIcmpv4Code(u8),
+
+    #[error("Unsupported value for an ICMPv6 code")]
+    UnknownIcmpv6Code(u8),
+
     #[error("Invalid value for a register")]
     UnknownRegister(u32),
 
@@ -64,12 +73,84 @@ pub enum DecodeError {
     #[error("Invalid type for a payload expression")]
     UnknownPayloadType(u32),
 
+    #[error("Invalid checksum type for a payload expression")]
+    UnknownPayloadCsumType(u32),
+
     #[error("Invalid type for a compare expression")]
     UnknownCmpOp(u32),
 
+    #[error("Invalid type for a range expression")]
+    UnknownRangeOp(u32),
+
+    #[error("Invalid type for a bitwise expression")]
+    UnknownBitwiseOp(u32),
+
     #[error("Invalid type for a conntrack key")]
     UnknownConntrackKey(u32),
 
+    #[error("Invalid direction for a conntrack expression")]
+    UnknownCtDirection(u8),
+
+    #[error("Invalid type for a limit expression")]
+    UnknownLimitType(u32),
+
+    #[error("Invalid result type for a fib expression")]
+    UnknownFibResult(u32),
+
+    #[error("Invalid key for a rt expression")]
+    UnknownRtKey(u32),
+
+    #[error("Invalid operation for a byteorder expression")]
+    UnknownByteorderOp(u32),
+
+    #[error("Invalid operation for an exthdr expression")]
+    UnknownExthdrOp(u32),
+
+    #[error("Invalid type for a hash expression")]
+    UnknownHashType(u32),
+
+    #[error("Invalid type for a numgen expression")]
+    UnknownNumgenType(u32),
+
+    #[error("Invalid key for a socket expression")]
+    UnknownSocketKey(u32),
+
+    #[error("Invalid TTL mode for an osf expression")]
+    UnknownOsfTtl(u8),
+
+    #[error("Unknown type for a stateful object")]
+    UnknownObjectType(u32),
+
+    #[error("Invalid key for a xfrm expression")]
+    UnknownXfrmKey(u32),
+
+    #[error("Invalid direction for a xfrm expression")]
+    UnknownXfrmDirection(u8),
+
+    #[error("Invalid key for a tunnel expression")]
+    UnknownTunnelKey(u32),
+
+    #[error("Invalid mode for a tunnel expression")]
+    UnknownTunnelMode(u32),
+
+    #[error("Invalid tunnel header type for an inner expression")]
+    UnknownInnerType(u32),
+
+    #[error("Invalid copy mode for a NFLOG group or a NFQUEUE queue")]
+    UnknownCopyMode(u8),
+
+    #[error("Invalid command for a NFLOG group or a NFQUEUE queue")]
+    UnknownConfigCommand(u8),
+
+    #[error("Invalid conntrack information for a packet")]
+    UnknownCtInfo(u32),
+
+    #[error("Invalid verdict for a queued packet")]
+    UnknownQueueVerdict(u32),
+
+    #[error("Invalid type for a trace event")]
+    UnknownTraceType(u32),
+
     #[error("Unsupported value for a link layer header field")]
     UnknownLinkLayerHeaderField(u32, u32),
 
@@ -88,6 +169,30 @@ pub enum DecodeError {
     #[error("Unsupported value for an ICMPv6 header field")]
     UnknownICMPv6HeaderField(u32, u32),
 
+    #[error("Unsupported value for a VLAN header field")]
+    UnknownVlanHeaderField(u32, u32),
+
+    #[error("Unsupported value for an ARP header field")]
+    UnknownArpHeaderField(u32, u32),
+
+    #[error("Unsupported value for an ICMP header field")]
+    UnknownICMPHeaderField(u32, u32),
+
+    #[error("Unsupported value for a SCTP header field")]
+    UnknownSctpHeaderField(u32, u32),
+
+    #[error("Unsupported value for a DCCP header field")]
+    UnknownDccpHeaderField(u32, u32),
+
+    #[error("Unsupported value for an AH header field")]
+    UnknownAhHeaderField(u32, u32),
+
+    #[error("Unsupported value for an ESP header field")]
+    UnknownEspHeaderField(u32, u32),
+
+    #[error("Unsupported value for a GRE header field")]
+    UnknownGreHeaderField(u32, u32),
+
     #[error("Missing the 'base' attribute to deserialize the payload object")]
     PayloadMissingBase,
 
@@ -100,6 +205,9 @@ pub enum DecodeError {
     #[error("The object does not contain a name for the expression being parsed")]
     MissingExpressionName,
 
+    #[error("The object does not contain a type for the object data being parsed")]
+    MissingObjectType,
+
     #[error("Unsupported attribute type")]
     UnsupportedAttributeType(NetlinkType),
 
@@ -130,8 +238,50 @@ pub enum BuilderError {
     #[error("The interface name is too long to be written")]
     InterfaceNameTooLong,
 
+    #[error("Couldn't find the index of the interface")]
+    InterfaceNotFound(#[source] std::io::Error),
+
     #[error("The log prefix string is more than 127 characters long")]
     TooLongLogPrefix,
+
+    #[error("The security context of a secmark object is too long")]
+    TooLongSecmarkContext,
+
+    #[error("The syslog and NFLOG options of a log expression can't be combined")]
+    IncompatibleLogOptions,
+
+    #[error("An attribute is too large to fit in a netlink attribute")]
+    AttributeTooLarge,
+
+    #[error("No register is available to hold the value")]
+    NoRegisterAvailable,
+
+    #[error("This kind of reject is not supported for the packets matched by the rule")]
+    UnsupportedReject(RejectKind),
+
+    #[error("The number of backends must be between 1 and 2^32 - 1")]
+    InvalidBackendCount,
+
+    #[error("The queue range is empty or holds more than 65535 queues")]
+    InvalidQueueRange,
+
+    #[error("Conntrack labels are numbered from 0 to 127")]
+    InvalidCtLabel(u8),
+
+    #[error("The bounds of the range do not belong to the same address family")]
+    IncompatibleAddressFamilies,
+
+    #[error("The start of the range is greater than its end")]
+    InvalidRange,
+
+    #[error("The DSCP is a 6 bits value")]
+    InvalidDscp(u8),
+
+    #[error("Shifts apply to 32 bits words, by less than 32 bits")]
+    InvalidShift,
+
+    #[error("Checksums can only be updated for writes starting on a 16 bits word")]
+    UnalignedChecksumUpdate,
 }
 
 #[derive(thiserror::Error, Debug)]
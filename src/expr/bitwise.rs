@@ -1,12 +1,25 @@
-use rustables_macros::nfnetlink_struct;
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
 
 use super::{Expression, Register};
 use crate::error::BuilderError;
 use crate::parser_impls::NfNetlinkData;
 use crate::sys::{
-    NFTA_BITWISE_DREG, NFTA_BITWISE_LEN, NFTA_BITWISE_MASK, NFTA_BITWISE_SREG, NFTA_BITWISE_XOR,
+    NFTA_BITWISE_DATA, NFTA_BITWISE_DREG, NFTA_BITWISE_LEN, NFTA_BITWISE_MASK, NFTA_BITWISE_OP,
+    NFTA_BITWISE_SREG, NFTA_BITWISE_XOR, NFT_BITWISE_BOOL, NFT_BITWISE_LSHIFT, NFT_BITWISE_RSHIFT,
 };
 
+/// The operation performed by a [`Bitwise`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum BitwiseOp {
+    /// Masks the value with `mask`, then performs xor with `xor`.
+    Bool = NFT_BITWISE_BOOL,
+    /// Shifts the 32-bit words of the value to the left by `data` bits.
+    Lshift = NFT_BITWISE_LSHIFT,
+    /// Shifts the 32-bit words of the value to the right by `data` bits.
+    Rshift = NFT_BITWISE_RSHIFT,
+}
+
 #[derive(Clone, PartialEq, Eq, Default, Debug, Hash)]
 #[nfnetlink_struct]
 pub struct Bitwise {
@@ -20,6 +33,10 @@ pub struct Bitwise {
     mask: NfNetlinkData,
     #[field(NFTA_BITWISE_XOR)]
     xor: NfNetlinkData,
+    #[field(NFTA_BITWISE_OP)]
+    op: BitwiseOp,
+    #[field(NFTA_BITWISE_DATA)]
+    data: NfNetlinkData,
 }
 
 impl Expression for Bitwise {
@@ -44,4 +61,20 @@ impl Bitwise {
             .with_xor(NfNetlinkData::default().with_value(xor))
             .with_mask(NfNetlinkData::default().with_value(mask)))
     }
+
+    /// Returns a new `Bitwise` instance that shifts the `len` bytes of the value it's applied to
+    /// by `shift` bits, in the direction given by `op`. The value is handled as a sequence of
+    /// 32-bit words in host byte order, so `len` must be a multiple of 4 and `shift` lower than
+    /// 32, as the kernel requires.
+    pub fn new_shift(op: BitwiseOp, len: u32, shift: u32) -> Result<Self, BuilderError> {
+        if shift >= 32 || len == 0 || len % 4 != 0 {
+            return Err(BuilderError::InvalidShift);
+        }
+        Ok(Bitwise::default()
+            .with_sreg(Register::Reg1)
+            .with_dreg(Register::Reg1)
+            .with_op(op)
+            .with_len(len)
+            .with_data(NfNetlinkData::default().with_value(shift.to_ne_bytes().to_vec())))
+    }
 }
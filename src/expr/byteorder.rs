@@ -0,0 +1,53 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_BYTEORDER_DREG, NFTA_BYTEORDER_LEN, NFTA_BYTEORDER_OP, NFTA_BYTEORDER_SIZE,
+    NFTA_BYTEORDER_SREG, NFT_BYTEORDER_HTON, NFT_BYTEORDER_NTOH,
+};
+
+/// The conversion performed by a [`Byteorder`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum ByteorderOp {
+    /// From network to host byte order.
+    Ntoh = NFT_BYTEORDER_NTOH,
+    /// From host to network byte order.
+    Hton = NFT_BYTEORDER_HTON,
+}
+
+/// A byteorder expression converts the values stored in registers between the host and the
+/// network byte orders.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Byteorder {
+    #[field(NFTA_BYTEORDER_SREG)]
+    sreg: Register,
+    #[field(NFTA_BYTEORDER_DREG)]
+    dreg: Register,
+    #[field(NFTA_BYTEORDER_OP)]
+    op: ByteorderOp,
+    #[field(NFTA_BYTEORDER_LEN)]
+    len: u32,
+    #[field(NFTA_BYTEORDER_SIZE, name_in_functions = "element_size")]
+    size: u32,
+}
+
+impl Byteorder {
+    /// Converts in place the `len` bytes long value stored in `reg`, made of integers of `size`
+    /// bytes (2, 4 or 8).
+    pub fn new(op: ByteorderOp, reg: Register, len: u32, size: u32) -> Self {
+        Byteorder::default()
+            .with_sreg(reg)
+            .with_dreg(reg)
+            .with_op(op)
+            .with_len(len)
+            .with_element_size(size)
+    }
+}
+
+impl Expression for Byteorder {
+    fn get_name() -> &'static str {
+        "byteorder"
+    }
+}
@@ -0,0 +1,36 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::Expression;
+use crate::sys::{NFTA_CONNLIMIT_COUNT, NFTA_CONNLIMIT_FLAGS, NFT_CONNLIMIT_F_INV};
+
+/// A connlimit expression matches packets depending on the number of connections tracked for
+/// the same key, usually used in dynamic sets keyed by source address.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Connlimit {
+    #[field(NFTA_CONNLIMIT_COUNT)]
+    count: u32,
+    #[field(NFTA_CONNLIMIT_FLAGS)]
+    flags: u32,
+}
+
+impl Connlimit {
+    /// Returns a connlimit matching packets when there are at most `count` connections, or more
+    /// than `count` connections if `inverted` is set (`ct count over`).
+    pub fn new(count: u32, inverted: bool) -> Self {
+        Connlimit::default()
+            .with_count(count)
+            .with_flags(if inverted { NFT_CONNLIMIT_F_INV } else { 0 })
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.get_flags()
+            .is_some_and(|flags| flags & NFT_CONNLIMIT_F_INV != 0)
+    }
+}
+
+impl Expression for Connlimit {
+    fn get_name() -> &'static str {
+        "connlimit"
+    }
+}
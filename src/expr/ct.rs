@@ -1,7 +1,11 @@
 use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
 
 use crate::sys::{
-    NFTA_CT_DIRECTION, NFTA_CT_DREG, NFTA_CT_KEY, NFTA_CT_SREG, NFT_CT_MARK, NFT_CT_STATE,
+    NFTA_CT_DIRECTION, NFTA_CT_DREG, NFTA_CT_KEY, NFTA_CT_SREG, NFT_CT_AVGPKT, NFT_CT_BYTES,
+    NFT_CT_DIRECTION, NFT_CT_DST, NFT_CT_DST_IP, NFT_CT_DST_IP6, NFT_CT_EVENTMASK,
+    NFT_CT_EXPIRATION, NFT_CT_HELPER, NFT_CT_ID, NFT_CT_L3PROTOCOL, NFT_CT_LABELS, NFT_CT_MARK,
+    NFT_CT_PKTS, NFT_CT_PROTOCOL, NFT_CT_PROTO_DST, NFT_CT_PROTO_SRC, NFT_CT_SECMARK, NFT_CT_SRC,
+    NFT_CT_SRC_IP, NFT_CT_SRC_IP6, NFT_CT_STATE, NFT_CT_STATUS, NFT_CT_ZONE,
 };
 
 use super::{Expression, Register};
@@ -16,11 +20,130 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// The status bits of a connection, as loaded with [`ConntrackKey::Status`].
+    pub struct ConnTrackStatus: u32 {
+        const EXPECTED = 1;
+        const SEEN_REPLY = 2;
+        const ASSURED = 4;
+        const CONFIRMED = 8;
+        const SNAT = 16;
+        const DNAT = 32;
+        const DYING = 512;
+    }
+}
+
+bitflags::bitflags! {
+    /// The conntrack events reported to userspace for a connection, as set with
+    /// [`ConntrackKey::EventMask`].
+    pub struct ConnTrackEvents: u32 {
+        const NEW = 1 << 0;
+        const RELATED = 1 << 1;
+        const DESTROY = 1 << 2;
+        const REPLY = 1 << 3;
+        const ASSURED = 1 << 4;
+        const PROTOINFO = 1 << 5;
+        const HELPER = 1 << 6;
+        const MARK = 1 << 7;
+        const SEQADJ = 1 << 8;
+        const SECMARK = 1 << 9;
+        const LABEL = 1 << 10;
+    }
+}
+
+// from the ip_conntrack_dir enum of the kernel
+const IP_CT_DIR_ORIGINAL: u8 = 0;
+const IP_CT_DIR_REPLY: u8 = 1;
+
+/// The direction of a connection some conntrack information is retrieved for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum CtDirection {
+    /// The direction of the packet that created the connection.
+    Original = IP_CT_DIR_ORIGINAL,
+    /// The direction of the replies.
+    Reply = IP_CT_DIR_REPLY,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[nfnetlink_enum(u32, nested = true)]
 pub enum ConntrackKey {
+    /// State of the connection, see [`ConnTrackState`].
     State = NFT_CT_STATE,
+    /// Direction of the packet in the connection, see [`CtDirection`].
+    Direction = NFT_CT_DIRECTION,
+    /// Status of the connection, see [`ConnTrackStatus`].
+    Status = NFT_CT_STATUS,
     Mark = NFT_CT_MARK,
+    /// Remaining lifetime of the connection, in milliseconds.
+    Expiration = NFT_CT_EXPIRATION,
+    /// Name of the helper assigned to the connection.
+    Helper = NFT_CT_HELPER,
+    /// Layer 3 protocol of the connection (`NFPROTO_*` constants).
+    L3Protocol = NFT_CT_L3PROTOCOL,
+    /// Source address in the given direction. Only available in `ip` and `ip6` tables, use
+    /// `SrcIp` or `SrcIp6` in `inet` tables.
+    Src = NFT_CT_SRC,
+    /// Destination address in the given direction. Only available in `ip` and `ip6` tables,
+    /// use `DstIp` or `DstIp6` in `inet` tables.
+    Dst = NFT_CT_DST,
+    /// Layer 4 protocol of the connection.
+    Protocol = NFT_CT_PROTOCOL,
+    /// Source port in the given direction.
+    ProtoSrc = NFT_CT_PROTO_SRC,
+    /// Destination port in the given direction.
+    ProtoDst = NFT_CT_PROTO_DST,
+    /// Bitmap of the labels of the connection.
+    Labels = NFT_CT_LABELS,
+    /// Number of packets seen, in the given direction or in both.
+    Pkts = NFT_CT_PKTS,
+    /// Number of bytes seen, in the given direction or in both.
+    Bytes = NFT_CT_BYTES,
+    /// Average size of the packets, in the given direction or in both.
+    AvgPkt = NFT_CT_AVGPKT,
+    /// Conntrack zone of the connection.
+    Zone = NFT_CT_ZONE,
+    /// Events reported to userspace for the connection, see [`ConnTrackEvents`].
+    EventMask = NFT_CT_EVENTMASK,
+    /// IPv4 source address in the given direction.
+    SrcIp = NFT_CT_SRC_IP,
+    /// IPv4 destination address in the given direction.
+    DstIp = NFT_CT_DST_IP,
+    /// IPv6 source address in the given direction.
+    SrcIp6 = NFT_CT_SRC_IP6,
+    /// IPv6 destination address in the given direction.
+    DstIp6 = NFT_CT_DST_IP6,
+    /// Identifier of the connection.
+    Id = NFT_CT_ID,
+    /// Security mark of the connection, see [`MetaType::Secmark`](super::MetaType::Secmark).
+    Secmark = NFT_CT_SECMARK,
+}
+
+impl ConntrackKey {
+    /// Length in bytes of the value loaded in a register for this key. The addresses loaded for
+    /// `Src` and `Dst` are only 4 bytes long in `ip` tables.
+    pub fn value_len(&self) -> u32 {
+        match *self {
+            ConntrackKey::Direction | ConntrackKey::L3Protocol | ConntrackKey::Protocol => 1,
+            ConntrackKey::ProtoSrc | ConntrackKey::ProtoDst | ConntrackKey::Zone => 2,
+            ConntrackKey::State
+            | ConntrackKey::Status
+            | ConntrackKey::Mark
+            | ConntrackKey::Expiration
+            | ConntrackKey::EventMask
+            | ConntrackKey::SrcIp
+            | ConntrackKey::DstIp
+            | ConntrackKey::Id
+            | ConntrackKey::Secmark => 4,
+            ConntrackKey::Pkts | ConntrackKey::Bytes | ConntrackKey::AvgPkt => 8,
+            ConntrackKey::Helper
+            | ConntrackKey::Src
+            | ConntrackKey::Dst
+            | ConntrackKey::Labels
+            | ConntrackKey::SrcIp6
+            | ConntrackKey::DstIp6 => 16,
+        }
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
@@ -31,7 +154,7 @@ pub struct Conntrack {
     #[field(NFTA_CT_KEY)]
     pub key: ConntrackKey,
     #[field(NFTA_CT_DIRECTION)]
-    pub direction: u8,
+    pub direction: CtDirection,
     #[field(NFTA_CT_SREG)]
     pub sreg: Register,
 }
@@ -47,6 +170,12 @@ impl Conntrack {
         Self::default().with_dreg(Register::Reg1).with_key(key)
     }
 
+    /// Sets the conntrack information `key` of the connection to the value stored in `sreg`.
+    /// Only `Mark`, `Labels`, `Zone`, `EventMask` and `Secmark` can be set.
+    pub fn new_set(key: ConntrackKey, sreg: Register) -> Self {
+        Self::default().with_sreg(sreg).with_key(key)
+    }
+
     pub fn set_mark_value(&mut self, reg: Register) {
         self.set_sreg(reg);
         self.set_key(ConntrackKey::Mark);
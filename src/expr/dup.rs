@@ -0,0 +1,22 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::{Expression, Register};
+use crate::sys::{NFTA_DUP_SREG_ADDR, NFTA_DUP_SREG_DEV};
+
+/// A dup expression sends a copy of the packet to the address stored in `sreg_addr`, through the
+/// interface whose index is stored in `sreg_dev`. In `netdev` tables, the copy is sent as is
+/// through the interface, and the address is not used.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Dup {
+    #[field(NFTA_DUP_SREG_ADDR)]
+    sreg_addr: Register,
+    #[field(NFTA_DUP_SREG_DEV)]
+    sreg_dev: Register,
+}
+
+impl Expression for Dup {
+    fn get_name() -> &'static str {
+        "dup"
+    }
+}
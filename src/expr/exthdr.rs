@@ -0,0 +1,368 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, HeaderField, Register};
+use crate::sys::{
+    NFTA_EXTHDR_DREG, NFTA_EXTHDR_FLAGS, NFTA_EXTHDR_LEN, NFTA_EXTHDR_OFFSET, NFTA_EXTHDR_OP,
+    NFTA_EXTHDR_SREG, NFTA_EXTHDR_TYPE, NFT_EXTHDR_F_PRESENT, NFT_EXTHDR_OP_DCCP,
+    NFT_EXTHDR_OP_IPV4, NFT_EXTHDR_OP_IPV6, NFT_EXTHDR_OP_SCTP, NFT_EXTHDR_OP_TCPOPT,
+};
+
+/// The kind of header looked up by an [`Exthdr`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum ExthdrOp {
+    /// IPv6 extension headers.
+    IPv6 = NFT_EXTHDR_OP_IPV6,
+    /// TCP options.
+    TcpOpt = NFT_EXTHDR_OP_TCPOPT,
+    /// IPv4 options.
+    IPv4 = NFT_EXTHDR_OP_IPV4,
+    /// SCTP chunks.
+    Sctp = NFT_EXTHDR_OP_SCTP,
+    /// DCCP options.
+    Dccp = NFT_EXTHDR_OP_DCCP,
+}
+
+bitflags::bitflags! {
+    /// The flags of an [`Exthdr`] expression.
+    pub struct ExthdrFlags: u32 {
+        /// Only load whether the header or option is present, as a boolean.
+        const PRESENT = NFT_EXTHDR_F_PRESENT;
+    }
+}
+
+/// An exthdr expression loads a field of an IPv6 extension header, of a TCP or IPv4 option or of
+/// a SCTP chunk into a register, or overwrites it with the content of a register.
+///
+/// Unlike [`Payload`] expressions, the offset is relative to the start of the header or option,
+/// wherever it is in the packet.
+///
+/// [`Payload`]: super::Payload
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Exthdr {
+    #[field(NFTA_EXTHDR_DREG)]
+    dreg: Register,
+    #[field(NFTA_EXTHDR_TYPE, name_in_functions = "type")]
+    exthdr_type: u8,
+    #[field(NFTA_EXTHDR_OFFSET)]
+    offset: u32,
+    #[field(NFTA_EXTHDR_LEN)]
+    len: u32,
+    #[field(NFTA_EXTHDR_FLAGS)]
+    flags: u32,
+    #[field(NFTA_EXTHDR_OP)]
+    op: ExthdrOp,
+    #[field(NFTA_EXTHDR_SREG)]
+    sreg: Register,
+}
+
+impl Expression for Exthdr {
+    fn get_name() -> &'static str {
+        "exthdr"
+    }
+}
+
+/// A field of a header or option that can be accessed with an [`Exthdr`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExthdrField {
+    IPv6(IPv6ExtHeaderField),
+    Tcp(TcpOptionField),
+    IPv4(IPv4OptionField),
+    Sctp(SctpChunkField),
+}
+
+impl ExthdrField {
+    /// The kind of header holding the field.
+    pub fn op(&self) -> ExthdrOp {
+        match *self {
+            ExthdrField::IPv6(_) => ExthdrOp::IPv6,
+            ExthdrField::Tcp(_) => ExthdrOp::TcpOpt,
+            ExthdrField::IPv4(_) => ExthdrOp::IPv4,
+            ExthdrField::Sctp(_) => ExthdrOp::Sctp,
+        }
+    }
+
+    /// The type of the header, option or chunk holding the field.
+    pub fn kind(&self) -> u8 {
+        match *self {
+            ExthdrField::IPv6(ref f) => f.kind(),
+            ExthdrField::Tcp(ref f) => f.kind(),
+            ExthdrField::IPv4(ref f) => f.kind(),
+            ExthdrField::Sctp(ref f) => f.kind(),
+        }
+    }
+
+    fn field(&self) -> &dyn HeaderField {
+        match *self {
+            ExthdrField::IPv6(ref f) => f,
+            ExthdrField::Tcp(ref f) => f,
+            ExthdrField::IPv4(ref f) => f,
+            ExthdrField::Sctp(ref f) => f,
+        }
+    }
+
+    fn build_raw(&self) -> Exthdr {
+        Exthdr::default()
+            .with_op(self.op())
+            .with_type(self.kind())
+            .with_offset(self.field().offset())
+            .with_len(self.field().len())
+    }
+
+    /// Loads the field into `Reg1`.
+    pub fn build(&self) -> Exthdr {
+        self.build_raw().with_dreg(Register::Reg1)
+    }
+
+    /// Loads into `Reg1` a boolean telling whether the header, option or chunk holding the field
+    /// is present in the packet.
+    pub fn build_exists(&self) -> Exthdr {
+        self.build_raw()
+            .with_offset(0u32)
+            .with_len(1u32)
+            .with_flags(ExthdrFlags::PRESENT.bits())
+            .with_dreg(Register::Reg1)
+    }
+
+    /// Overwrites the field with the content of `sreg`. The kernel only supports writing TCP
+    /// options, and takes care of updating the checksum.
+    pub fn build_write(&self, sreg: Register) -> Exthdr {
+        self.build_raw().with_sreg(sreg)
+    }
+}
+
+/// The IPv6 extension headers, identified by their next header value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IPv6ExtHeader {
+    HopByHop,
+    Routing,
+    Fragment,
+    DestOptions,
+    Mobility,
+}
+
+impl IPv6ExtHeader {
+    pub fn kind(&self) -> u8 {
+        (match *self {
+            IPv6ExtHeader::HopByHop => libc::IPPROTO_HOPOPTS,
+            IPv6ExtHeader::Routing => libc::IPPROTO_ROUTING,
+            IPv6ExtHeader::Fragment => libc::IPPROTO_FRAGMENT,
+            IPv6ExtHeader::DestOptions => libc::IPPROTO_DSTOPTS,
+            IPv6ExtHeader::Mobility => libc::IPPROTO_MH,
+        }) as u8
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IPv6ExtHeaderField {
+    HopByHopNextHeader,
+    HopByHopHdrLength,
+    RoutingNextHeader,
+    RoutingHdrLength,
+    RoutingType,
+    RoutingSegmentsLeft,
+    FragmentNextHeader,
+    FragmentId,
+    DestOptionsNextHeader,
+    DestOptionsHdrLength,
+    MobilityNextHeader,
+    MobilityHdrLength,
+    MobilityType,
+    MobilityChecksum,
+}
+
+impl IPv6ExtHeaderField {
+    /// The extension header holding the field.
+    pub fn header(&self) -> IPv6ExtHeader {
+        use self::IPv6ExtHeaderField::*;
+        match *self {
+            HopByHopNextHeader | HopByHopHdrLength => IPv6ExtHeader::HopByHop,
+            RoutingNextHeader | RoutingHdrLength | RoutingType | RoutingSegmentsLeft => {
+                IPv6ExtHeader::Routing
+            }
+            FragmentNextHeader | FragmentId => IPv6ExtHeader::Fragment,
+            DestOptionsNextHeader | DestOptionsHdrLength => IPv6ExtHeader::DestOptions,
+            MobilityNextHeader | MobilityHdrLength | MobilityType | MobilityChecksum => {
+                IPv6ExtHeader::Mobility
+            }
+        }
+    }
+
+    pub fn kind(&self) -> u8 {
+        self.header().kind()
+    }
+}
+
+impl HeaderField for IPv6ExtHeaderField {
+    fn offset(&self) -> u32 {
+        use self::IPv6ExtHeaderField::*;
+        match *self {
+            HopByHopNextHeader
+            | RoutingNextHeader
+            | FragmentNextHeader
+            | DestOptionsNextHeader
+            | MobilityNextHeader => 0,
+            HopByHopHdrLength | RoutingHdrLength | DestOptionsHdrLength | MobilityHdrLength => 1,
+            RoutingType | MobilityType => 2,
+            RoutingSegmentsLeft => 3,
+            FragmentId | MobilityChecksum => 4,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::IPv6ExtHeaderField::*;
+        match *self {
+            FragmentId => 4,
+            MobilityChecksum => 2,
+            _ => 1,
+        }
+    }
+}
+
+// TCP option kinds, as defined in the IANA registry.
+const TCPOPT_MAXSEG: u8 = 2;
+const TCPOPT_WINDOW: u8 = 3;
+const TCPOPT_SACK: u8 = 5;
+const TCPOPT_TIMESTAMP: u8 = 8;
+
+/// The fields of the TCP options.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TcpOptionField {
+    /// Kind of the option with the given kind, mostly useful to check for its presence.
+    Kind(u8),
+    /// Length of the option with the given kind.
+    Length(u8),
+    /// Maximum segment size.
+    MaxSegSize,
+    /// Window scale shift count.
+    WindowCount,
+    /// Left edge of the given block (0 to 3) of a selective acknowledgement.
+    SackLeft(u8),
+    /// Right edge of the given block (0 to 3) of a selective acknowledgement.
+    SackRight(u8),
+    /// Timestamp value.
+    TimestampValue,
+    /// Timestamp echo reply.
+    TimestampEcho,
+}
+
+impl TcpOptionField {
+    pub fn kind(&self) -> u8 {
+        use self::TcpOptionField::*;
+        match *self {
+            Kind(kind) | Length(kind) => kind,
+            MaxSegSize => TCPOPT_MAXSEG,
+            WindowCount => TCPOPT_WINDOW,
+            SackLeft(_) | SackRight(_) => TCPOPT_SACK,
+            TimestampValue | TimestampEcho => TCPOPT_TIMESTAMP,
+        }
+    }
+}
+
+impl HeaderField for TcpOptionField {
+    fn offset(&self) -> u32 {
+        use self::TcpOptionField::*;
+        match *self {
+            Kind(_) => 0,
+            Length(_) => 1,
+            MaxSegSize | WindowCount | TimestampValue => 2,
+            SackLeft(block) => 2 + 8 * block as u32,
+            SackRight(block) => 6 + 8 * block as u32,
+            TimestampEcho => 6,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::TcpOptionField::*;
+        match *self {
+            Kind(_) | Length(_) | WindowCount => 1,
+            MaxSegSize => 2,
+            SackLeft(_) | SackRight(_) | TimestampValue | TimestampEcho => 4,
+        }
+    }
+}
+
+const IPOPT_RA: u8 = 148;
+
+/// The fields of the IPv4 options.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IPv4OptionField {
+    /// Type of the option with the given type, mostly useful to check for its presence.
+    Type(u8),
+    /// Length of the option with the given type.
+    Length(u8),
+    /// Pointer of the route recording option with the given type (LSRR, SSRR or RR).
+    Pointer(u8),
+    /// Value of the router alert option.
+    RouterAlert,
+}
+
+impl IPv4OptionField {
+    pub fn kind(&self) -> u8 {
+        use self::IPv4OptionField::*;
+        match *self {
+            Type(kind) | Length(kind) | Pointer(kind) => kind,
+            RouterAlert => IPOPT_RA,
+        }
+    }
+}
+
+impl HeaderField for IPv4OptionField {
+    fn offset(&self) -> u32 {
+        use self::IPv4OptionField::*;
+        match *self {
+            Type(_) => 0,
+            Length(_) => 1,
+            Pointer(_) | RouterAlert => 2,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::IPv4OptionField::*;
+        match *self {
+            Type(_) | Length(_) | Pointer(_) => 1,
+            RouterAlert => 2,
+        }
+    }
+}
+
+/// The common fields of the SCTP chunks, for the chunk of the given type.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SctpChunkField {
+    Type(u8),
+    Flags(u8),
+    Length(u8),
+}
+
+impl SctpChunkField {
+    pub fn kind(&self) -> u8 {
+        use self::SctpChunkField::*;
+        match *self {
+            Type(kind) | Flags(kind) | Length(kind) => kind,
+        }
+    }
+}
+
+impl HeaderField for SctpChunkField {
+    fn offset(&self) -> u32 {
+        use self::SctpChunkField::*;
+        match *self {
+            Type(_) => 0,
+            Flags(_) => 1,
+            Length(_) => 2,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::SctpChunkField::*;
+        match *self {
+            Type(_) | Flags(_) => 1,
+            Length(_) => 2,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_FIB_DREG, NFTA_FIB_FLAGS, NFTA_FIB_F_DADDR, NFTA_FIB_F_IIF, NFTA_FIB_F_MARK,
+    NFTA_FIB_F_OIF, NFTA_FIB_F_PRESENT, NFTA_FIB_F_SADDR, NFTA_FIB_RESULT, NFT_FIB_RESULT_ADDRTYPE,
+    NFT_FIB_RESULT_OIF, NFT_FIB_RESULT_OIFNAME,
+};
+
+bitflags::bitflags! {
+    /// The keys of the route lookup performed by a [`Fib`] expression.
+    pub struct FibFlags: u32 {
+        /// Look up the source address of the packet.
+        const SADDR = NFTA_FIB_F_SADDR;
+        /// Look up the destination address of the packet.
+        const DADDR = NFTA_FIB_F_DADDR;
+        /// Take the mark of the packet into account.
+        const MARK = NFTA_FIB_F_MARK;
+        /// Restrict the lookup to the input interface of the packet.
+        const IIF = NFTA_FIB_F_IIF;
+        /// Restrict the lookup to the output interface of the packet.
+        const OIF = NFTA_FIB_F_OIF;
+        /// Only store whether the lookup succeeded, as a boolean.
+        const PRESENT = NFTA_FIB_F_PRESENT;
+    }
+}
+
+/// The information retrieved by a [`Fib`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum FibResult {
+    /// Index of the output interface of the route.
+    Oif = NFT_FIB_RESULT_OIF,
+    /// Name of the output interface of the route.
+    OifName = NFT_FIB_RESULT_OIFNAME,
+    /// Type of the looked up address (`RTN_*` constants).
+    AddrType = NFT_FIB_RESULT_ADDRTYPE,
+}
+
+/// A fib expression looks up a route in the forwarding information base, for instance to
+/// implement reverse path filtering.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Fib {
+    #[field(NFTA_FIB_DREG)]
+    dreg: Register,
+    #[field(NFTA_FIB_RESULT)]
+    result: FibResult,
+    #[field(NFTA_FIB_FLAGS)]
+    flags: u32,
+}
+
+impl Fib {
+    pub fn new(flags: FibFlags, result: FibResult) -> Self {
+        Fib::default()
+            .with_dreg(Register::Reg1)
+            .with_result(result)
+            .with_flags(flags.bits())
+    }
+}
+
+impl Expression for Fib {
+    fn get_name() -> &'static str {
+        "fib"
+    }
+}
@@ -0,0 +1,28 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::{Expression, Register};
+use crate::sys::{NFTA_FWD_NFPROTO, NFTA_FWD_SREG_ADDR, NFTA_FWD_SREG_DEV};
+use crate::ProtocolFamily;
+
+/// A fwd expression sends the packet through the interface whose index is stored in `sreg_dev`,
+/// bypassing the rest of the network stack. It is only available in the ingress hook of `netdev`
+/// tables.
+///
+/// When `sreg_addr` and `nfproto` are set, the packet is sent to the neighbour with the address
+/// stored in `sreg_addr`, after decrementing its TTL.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Fwd {
+    #[field(NFTA_FWD_SREG_DEV)]
+    sreg_dev: Register,
+    #[field(NFTA_FWD_SREG_ADDR)]
+    sreg_addr: Register,
+    #[field(NFTA_FWD_NFPROTO)]
+    nfproto: ProtocolFamily,
+}
+
+impl Expression for Fwd {
+    fn get_name() -> &'static str {
+        "fwd"
+    }
+}
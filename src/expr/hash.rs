@@ -0,0 +1,65 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_HASH_DREG, NFTA_HASH_LEN, NFTA_HASH_MODULUS, NFTA_HASH_OFFSET, NFTA_HASH_SEED,
+    NFTA_HASH_SREG, NFTA_HASH_TYPE, NFT_HASH_JENKINS, NFT_HASH_SYM,
+};
+
+/// The hash function computed by a [`Hash`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum HashType {
+    /// Jenkins hash of the content of a register.
+    Jenkins = NFT_HASH_JENKINS,
+    /// Hash of the flow of the packet, which is the same in both directions.
+    Sym = NFT_HASH_SYM,
+}
+
+/// A hash expression stores in a register a hash of the packet, modulo `modulus`, plus `offset`.
+/// Along with a map, it can be used to spread the traffic over several backends.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Hash {
+    #[field(NFTA_HASH_SREG)]
+    sreg: Register,
+    #[field(NFTA_HASH_DREG)]
+    dreg: Register,
+    #[field(NFTA_HASH_LEN)]
+    len: u32,
+    #[field(NFTA_HASH_MODULUS)]
+    modulus: u32,
+    #[field(NFTA_HASH_SEED)]
+    seed: u32,
+    #[field(NFTA_HASH_OFFSET)]
+    offset: u32,
+    #[field(NFTA_HASH_TYPE, name_in_functions = "type")]
+    hash_type: HashType,
+}
+
+impl Hash {
+    /// Hashes the `len` bytes stored in `sreg` with the Jenkins hash function. Unless a seed is
+    /// set, the kernel picks a random one.
+    pub fn new_jhash(sreg: Register, len: u32, modulus: u32) -> Self {
+        Hash::default()
+            .with_type(HashType::Jenkins)
+            .with_sreg(sreg)
+            .with_len(len)
+            .with_modulus(modulus)
+            .with_dreg(Register::Reg1)
+    }
+
+    /// Hashes the flow of the packet, so that both directions of a connection get the same hash.
+    pub fn new_symhash(modulus: u32) -> Self {
+        Hash::default()
+            .with_type(HashType::Sym)
+            .with_modulus(modulus)
+            .with_dreg(Register::Reg1)
+    }
+}
+
+impl Expression for Hash {
+    fn get_name() -> &'static str {
+        "hash"
+    }
+}
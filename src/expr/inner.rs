@@ -0,0 +1,140 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, HighLevelPayload, Meta, Payload, RawExpression};
+
+// from linux/netfilter/nf_tables.h, not yet in the headers the bindings are generated from
+const NFTA_INNER_NUM: u16 = 1;
+const NFTA_INNER_TYPE: u16 = 2;
+const NFTA_INNER_FLAGS: u16 = 3;
+const NFTA_INNER_HDRSIZE: u16 = 4;
+const NFTA_INNER_EXPR: u16 = 5;
+
+const NFT_INNER_UNSPEC: u32 = 0;
+const NFT_INNER_VXLAN: u32 = 1;
+const NFT_INNER_GENEVE: u32 = 2;
+
+const NFT_INNER_HDRSIZE: u32 = 1 << 0;
+const NFT_INNER_LL: u32 = 1 << 1;
+const NFT_INNER_NH: u32 = 1 << 2;
+const NFT_INNER_TH: u32 = 1 << 3;
+
+/// How the kernel parses the tunnel header preceding the inner packet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum InnerType {
+    /// A tunnel header of a fixed size, e.g. GRE.
+    Generic = NFT_INNER_UNSPEC,
+    Vxlan = NFT_INNER_VXLAN,
+    /// A Geneve header, whose options are skipped.
+    Geneve = NFT_INNER_GENEVE,
+}
+
+bitflags::bitflags! {
+    /// The headers of the inner packet an [`Inner`] expression locates.
+    pub struct InnerFlags: u32 {
+        /// Skip the tunnel header, of the size set in the expression.
+        const HDRSIZE = NFT_INNER_HDRSIZE;
+        /// The inner packet starts with a link layer header.
+        const LL = NFT_INNER_LL;
+        const NH = NFT_INNER_NH;
+        const TH = NFT_INNER_TH;
+    }
+}
+
+/// The encapsulations whose inner packets can be matched with an [`Inner`] expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encapsulation {
+    /// VXLAN over UDP, to port 4789.
+    Vxlan,
+    /// Geneve over UDP, to port 6081.
+    Geneve,
+    /// IP in GRE.
+    Gre,
+    /// Ethernet in GRE.
+    Gretap,
+}
+
+impl Encapsulation {
+    fn inner_type(&self) -> InnerType {
+        match *self {
+            Encapsulation::Vxlan => InnerType::Vxlan,
+            Encapsulation::Geneve => InnerType::Geneve,
+            Encapsulation::Gre | Encapsulation::Gretap => InnerType::Generic,
+        }
+    }
+
+    fn flags(&self) -> InnerFlags {
+        match *self {
+            Encapsulation::Gre => InnerFlags::HDRSIZE | InnerFlags::NH | InnerFlags::TH,
+            _ => InnerFlags::all(),
+        }
+    }
+
+    /// Size of the tunnel header, without the Geneve options.
+    fn hdrsize(&self) -> u32 {
+        match *self {
+            Encapsulation::Vxlan | Encapsulation::Geneve => 8,
+            Encapsulation::Gre | Encapsulation::Gretap => 4,
+        }
+    }
+}
+
+/// An inner expression evaluates a payload expression against the headers of the packet
+/// encapsulated in a tunnel, rather than those of the packet itself (`vxlan ip saddr`).
+///
+/// The expression does not check that the packet is actually encapsulated: the rule must match
+/// the outer headers beforehand, as [`Rule::match_inner`] does.
+///
+/// [`Rule::match_inner`]: crate::Rule::match_inner
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Inner {
+    /// Depth of the encapsulation, only 0 is supported by the kernel.
+    #[field(NFTA_INNER_NUM)]
+    num: u32,
+    #[field(NFTA_INNER_TYPE, name_in_functions = "type")]
+    inner_type: InnerType,
+    /// See [`InnerFlags`].
+    #[field(NFTA_INNER_FLAGS)]
+    flags: u32,
+    #[field(NFTA_INNER_HDRSIZE)]
+    hdrsize: u32,
+    #[field(NFTA_INNER_EXPR)]
+    expr: Box<RawExpression>,
+}
+
+impl Inner {
+    /// Returns an inner expression loading `field` from the packet encapsulated with `encap`
+    /// into the first register.
+    pub fn new(encap: Encapsulation, field: HighLevelPayload) -> Self {
+        Self::new_payload(encap, field.build())
+    }
+
+    /// Returns an inner expression evaluating `payload` against the packet encapsulated with
+    /// `encap`.
+    pub fn new_payload(encap: Encapsulation, payload: Payload) -> Self {
+        Self::new_expr(encap, RawExpression::from(payload))
+    }
+
+    /// Returns an inner expression evaluating `meta` against the packet encapsulated with
+    /// `encap`. The kernel only supports the protocol and the layer 4 protocol of the inner
+    /// packet.
+    pub fn new_meta(encap: Encapsulation, meta: Meta) -> Self {
+        Self::new_expr(encap, RawExpression::from(meta))
+    }
+
+    fn new_expr(encap: Encapsulation, expr: RawExpression) -> Self {
+        Inner::default()
+            .with_num(0u32)
+            .with_type(encap.inner_type())
+            .with_flags(encap.flags().bits())
+            .with_hdrsize(encap.hdrsize())
+            .with_expr(Box::new(expr))
+    }
+}
+
+impl Expression for Inner {
+    fn get_name() -> &'static str {
+        "inner"
+    }
+}
@@ -0,0 +1,21 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::Expression;
+
+/// A last expression records when the rule was last matched.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Last {
+    /// Whether the rule was matched at least once.
+    #[field(optional = true, crate::sys::NFTA_LAST_SET)]
+    set: u32,
+    /// Number of milliseconds since the rule was last matched.
+    #[field(optional = true, crate::sys::NFTA_LAST_MSECS)]
+    msecs: u64,
+}
+
+impl Expression for Last {
+    fn get_name() -> &'static str {
+        "last"
+    }
+}
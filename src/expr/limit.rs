@@ -0,0 +1,100 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::Expression;
+use crate::sys::{
+    NFTA_LIMIT_BURST, NFTA_LIMIT_FLAGS, NFTA_LIMIT_RATE, NFTA_LIMIT_TYPE, NFTA_LIMIT_UNIT,
+    NFT_LIMIT_F_INV, NFT_LIMIT_PKTS, NFT_LIMIT_PKT_BYTES,
+};
+
+/// What a [`Limit`] counts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum LimitType {
+    /// The rate is a number of packets.
+    Packets = NFT_LIMIT_PKTS,
+    /// The rate is a number of bytes.
+    Bytes = NFT_LIMIT_PKT_BYTES,
+}
+
+/// The time unit of the rate of a [`Limit`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LimitUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+}
+
+impl LimitUnit {
+    /// Returns the duration of the unit, in seconds, as expected by the kernel.
+    pub fn seconds(&self) -> u64 {
+        match *self {
+            LimitUnit::Second => 1,
+            LimitUnit::Minute => 60,
+            LimitUnit::Hour => 60 * 60,
+            LimitUnit::Day => 60 * 60 * 24,
+            LimitUnit::Week => 60 * 60 * 24 * 7,
+        }
+    }
+}
+
+/// A limit expression matches packets until the rate limit is reached, using a token bucket.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Limit {
+    #[field(NFTA_LIMIT_RATE)]
+    rate: u64,
+    /// Duration of the period of the rate, in seconds.
+    #[field(NFTA_LIMIT_UNIT)]
+    unit: u64,
+    #[field(NFTA_LIMIT_BURST)]
+    burst: u32,
+    #[field(NFTA_LIMIT_TYPE)]
+    limit_type: LimitType,
+    #[field(NFTA_LIMIT_FLAGS)]
+    flags: u32,
+}
+
+impl Limit {
+    /// Returns a limit matching at most `rate` packets per `unit`, with a burst of 5 packets
+    /// like nftables.
+    pub fn new(rate: u64, unit: LimitUnit) -> Self {
+        Limit::default()
+            .with_rate(rate)
+            .with_unit(unit.seconds())
+            .with_burst(5u32)
+            .with_limit_type(LimitType::Packets)
+    }
+
+    /// Returns a limit matching at most `rate` bytes per `unit`, with a burst of `burst` bytes.
+    pub fn new_bytes(rate: u64, unit: LimitUnit, burst: u32) -> Self {
+        Limit::default()
+            .with_rate(rate)
+            .with_unit(unit.seconds())
+            .with_burst(burst)
+            .with_limit_type(LimitType::Bytes)
+    }
+
+    /// Inverts the limit, to match the packets over the rate instead of those under it.
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        let flags = self.get_flags().copied().unwrap_or(0);
+        self.set_flags(if inverted {
+            flags | NFT_LIMIT_F_INV
+        } else {
+            flags & !NFT_LIMIT_F_INV
+        });
+        self
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.get_flags()
+            .is_some_and(|flags| flags & NFT_LIMIT_F_INV != 0)
+    }
+}
+
+impl Expression for Limit {
+    fn get_name() -> &'static str {
+        "limit"
+    }
+}
@@ -1,19 +1,71 @@
-use rustables_macros::nfnetlink_struct;
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
 
 use super::Expression;
 use crate::{
     error::BuilderError,
-    sys::{NFTA_LOG_GROUP, NFTA_LOG_PREFIX},
+    sys::{
+        NFTA_LOG_FLAGS, NFTA_LOG_GROUP, NFTA_LOG_LEVEL, NFTA_LOG_PREFIX, NFTA_LOG_QTHRESHOLD,
+        NFTA_LOG_SNAPLEN, NFT_LOGLEVEL_ALERT, NFT_LOGLEVEL_AUDIT, NFT_LOGLEVEL_CRIT,
+        NFT_LOGLEVEL_DEBUG, NFT_LOGLEVEL_EMERG, NFT_LOGLEVEL_ERR, NFT_LOGLEVEL_INFO,
+        NFT_LOGLEVEL_NOTICE, NFT_LOGLEVEL_WARNING,
+    },
 };
 
+/// The level of the messages logged to the kernel log.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum LogLevel {
+    Emerg = NFT_LOGLEVEL_EMERG,
+    Alert = NFT_LOGLEVEL_ALERT,
+    Crit = NFT_LOGLEVEL_CRIT,
+    Err = NFT_LOGLEVEL_ERR,
+    Warning = NFT_LOGLEVEL_WARNING,
+    Notice = NFT_LOGLEVEL_NOTICE,
+    Info = NFT_LOGLEVEL_INFO,
+    Debug = NFT_LOGLEVEL_DEBUG,
+    /// Sends the messages to the audit subsystem instead of the kernel log.
+    Audit = NFT_LOGLEVEL_AUDIT,
+}
+
+bitflags::bitflags! {
+    /// The information added to the messages logged to the kernel log, from the `NF_LOG_*`
+    /// constants of the kernel.
+    pub struct LogFlags: u32 {
+        /// The TCP sequence numbers.
+        const TCP_SEQ = 0x01;
+        /// The TCP options.
+        const TCP_OPT = 0x02;
+        /// The IP options.
+        const IP_OPT = 0x04;
+        /// The user and group of the socket that sent the packet.
+        const UID = 0x08;
+        /// The decoded link layer header.
+        const MAC_DECODE = 0x20;
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Default, Debug, Hash)]
 #[nfnetlink_struct]
 /// A Log expression will log all packets that match the rule.
+///
+/// The packets are either sent to the kernel log, with a `level` and `flags`, or to the
+/// userspace programs listening on the NFLOG `group`, with a `snaplen` and a `qthreshold`.
 pub struct Log {
     #[field(NFTA_LOG_GROUP)]
     group: u16,
     #[field(NFTA_LOG_PREFIX)]
     prefix: String,
+    /// Number of bytes of the packets copied to the NFLOG group.
+    #[field(NFTA_LOG_SNAPLEN)]
+    snaplen: u32,
+    /// Number of packets queued in the kernel before being sent to the NFLOG group.
+    #[field(NFTA_LOG_QTHRESHOLD)]
+    qthreshold: u16,
+    #[field(NFTA_LOG_LEVEL)]
+    level: LogLevel,
+    /// See [`LogFlags`].
+    #[field(NFTA_LOG_FLAGS)]
+    flags: u32,
 }
 
 impl Log {
@@ -32,10 +84,54 @@ impl Log {
         }
         Ok(res)
     }
+
+    /// Returns a log expression sending the packets to the kernel log at `level`, along with the
+    /// information given by `flags` (`log level warn flags tcp sequence`).
+    pub fn new_syslog(
+        level: LogLevel,
+        flags: LogFlags,
+        prefix: Option<impl Into<String>>,
+    ) -> Result<Log, BuilderError> {
+        let mut res = Log::new(None, prefix)?.with_level(level);
+        if !flags.is_empty() {
+            res.set_flags(flags.bits());
+        }
+        Ok(res)
+    }
+
+    /// Returns a log expression sending the first `snaplen` bytes of the packets to the NFLOG
+    /// `group`, in batches of `qthreshold` packets (`log group 2 snaplen 128`).
+    pub fn new_nflog(
+        group: u16,
+        snaplen: Option<u32>,
+        qthreshold: Option<u16>,
+        prefix: Option<impl Into<String>>,
+    ) -> Result<Log, BuilderError> {
+        let mut res = Log::new(Some(group), prefix)?;
+        if let Some(snaplen) = snaplen {
+            res.set_snaplen(snaplen);
+        }
+        if let Some(qthreshold) = qthreshold {
+            res.set_qthreshold(qthreshold);
+        }
+        Ok(res)
+    }
 }
 
 impl Expression for Log {
     fn get_name() -> &'static str {
         "log"
     }
+
+    /// A group cannot be combined with a level or flags, nor can a snaplen or a queue threshold
+    /// be set without a group.
+    fn validate(&self) -> Result<(), BuilderError> {
+        let syslog = self.level.is_some() || self.flags.is_some();
+        let nflog = self.snaplen.is_some() || self.qthreshold.is_some();
+        match self.group {
+            Some(_) if syslog => Err(BuilderError::IncompatibleLogOptions),
+            None if nflog => Err(BuilderError::IncompatibleLogOptions),
+            _ => Ok(()),
+        }
+    }
 }
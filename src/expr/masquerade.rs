@@ -1,16 +1,22 @@
 use rustables_macros::nfnetlink_struct;
 
-use super::Expression;
+use super::{Expression, Register};
+use crate::sys::{NFTA_MASQ_FLAGS, NFTA_MASQ_REG_PROTO_MAX, NFTA_MASQ_REG_PROTO_MIN};
 
 /// Sets the source IP to that of the output interface.
-#[derive(Default, Debug, PartialEq, Eq, Hash)]
+///
+/// The source port can be restricted to the range going from the value of `reg_proto_min` to the
+/// value of `reg_proto_max`.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
 #[nfnetlink_struct(nested = true)]
-pub struct Masquerade;
-
-impl Clone for Masquerade {
-    fn clone(&self) -> Self {
-        Masquerade {}
-    }
+pub struct Masquerade {
+    /// See [`NatFlags`](super::NatFlags).
+    #[field(NFTA_MASQ_FLAGS)]
+    flags: u32,
+    #[field(NFTA_MASQ_REG_PROTO_MIN)]
+    reg_proto_min: Register,
+    #[field(NFTA_MASQ_REG_PROTO_MAX)]
+    reg_proto_max: Register,
 }
 
 impl Expression for Masquerade {
@@ -8,8 +8,12 @@ use crate::sys;
 #[nfnetlink_enum(u32)]
 #[non_exhaustive]
 pub enum MetaType {
+    /// Packet length (skb->len).
+    Len = sys::NFT_META_LEN,
     /// Packet ethertype protocol (skb->protocol), invalid in OUTPUT.
     Protocol = sys::NFT_META_PROTOCOL,
+    /// Packet priority, as a tc class handle (skb->priority).
+    Priority = sys::NFT_META_PRIORITY,
     /// Packet mark.
     Mark = sys::NFT_META_MARK,
     /// Packet input interface index (dev->ifindex).
@@ -28,14 +32,95 @@ pub enum MetaType {
     SkUid = sys::NFT_META_SKUID,
     /// Originating socket GID (fsgid).
     SkGid = sys::NFT_META_SKGID,
+    /// Whether the packet is traced (skb->nftrace).
+    NfTrace = sys::NFT_META_NFTRACE,
+    /// Routing realm (dst->tclassid).
+    RtClassId = sys::NFT_META_RTCLASSID,
+    /// Packet security mark (skb->secmark).
+    Secmark = sys::NFT_META_SECMARK,
     /// Netfilter protocol (Transport layer protocol).
     NfProto = sys::NFT_META_NFPROTO,
     /// Layer 4 protocol number.
     L4Proto = sys::NFT_META_L4PROTO,
+    /// Name of the bridge the packet came in through.
+    BriIifName = sys::NFT_META_BRI_IIFNAME,
+    /// Name of the bridge the packet goes out through.
+    BriOifName = sys::NFT_META_BRI_OIFNAME,
+    /// Packet type (unicast, broadcast, multicast or other host, `PACKET_*` constants).
+    PktType = sys::NFT_META_PKTTYPE,
+    /// CPU handling the packet.
+    Cpu = sys::NFT_META_CPU,
+    /// Group of the input interface.
+    IifGroup = sys::NFT_META_IIFGROUP,
+    /// Group of the output interface.
+    OifGroup = sys::NFT_META_OIFGROUP,
     /// Socket control group (skb->sk->sk_classid).
     Cgroup = sys::NFT_META_CGROUP,
     /// A 32bit pseudo-random number.
     PRandom = sys::NFT_META_PRANDOM,
+    /// Whether the packet went through IPsec processing.
+    Secpath = sys::NFT_META_SECPATH,
+    /// Kind of the input interface, as in "vlan" or "wireguard".
+    IifKind = sys::NFT_META_IIFKIND,
+    /// Kind of the output interface.
+    OifKind = sys::NFT_META_OIFKIND,
+    /// VLAN id of the port of the bridge the packet came in through.
+    BriIifPvid = sys::NFT_META_BRI_IIFPVID,
+    /// VLAN protocol of the bridge the packet came in through.
+    BriIifVproto = sys::NFT_META_BRI_IIFVPROTO,
+    /// Current time, in nanoseconds since the epoch.
+    TimeNs = sys::NFT_META_TIME_NS,
+    /// Current day of the week, 0 being Sunday.
+    TimeDay = sys::NFT_META_TIME_DAY,
+    /// Number of seconds since midnight.
+    TimeHour = sys::NFT_META_TIME_HOUR,
+    /// Index of the slave interface of a VRF the packet came in through.
+    Sdif = sys::NFT_META_SDIF,
+    /// Name of the slave interface of a VRF the packet came in through.
+    SdifName = sys::NFT_META_SDIFNAME,
+}
+
+impl MetaType {
+    /// Length in bytes of the value loaded in a register for this key.
+    pub fn value_len(&self) -> u32 {
+        match *self {
+            MetaType::NfTrace
+            | MetaType::NfProto
+            | MetaType::L4Proto
+            | MetaType::PktType
+            | MetaType::Secpath
+            | MetaType::TimeDay => 1,
+            MetaType::Protocol
+            | MetaType::IifType
+            | MetaType::OifType
+            | MetaType::BriIifPvid
+            | MetaType::BriIifVproto => 2,
+            MetaType::Len
+            | MetaType::Priority
+            | MetaType::Mark
+            | MetaType::Iif
+            | MetaType::Oif
+            | MetaType::SkUid
+            | MetaType::SkGid
+            | MetaType::RtClassId
+            | MetaType::Secmark
+            | MetaType::Cpu
+            | MetaType::IifGroup
+            | MetaType::OifGroup
+            | MetaType::Cgroup
+            | MetaType::PRandom
+            | MetaType::TimeHour
+            | MetaType::Sdif => 4,
+            MetaType::TimeNs => 8,
+            MetaType::IifName
+            | MetaType::OifName
+            | MetaType::BriIifName
+            | MetaType::BriOifName
+            | MetaType::IifKind
+            | MetaType::OifKind
+            | MetaType::SdifName => libc::IFNAMSIZ as u32,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Default, Debug, Hash)]
@@ -53,6 +138,12 @@ impl Meta {
     pub fn new(ty: MetaType) -> Self {
         Meta::default().with_dreg(Register::Reg1).with_key(ty)
     }
+
+    /// Sets the meta data `key` of the packet to the value stored in `sreg`. Only `Mark`,
+    /// `Priority`, `PktType`, `NfTrace` and `Secmark` can be set.
+    pub fn set(key: MetaType, sreg: Register) -> Self {
+        Meta::default().with_key(key).with_sreg(sreg)
+    }
 }
 
 impl Expression for Meta {
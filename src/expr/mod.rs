@@ -15,18 +15,48 @@ use crate::sys::{self, NFTA_EXPR_DATA, NFTA_EXPR_NAME};
 mod bitwise;
 pub use self::bitwise::*;
 
+mod byteorder;
+pub use self::byteorder::*;
+
 mod cmp;
 pub use self::cmp::*;
 
+mod connlimit;
+pub use self::connlimit::*;
+
 mod counter;
 pub use self::counter::*;
 
 pub mod ct;
 pub use self::ct::*;
 
+mod dup;
+pub use self::dup::*;
+
+mod exthdr;
+pub use self::exthdr::*;
+
+mod fib;
+pub use self::fib::*;
+
+mod fwd;
+pub use self::fwd::*;
+
+mod hash;
+pub use self::hash::*;
+
 mod immediate;
 pub use self::immediate::*;
 
+mod inner;
+pub use self::inner::*;
+
+mod last;
+pub use self::last::*;
+
+mod limit;
+pub use self::limit::*;
+
 mod log;
 pub use self::log::*;
 
@@ -42,20 +72,68 @@ pub use self::meta::*;
 mod nat;
 pub use self::nat::*;
 
+mod notrack;
+pub use self::notrack::*;
+
+mod numgen;
+pub use self::numgen::*;
+
+mod objref;
+pub use self::objref::*;
+
+mod osf;
+pub use self::osf::*;
+
 mod payload;
 pub use self::payload::*;
 
+mod queue;
+pub use self::queue::*;
+
+mod quota;
+pub use self::quota::*;
+
 mod reject;
-pub use self::reject::{IcmpCode, Reject, RejectType};
+pub use self::reject::{IcmpCode, Icmpv4Code, Icmpv6Code, Reject, RejectKind, RejectType};
+
+mod range;
+pub use self::range::*;
+
+mod redir;
+pub use self::redir::*;
 
 mod register;
-pub use self::register::Register;
+pub use self::register::{Register, RegisterAllocator, RegisterValue};
+
+mod rt;
+pub use self::rt::*;
+
+mod socket;
+pub use self::socket::*;
+
+mod synproxy;
+pub use self::synproxy::*;
+
+mod tproxy;
+pub use self::tproxy::*;
+
+mod tunnel;
+pub use self::tunnel::*;
 
 mod verdict;
 pub use self::verdict::*;
 
+mod xfrm;
+pub use self::xfrm::*;
+
 pub trait Expression {
     fn get_name() -> &'static str;
+
+    /// Checks that the attributes of the expression can be combined. This is called before the
+    /// expression is serialized, along with the size checks.
+    fn validate(&self) -> Result<(), crate::error::BuilderError> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Default, Debug, Hash)]
@@ -79,6 +157,32 @@ where
     }
 }
 
+// boxed, for the expressions holding another expression
+impl NfNetlinkAttribute for Box<RawExpression> {
+    fn is_nested(&self) -> bool {
+        self.as_ref().is_nested()
+    }
+
+    fn get_size(&self) -> usize {
+        self.as_ref().get_size()
+    }
+
+    fn check_size(&self) -> Result<(), crate::error::BuilderError> {
+        self.as_ref().check_size()
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        self.as_ref().write_payload(addr);
+    }
+}
+
+impl NfNetlinkDeserializable for Box<RawExpression> {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        let (expr, remaining) = RawExpression::deserialize(buf)?;
+        Ok((Box::new(expr), remaining))
+    }
+}
+
 macro_rules! create_expr_variant {
     ($enum:ident $(, [$name:ident, $type:ty])+) => {
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -101,6 +205,17 @@ macro_rules! create_expr_variant {
                 }
             }
 
+            fn check_size(&self) -> Result<(), $crate::error::BuilderError> {
+                match self {
+                    $(
+                        $enum::$name(val) => {
+                            $crate::expr::Expression::validate(val)?;
+                            val.check_size()
+                        }
+                    )+
+                }
+            }
+
             fn write_payload(&self, addr: &mut [u8]) {
                 match self {
                     $(
@@ -169,18 +284,42 @@ macro_rules! create_expr_variant {
 create_expr_variant!(
     ExpressionVariant,
     [Bitwise, Bitwise],
+    [Byteorder, Byteorder],
     [Cmp, Cmp],
+    [Connlimit, Connlimit],
     [Conntrack, Conntrack],
     [Counter, Counter],
+    [Dup, Dup],
     [ExpressionRaw, ExpressionRaw],
+    [Exthdr, Exthdr],
+    [Fib, Fib],
+    [Fwd, Fwd],
+    [Hash, Hash],
     [Immediate, Immediate],
+    [Inner, Inner],
+    [Last, Last],
+    [Limit, Limit],
     [Log, Log],
     [Lookup, Lookup],
     [Masquerade, Masquerade],
     [Meta, Meta],
     [Nat, Nat],
+    [Notrack, Notrack],
+    [Numgen, Numgen],
+    [Objref, Objref],
+    [Osf, Osf],
     [Payload, Payload],
-    [Reject, Reject]
+    [Queue, Queue],
+    [Quota, Quota],
+    [Range, Range],
+    [Redir, Redir],
+    [Reject, Reject],
+    [Rt, Rt],
+    [Socket, Socket],
+    [Synproxy, Synproxy],
+    [Tproxy, Tproxy],
+    [Tunnel, Tunnel],
+    [Xfrm, Xfrm]
 );
 
 pub type ExpressionList = NfNetlinkList<RawExpression>;
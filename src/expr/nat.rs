@@ -6,6 +6,12 @@ use crate::{
     ProtocolFamily,
 };
 
+// from linux/netfilter/nf_nat.h
+const NF_NAT_RANGE_PROTO_RANDOM: u32 = 1 << 2;
+const NF_NAT_RANGE_PERSISTENT: u32 = 1 << 3;
+const NF_NAT_RANGE_PROTO_RANDOM_FULLY: u32 = 1 << 4;
+const NF_NAT_RANGE_NETMAP: u32 = 1 << 6;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[nfnetlink_enum(i32)]
 pub enum NatType {
@@ -15,8 +21,32 @@ pub enum NatType {
     DNat = NFT_NAT_DNAT,
 }
 
+bitflags::bitflags! {
+    /// The flags of the [`Nat`], [`Masquerade`] and [`Redir`] expressions, from the
+    /// `NF_NAT_RANGE_*` constants of the kernel.
+    ///
+    /// [`Masquerade`]: super::Masquerade
+    /// [`Redir`]: super::Redir
+    pub struct NatFlags: u32 {
+        /// Randomize the source port mapping (`random`).
+        const RANDOM = NF_NAT_RANGE_PROTO_RANDOM;
+        /// Give a client the same address for all its connections (`persistent`).
+        const PERSISTENT = NF_NAT_RANGE_PERSISTENT;
+        /// Randomize the source port mapping with a full PRNG (`fully-random`).
+        const FULLY_RANDOM = NF_NAT_RANGE_PROTO_RANDOM_FULLY;
+        /// Map the addresses of a prefix one to one onto the addresses of another prefix of the
+        /// same size (`prefix`).
+        const NETMAP = NF_NAT_RANGE_NETMAP;
+    }
+}
+
 /// A source or destination NAT statement. Modifies the source or destination address (and possibly
 /// port) of packets.
+///
+/// The new address is picked in the range going from the value of `ip_register` to the value of
+/// `ip_register_max`, and the new port in the range going from the value of `port_register` to
+/// the value of `port_register_max`. The maximum registers can be left out to map to a single
+/// address or port.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[nfnetlink_struct(nested = true)]
 pub struct Nat {
@@ -26,8 +56,15 @@ pub struct Nat {
     pub family: ProtocolFamily,
     #[field(sys::NFTA_NAT_REG_ADDR_MIN)]
     pub ip_register: Register,
+    #[field(sys::NFTA_NAT_REG_ADDR_MAX)]
+    pub ip_register_max: Register,
     #[field(sys::NFTA_NAT_REG_PROTO_MIN)]
     pub port_register: Register,
+    #[field(sys::NFTA_NAT_REG_PROTO_MAX)]
+    pub port_register_max: Register,
+    /// See [`NatFlags`].
+    #[field(sys::NFTA_NAT_FLAGS)]
+    pub flags: u32,
 }
 
 impl Expression for Nat {
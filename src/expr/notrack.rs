@@ -0,0 +1,15 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::Expression;
+
+/// A notrack expression disables connection tracking for the packet, which must not have been
+/// tracked yet: it is only effective in chains with a priority lower than `raw`.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Notrack {}
+
+impl Expression for Notrack {
+    fn get_name() -> &'static str {
+        "notrack"
+    }
+}
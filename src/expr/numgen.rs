@@ -0,0 +1,46 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_NG_DREG, NFTA_NG_MODULUS, NFTA_NG_OFFSET, NFTA_NG_TYPE, NFT_NG_INCREMENTAL, NFT_NG_RANDOM,
+};
+
+/// The way numbers are generated by a [`Numgen`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum NumgenType {
+    /// Round robin over the numbers from 0 to the modulus.
+    Incremental = NFT_NG_INCREMENTAL,
+    /// Random numbers between 0 and the modulus.
+    Random = NFT_NG_RANDOM,
+}
+
+/// A numgen expression stores in a register a number lower than `modulus`, plus `offset`. Along
+/// with a map, it can be used to spread the traffic over several backends.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Numgen {
+    #[field(NFTA_NG_DREG)]
+    dreg: Register,
+    #[field(NFTA_NG_MODULUS)]
+    modulus: u32,
+    #[field(NFTA_NG_TYPE, name_in_functions = "type")]
+    numgen_type: NumgenType,
+    #[field(NFTA_NG_OFFSET)]
+    offset: u32,
+}
+
+impl Numgen {
+    pub fn new(numgen_type: NumgenType, modulus: u32) -> Self {
+        Numgen::default()
+            .with_dreg(Register::Reg1)
+            .with_modulus(modulus)
+            .with_type(numgen_type)
+    }
+}
+
+impl Expression for Numgen {
+    fn get_name() -> &'static str {
+        "numgen"
+    }
+}
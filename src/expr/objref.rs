@@ -0,0 +1,46 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_OBJREF_IMM_NAME, NFTA_OBJREF_IMM_TYPE, NFTA_OBJREF_SET_ID, NFTA_OBJREF_SET_NAME,
+    NFTA_OBJREF_SET_SREG,
+};
+use crate::ObjectType;
+
+/// An objref expression applies a named stateful [`Object`] of the table to the packet, for
+/// instance to count it with a shared counter, or to label it with a secmark object
+/// (`meta secmark set "http"`).
+///
+/// The object is either given by its name, or looked up in a map whose keys are loaded from
+/// `set_sreg`.
+///
+/// [`Object`]: crate::Object
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Objref {
+    #[field(NFTA_OBJREF_IMM_TYPE)]
+    imm_type: ObjectType,
+    #[field(NFTA_OBJREF_IMM_NAME)]
+    imm_name: String,
+    #[field(NFTA_OBJREF_SET_SREG)]
+    set_sreg: Register,
+    #[field(NFTA_OBJREF_SET_NAME)]
+    set_name: String,
+    #[field(NFTA_OBJREF_SET_ID)]
+    set_id: u32,
+}
+
+impl Objref {
+    /// Returns an objref applying the object `name` of type `obj_type`.
+    pub fn new(obj_type: ObjectType, name: impl Into<String>) -> Self {
+        Objref::default()
+            .with_imm_type(obj_type)
+            .with_imm_name(name)
+    }
+}
+
+impl Expression for Objref {
+    fn get_name() -> &'static str {
+        "objref"
+    }
+}
@@ -0,0 +1,60 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{NFTA_OSF_DREG, NFTA_OSF_FLAGS, NFTA_OSF_TTL, NFT_OSF_F_VERSION};
+
+// from linux/netfilter/nfnetlink_osf.h
+const NF_OSF_TTL_TRUE: u8 = 0;
+const NF_OSF_TTL_LESS: u8 = 1;
+const NF_OSF_TTL_NOCHECK: u8 = 2;
+
+/// How an [`Osf`] expression compares the TTL of the packets with the one of the fingerprints.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum OsfTtl {
+    /// The TTL must match the one of the fingerprint, the default.
+    Exact = NF_OSF_TTL_TRUE,
+    /// The TTL may be lower than the one of the fingerprint, for the hosts a few hops away
+    /// (`osf ttl loose`).
+    Loose = NF_OSF_TTL_LESS,
+    /// The TTL is not checked (`osf ttl skip`).
+    Skip = NF_OSF_TTL_NOCHECK,
+}
+
+bitflags::bitflags! {
+    pub struct OsfFlags: u32 {
+        /// Load the version of the operating system along with its name (`osf version`).
+        const VERSION = NFT_OSF_F_VERSION;
+    }
+}
+
+/// An osf expression loads the name of the operating system that sent a TCP SYN packet,
+/// recognized by passive fingerprinting, for instance `Linux`, or `unknown`.
+///
+/// The fingerprints must have been loaded in the kernel beforehand, with `nfnl_osf`.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Osf {
+    #[field(NFTA_OSF_DREG)]
+    dreg: Register,
+    #[field(NFTA_OSF_TTL)]
+    ttl: OsfTtl,
+    /// See [`OsfFlags`].
+    #[field(NFTA_OSF_FLAGS)]
+    flags: u32,
+}
+
+impl Osf {
+    pub fn new(ttl: OsfTtl, flags: OsfFlags) -> Self {
+        Osf::default()
+            .with_dreg(Register::Reg1)
+            .with_ttl(ttl)
+            .with_flags(flags.bits())
+    }
+}
+
+impl Expression for Osf {
+    fn get_name() -> &'static str {
+        "osf"
+    }
+}
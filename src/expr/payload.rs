@@ -1,12 +1,39 @@
-use rustables_macros::nfnetlink_struct;
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
 
-use super::{Expression, Register};
+use super::{Bitwise, Expression, Register};
 use crate::{
-    error::DecodeError,
-    sys::{self, NFT_PAYLOAD_LL_HEADER, NFT_PAYLOAD_NETWORK_HEADER, NFT_PAYLOAD_TRANSPORT_HEADER},
+    error::{BuilderError, DecodeError},
+    sys::{
+        self, NFT_PAYLOAD_CSUM_INET, NFT_PAYLOAD_CSUM_NONE, NFT_PAYLOAD_CSUM_SCTP,
+        NFT_PAYLOAD_L4CSUM_PSEUDOHDR, NFT_PAYLOAD_LL_HEADER, NFT_PAYLOAD_NETWORK_HEADER,
+        NFT_PAYLOAD_TRANSPORT_HEADER,
+    },
 };
 
+/// The checksum a payload expression updates when it writes to the packet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum PayloadCsumType {
+    None = NFT_PAYLOAD_CSUM_NONE,
+    /// The internet checksum of IPv4, TCP, UDP and ICMP.
+    Inet = NFT_PAYLOAD_CSUM_INET,
+    /// The CRC32c checksum of SCTP.
+    Sctp = NFT_PAYLOAD_CSUM_SCTP,
+}
+
+bitflags::bitflags! {
+    /// The checksum flags of a payload expression.
+    pub struct PayloadCsumFlags: u32 {
+        /// Also update the layer 4 checksum, which covers the addresses of the network header
+        /// through its pseudo-header.
+        const L4_PSEUDOHDR = NFT_PAYLOAD_L4CSUM_PSEUDOHDR;
+    }
+}
+
 /// Payload expressions refer to data from the packet's payload.
+///
+/// They load the data into `dreg`, or overwrite it with the content of `sreg`. In the latter
+/// case, the checksum at `csum_offset` is updated according to `csum_type` and `csum_flags`.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[nfnetlink_struct(nested = true)]
 pub struct Payload {
@@ -20,6 +47,13 @@ pub struct Payload {
     len: u32,
     #[field(sys::NFTA_PAYLOAD_SREG)]
     sreg: Register,
+    #[field(sys::NFTA_PAYLOAD_CSUM_TYPE)]
+    csum_type: PayloadCsumType,
+    #[field(sys::NFTA_PAYLOAD_CSUM_OFFSET)]
+    csum_offset: u32,
+    /// See [`PayloadCsumFlags`].
+    #[field(sys::NFTA_PAYLOAD_CSUM_FLAGS)]
+    csum_flags: u32,
 }
 
 impl Expression for Payload {
@@ -37,22 +71,105 @@ pub enum HighLevelPayload {
 }
 
 impl HighLevelPayload {
-    pub fn build(&self) -> Payload {
+    fn base(&self) -> u32 {
         match *self {
-            HighLevelPayload::LinkLayer(ref f) => Payload::default()
-                .with_base(NFT_PAYLOAD_LL_HEADER)
-                .with_offset(f.offset())
-                .with_len(f.len()),
-            HighLevelPayload::Network(ref f) => Payload::default()
-                .with_base(NFT_PAYLOAD_NETWORK_HEADER)
-                .with_offset(f.offset())
-                .with_len(f.len()),
-            HighLevelPayload::Transport(ref f) => Payload::default()
-                .with_base(NFT_PAYLOAD_TRANSPORT_HEADER)
-                .with_offset(f.offset())
-                .with_len(f.len()),
+            HighLevelPayload::LinkLayer(_) => NFT_PAYLOAD_LL_HEADER,
+            HighLevelPayload::Network(_) => NFT_PAYLOAD_NETWORK_HEADER,
+            HighLevelPayload::Transport(_) => NFT_PAYLOAD_TRANSPORT_HEADER,
         }
-        .with_dreg(Register::Reg1)
+    }
+
+    pub(crate) fn field(&self) -> &dyn HeaderField {
+        match self {
+            HighLevelPayload::LinkLayer(f) => f,
+            HighLevelPayload::Network(f) => f,
+            HighLevelPayload::Transport(f) => f,
+        }
+    }
+
+    pub fn build(&self) -> Payload {
+        let field = self.field();
+        Payload::default()
+            .with_base(self.base())
+            .with_offset(field.offset())
+            .with_len(field.len())
+            .with_dreg(Register::Reg1)
+    }
+
+    /// Builds the bitwise expression isolating the bits of the field in the value loaded by
+    /// [`HighLevelPayload::build`], for the fields that do not span whole bytes.
+    pub fn build_mask(&self) -> Option<Bitwise> {
+        self.field()
+            .mask()
+            .map(|mask| Bitwise::new(mask, vec![0; mask.len()]).unwrap())
+    }
+
+    /// Builds a payload expression overwriting the field with the content of `sreg`, and
+    /// updating the IPv4 header checksum and the checksum of the transport header accordingly
+    /// (`ip ttl set 64`).
+    ///
+    /// The whole bytes of the field are overwritten, so the fields that do not span whole bytes
+    /// must be loaded and masked beforehand. The checksums are updated by 16-bit words, so the
+    /// fields starting at an odd offset, such as the IPv4 protocol, are refused with
+    /// [`BuilderError::UnalignedChecksumUpdate`] when a checksum covers them: they must be
+    /// written along with the byte before them, with a [`Payload`] expression.
+    pub fn set(&self, sreg: Register) -> Result<Payload, BuilderError> {
+        let field = self.field();
+        let payload = Payload::default()
+            .with_base(self.base())
+            .with_offset(field.offset())
+            .with_len(field.len())
+            .with_sreg(sreg);
+        let payload = match *self {
+            HighLevelPayload::LinkLayer(_) => payload,
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(f)) => {
+                let payload = payload
+                    .with_csum_type(PayloadCsumType::Inet)
+                    .with_csum_offset(IPv4HeaderField::Checksum.offset());
+                match f {
+                    IPv4HeaderField::Saddr | IPv4HeaderField::Daddr => {
+                        payload.with_csum_flags(PayloadCsumFlags::L4_PSEUDOHDR.bits())
+                    }
+                    _ => payload,
+                }
+            }
+            HighLevelPayload::Network(NetworkHeaderField::IPv6(f)) => match f {
+                IPv6HeaderField::Saddr | IPv6HeaderField::Daddr => payload
+                    .with_csum_type(PayloadCsumType::None)
+                    .with_csum_flags(PayloadCsumFlags::L4_PSEUDOHDR.bits()),
+                _ => payload,
+            },
+            HighLevelPayload::Network(NetworkHeaderField::Arp(_)) => payload,
+            HighLevelPayload::Transport(f) => {
+                let (csum_type, csum_offset) = match f {
+                    TransportHeaderField::Tcp(_) => {
+                        (PayloadCsumType::Inet, TCPHeaderField::Checksum.offset())
+                    }
+                    TransportHeaderField::Udp(_) => {
+                        (PayloadCsumType::Inet, UDPHeaderField::Checksum.offset())
+                    }
+                    TransportHeaderField::ICMP(_) => {
+                        (PayloadCsumType::Inet, ICMPHeaderField::Checksum.offset())
+                    }
+                    TransportHeaderField::ICMPv6(_) => {
+                        (PayloadCsumType::Inet, ICMPv6HeaderField::Checksum.offset())
+                    }
+                    TransportHeaderField::Sctp(_) => {
+                        (PayloadCsumType::Sctp, SctpHeaderField::Checksum.offset())
+                    }
+                    _ => return Ok(payload),
+                };
+                payload
+                    .with_csum_type(csum_type)
+                    .with_csum_offset(csum_offset)
+            }
+        };
+        let inet_csum = payload.get_csum_type() == Some(&PayloadCsumType::Inet)
+            || payload.get_csum_flags().is_some();
+        if inet_csum && field.offset() % 2 != 0 {
+            return Err(BuilderError::UnalignedChecksumUpdate);
+        }
+        Ok(payload)
     }
 }
 
@@ -66,6 +183,15 @@ pub enum PayloadType {
 
 impl PayloadType {
     pub fn parse_from_payload(raw: &Payload) -> Result<Self, DecodeError> {
+        Self::parse_from_payload_with_mask(raw, None)
+    }
+
+    /// Like [`Self::parse_from_payload`], also using the `mask` applied to the loaded bytes to
+    /// recognize the link layer fields that do not span whole bytes.
+    pub fn parse_from_payload_with_mask(
+        raw: &Payload,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
         if raw.base.is_none() {
             return Err(DecodeError::PayloadMissingBase);
         }
@@ -76,10 +202,13 @@ impl PayloadType {
             return Err(DecodeError::PayloadMissingOffset);
         }
         Ok(match raw.base {
-            Some(NFT_PAYLOAD_LL_HEADER) => PayloadType::LinkLayer(LLHeaderField::from_raw_data(
-                raw.offset.unwrap(),
-                raw.len.unwrap(),
-            )?),
+            Some(NFT_PAYLOAD_LL_HEADER) => {
+                PayloadType::LinkLayer(LLHeaderField::from_raw_data_with_mask(
+                    raw.offset.unwrap(),
+                    raw.len.unwrap(),
+                    mask,
+                )?)
+            }
             Some(NFT_PAYLOAD_NETWORK_HEADER) => PayloadType::Network,
             Some(NFT_PAYLOAD_TRANSPORT_HEADER) => PayloadType::Transport,
             Some(v) => return Err(DecodeError::UnknownPayloadType(v)),
@@ -88,9 +217,17 @@ impl PayloadType {
     }
 }
 
+/// A field of a protocol header, as loaded by a [`Payload`] expression.
 pub trait HeaderField {
+    /// Offset of the field from the start of its header, in bytes.
     fn offset(&self) -> u32;
+    /// Length in bytes of the data loaded for the field.
     fn len(&self) -> u32;
+    /// Mask selecting the bits of the field in the loaded bytes, for the fields that do not span
+    /// whole bytes. The values compared with such fields must have their bits at the same place.
+    fn mask(&self) -> Option<&'static [u8]> {
+        None
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -99,6 +236,7 @@ pub enum LLHeaderField {
     Daddr,
     Saddr,
     EtherType,
+    Vlan(VlanHeaderField),
 }
 
 impl HeaderField for LLHeaderField {
@@ -108,6 +246,7 @@ impl HeaderField for LLHeaderField {
             Daddr => 0,
             Saddr => 6,
             EtherType => 12,
+            Vlan(ref f) => f.offset(),
         }
     }
 
@@ -117,17 +256,106 @@ impl HeaderField for LLHeaderField {
             Daddr => 6,
             Saddr => 6,
             EtherType => 2,
+            Vlan(ref f) => f.len(),
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::LLHeaderField::*;
+        match *self {
+            Vlan(ref f) => f.mask(),
+            _ => None,
         }
     }
 }
 
 impl LLHeaderField {
     pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], also using the `mask` applied to the loaded bytes to
+    /// recognize the VLAN fields that do not span whole bytes.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
         Ok(match (offset, len) {
             (0, 6) => Self::Daddr,
             (6, 6) => Self::Saddr,
             (12, 2) => Self::EtherType,
-            _ => return Err(DecodeError::UnknownLinkLayerHeaderField(offset, len)),
+            _ => Self::Vlan(
+                VlanHeaderField::from_raw_data_with_mask(offset, len, mask)
+                    .map_err(|_| DecodeError::UnknownLinkLayerHeaderField(offset, len))?,
+            ),
+        })
+    }
+}
+
+/// Fields of the 802.1Q header following the Ethernet addresses, as seen from the link layer header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum VlanHeaderField {
+    /// Priority code point.
+    Pcp,
+    /// Drop eligible indicator.
+    Dei,
+    Id,
+    /// EtherType of the encapsulated packet.
+    Type,
+}
+
+impl HeaderField for VlanHeaderField {
+    fn offset(&self) -> u32 {
+        use self::VlanHeaderField::*;
+        match *self {
+            Pcp => 14,
+            Dei => 14,
+            Id => 14,
+            Type => 16,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::VlanHeaderField::*;
+        match *self {
+            Pcp => 1,
+            Dei => 1,
+            Id => 2,
+            Type => 2,
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::VlanHeaderField::*;
+        match *self {
+            Pcp => Some(&[0xe0]),
+            Dei => Some(&[0x10]),
+            Id => Some(&[0x0f, 0xff]),
+            _ => None,
+        }
+    }
+}
+
+impl VlanHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], telling apart the priority, the drop eligible indicator
+    /// and the id, which share the first bytes of the tag, with the `mask` applied to them.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
+        Ok(match (offset, len, mask) {
+            (14, 1, Some([0xe0])) => Self::Pcp,
+            (14, 1, Some([0x10])) => Self::Dei,
+            (14, 2, Some([0x0f, 0xff])) => Self::Id,
+            (16, 2, _) => Self::Type,
+            _ => return Err(DecodeError::UnknownVlanHeaderField(offset, len)),
         })
     }
 }
@@ -136,6 +364,7 @@ impl LLHeaderField {
 pub enum NetworkHeaderField {
     IPv4(IPv4HeaderField),
     IPv6(IPv6HeaderField),
+    Arp(ArpHeaderField),
 }
 
 impl HeaderField for NetworkHeaderField {
@@ -144,6 +373,7 @@ impl HeaderField for NetworkHeaderField {
         match *self {
             IPv4(ref f) => f.offset(),
             IPv6(ref f) => f.offset(),
+            Arp(ref f) => f.offset(),
         }
     }
 
@@ -152,6 +382,16 @@ impl HeaderField for NetworkHeaderField {
         match *self {
             IPv4(ref f) => f.len(),
             IPv6(ref f) => f.len(),
+            Arp(ref f) => f.len(),
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::NetworkHeaderField::*;
+        match *self {
+            IPv4(ref f) => f.mask(),
+            IPv6(ref f) => f.mask(),
+            Arp(ref f) => f.mask(),
         }
     }
 }
@@ -159,8 +399,19 @@ impl HeaderField for NetworkHeaderField {
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum IPv4HeaderField {
+    Version,
+    /// Length of the header, in 32-bit words.
+    HdrLength,
+    Dscp,
+    Ecn,
+    /// Total length of the packet.
+    Length,
+    Id,
+    /// Fragment offset, along with the flags in its 3 upper bits.
+    FragOff,
     Ttl,
     Protocol,
+    Checksum,
     Saddr,
     Daddr,
 }
@@ -169,8 +420,16 @@ impl HeaderField for IPv4HeaderField {
     fn offset(&self) -> u32 {
         use self::IPv4HeaderField::*;
         match *self {
+            Version => 0,
+            HdrLength => 0,
+            Dscp => 1,
+            Ecn => 1,
+            Length => 2,
+            Id => 4,
+            FragOff => 6,
             Ttl => 8,
             Protocol => 9,
+            Checksum => 10,
             Saddr => 12,
             Daddr => 16,
         }
@@ -179,21 +438,58 @@ impl HeaderField for IPv4HeaderField {
     fn len(&self) -> u32 {
         use self::IPv4HeaderField::*;
         match *self {
+            Version => 1,
+            HdrLength => 1,
+            Dscp => 1,
+            Ecn => 1,
+            Length => 2,
+            Id => 2,
+            FragOff => 2,
             Ttl => 1,
             Protocol => 1,
+            Checksum => 2,
             Saddr => 4,
             Daddr => 4,
         }
     }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::IPv4HeaderField::*;
+        match *self {
+            Version => Some(&[0xf0]),
+            HdrLength => Some(&[0x0f]),
+            Dscp => Some(&[0xfc]),
+            Ecn => Some(&[0x03]),
+            _ => None,
+        }
+    }
 }
 
 impl IPv4HeaderField {
     pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
-        Ok(match (offset, len) {
-            (8, 1) => Self::Ttl,
-            (9, 1) => Self::Protocol,
-            (12, 4) => Self::Saddr,
-            (16, 4) => Self::Daddr,
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], telling apart the version and the header length, or the
+    /// DSCP and the ECN, with the `mask` applied to the byte they share.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
+        Ok(match (offset, len, mask) {
+            (0, 1, Some([0xf0])) => Self::Version,
+            (0, 1, Some([0x0f])) => Self::HdrLength,
+            (1, 1, Some([0xfc])) => Self::Dscp,
+            (1, 1, Some([0x03])) => Self::Ecn,
+            (2, 2, _) => Self::Length,
+            (4, 2, _) => Self::Id,
+            (6, 2, _) => Self::FragOff,
+            (8, 1, _) => Self::Ttl,
+            (9, 1, _) => Self::Protocol,
+            (10, 2, _) => Self::Checksum,
+            (12, 4, _) => Self::Saddr,
+            (16, 4, _) => Self::Daddr,
             _ => return Err(DecodeError::UnknownIPv4HeaderField(offset, len)),
         })
     }
@@ -202,6 +498,12 @@ impl IPv4HeaderField {
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum IPv6HeaderField {
+    Version,
+    Dscp,
+    Ecn,
+    FlowLabel,
+    /// Length of the payload, extension headers included.
+    Length,
     NextHeader,
     HopLimit,
     Saddr,
@@ -212,6 +514,11 @@ impl HeaderField for IPv6HeaderField {
     fn offset(&self) -> u32 {
         use self::IPv6HeaderField::*;
         match *self {
+            Version => 0,
+            Dscp => 0,
+            Ecn => 1,
+            FlowLabel => 1,
+            Length => 4,
             NextHeader => 6,
             HopLimit => 7,
             Saddr => 8,
@@ -222,32 +529,137 @@ impl HeaderField for IPv6HeaderField {
     fn len(&self) -> u32 {
         use self::IPv6HeaderField::*;
         match *self {
+            Version => 1,
+            Dscp => 2,
+            Ecn => 1,
+            FlowLabel => 3,
+            Length => 2,
             NextHeader => 1,
             HopLimit => 1,
             Saddr => 16,
             Daddr => 16,
         }
     }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::IPv6HeaderField::*;
+        match *self {
+            Version => Some(&[0xf0]),
+            Dscp => Some(&[0x0f, 0xc0]),
+            Ecn => Some(&[0x30]),
+            FlowLabel => Some(&[0x0f, 0xff, 0xff]),
+            _ => None,
+        }
+    }
 }
 
 impl IPv6HeaderField {
     pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
-        Ok(match (offset, len) {
-            (6, 1) => Self::NextHeader,
-            (7, 1) => Self::HopLimit,
-            (8, 16) => Self::Saddr,
-            (24, 16) => Self::Daddr,
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], recognizing the version, the traffic class fields and the
+    /// flow label, which do not span whole bytes, with the `mask` applied to the loaded bytes.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
+        Ok(match (offset, len, mask) {
+            (0, 1, Some([0xf0])) => Self::Version,
+            (0, 2, Some([0x0f, 0xc0])) => Self::Dscp,
+            (1, 1, Some([0x30])) => Self::Ecn,
+            (1, 3, Some([0x0f, 0xff, 0xff])) => Self::FlowLabel,
+            (4, 2, _) => Self::Length,
+            (6, 1, _) => Self::NextHeader,
+            (7, 1, _) => Self::HopLimit,
+            (8, 16, _) => Self::Saddr,
+            (24, 16, _) => Self::Daddr,
             _ => return Err(DecodeError::UnknownIPv6HeaderField(offset, len)),
         })
     }
 }
 
+/// Fields of the ARP header, with the addresses of Ethernet and IPv4.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ArpHeaderField {
+    /// Hardware type.
+    HType,
+    /// Protocol type.
+    PType,
+    /// Length of the hardware addresses.
+    HLen,
+    /// Length of the protocol addresses.
+    PLen,
+    Operation,
+    SaddrEther,
+    SaddrIp,
+    DaddrEther,
+    DaddrIp,
+}
+
+impl HeaderField for ArpHeaderField {
+    fn offset(&self) -> u32 {
+        use self::ArpHeaderField::*;
+        match *self {
+            HType => 0,
+            PType => 2,
+            HLen => 4,
+            PLen => 5,
+            Operation => 6,
+            SaddrEther => 8,
+            SaddrIp => 14,
+            DaddrEther => 18,
+            DaddrIp => 24,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::ArpHeaderField::*;
+        match *self {
+            HType => 2,
+            PType => 2,
+            HLen => 1,
+            PLen => 1,
+            Operation => 2,
+            SaddrEther => 6,
+            SaddrIp => 4,
+            DaddrEther => 6,
+            DaddrIp => 4,
+        }
+    }
+}
+
+impl ArpHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Ok(match (offset, len) {
+            (0, 2) => Self::HType,
+            (2, 2) => Self::PType,
+            (4, 1) => Self::HLen,
+            (5, 1) => Self::PLen,
+            (6, 2) => Self::Operation,
+            (8, 6) => Self::SaddrEther,
+            (14, 4) => Self::SaddrIp,
+            (18, 6) => Self::DaddrEther,
+            (24, 4) => Self::DaddrIp,
+            _ => return Err(DecodeError::UnknownArpHeaderField(offset, len)),
+        })
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum TransportHeaderField {
     Tcp(TCPHeaderField),
     Udp(UDPHeaderField),
+    ICMP(ICMPHeaderField),
     ICMPv6(ICMPv6HeaderField),
+    Sctp(SctpHeaderField),
+    Dccp(DccpHeaderField),
+    Ah(AhHeaderField),
+    Esp(EspHeaderField),
+    Gre(GreHeaderField),
 }
 
 impl HeaderField for TransportHeaderField {
@@ -256,7 +668,13 @@ impl HeaderField for TransportHeaderField {
         match *self {
             Tcp(ref f) => f.offset(),
             Udp(ref f) => f.offset(),
+            ICMP(ref f) => f.offset(),
             ICMPv6(ref f) => f.offset(),
+            Sctp(ref f) => f.offset(),
+            Dccp(ref f) => f.offset(),
+            Ah(ref f) => f.offset(),
+            Esp(ref f) => f.offset(),
+            Gre(ref f) => f.offset(),
         }
     }
 
@@ -265,16 +683,45 @@ impl HeaderField for TransportHeaderField {
         match *self {
             Tcp(ref f) => f.len(),
             Udp(ref f) => f.len(),
+            ICMP(ref f) => f.len(),
             ICMPv6(ref f) => f.len(),
+            Sctp(ref f) => f.len(),
+            Dccp(ref f) => f.len(),
+            Ah(ref f) => f.len(),
+            Esp(ref f) => f.len(),
+            Gre(ref f) => f.len(),
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::TransportHeaderField::*;
+        match *self {
+            Tcp(ref f) => f.mask(),
+            Udp(ref f) => f.mask(),
+            ICMP(ref f) => f.mask(),
+            ICMPv6(ref f) => f.mask(),
+            Sctp(ref f) => f.mask(),
+            Dccp(ref f) => f.mask(),
+            Ah(ref f) => f.mask(),
+            Esp(ref f) => f.mask(),
+            Gre(ref f) => f.mask(),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum TCPHeaderField {
     Sport,
     Dport,
+    Sequence,
+    AckSeq,
+    /// Length of the header, in 32-bit words.
+    DataOffset,
+    Flags,
+    Window,
+    Checksum,
+    UrgPtr,
 }
 
 impl HeaderField for TCPHeaderField {
@@ -283,6 +730,13 @@ impl HeaderField for TCPHeaderField {
         match *self {
             Sport => 0,
             Dport => 2,
+            Sequence => 4,
+            AckSeq => 8,
+            DataOffset => 12,
+            Flags => 13,
+            Window => 14,
+            Checksum => 16,
+            UrgPtr => 18,
         }
     }
 
@@ -291,26 +745,59 @@ impl HeaderField for TCPHeaderField {
         match *self {
             Sport => 2,
             Dport => 2,
+            Sequence => 4,
+            AckSeq => 4,
+            DataOffset => 1,
+            Flags => 1,
+            Window => 2,
+            Checksum => 2,
+            UrgPtr => 2,
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::TCPHeaderField::*;
+        match *self {
+            DataOffset => Some(&[0xf0]),
+            _ => None,
         }
     }
 }
 
 impl TCPHeaderField {
     pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
-        Ok(match (offset, len) {
-            (0, 2) => Self::Sport,
-            (2, 2) => Self::Dport,
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], recognizing the data offset, which only spans the upper
+    /// half of its byte, with the `mask` applied to it.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
+        Ok(match (offset, len, mask) {
+            (0, 2, _) => Self::Sport,
+            (2, 2, _) => Self::Dport,
+            (4, 4, _) => Self::Sequence,
+            (8, 4, _) => Self::AckSeq,
+            (12, 1, Some([0xf0])) => Self::DataOffset,
+            (13, 1, _) => Self::Flags,
+            (14, 2, _) => Self::Window,
+            (16, 2, _) => Self::Checksum,
+            (18, 2, _) => Self::UrgPtr,
             _ => return Err(DecodeError::UnknownTCPHeaderField(offset, len)),
         })
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum UDPHeaderField {
     Sport,
     Dport,
     Len,
+    Checksum,
 }
 
 impl HeaderField for UDPHeaderField {
@@ -320,6 +807,7 @@ impl HeaderField for UDPHeaderField {
             Sport => 0,
             Dport => 2,
             Len => 4,
+            Checksum => 6,
         }
     }
 
@@ -329,6 +817,7 @@ impl HeaderField for UDPHeaderField {
             Sport => 2,
             Dport => 2,
             Len => 2,
+            Checksum => 2,
         }
     }
 }
@@ -339,17 +828,71 @@ impl UDPHeaderField {
             (0, 2) => Self::Sport,
             (2, 2) => Self::Dport,
             (4, 2) => Self::Len,
+            (6, 2) => Self::Checksum,
             _ => return Err(DecodeError::UnknownUDPHeaderField(offset, len)),
         })
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ICMPHeaderField {
+    Type,
+    Code,
+    Checksum,
+    /// Identifier of the echo requests and replies.
+    Id,
+    /// Sequence number of the echo requests and replies.
+    Sequence,
+}
+
+impl HeaderField for ICMPHeaderField {
+    fn offset(&self) -> u32 {
+        use self::ICMPHeaderField::*;
+        match *self {
+            Type => 0,
+            Code => 1,
+            Checksum => 2,
+            Id => 4,
+            Sequence => 6,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::ICMPHeaderField::*;
+        match *self {
+            Type => 1,
+            Code => 1,
+            Checksum => 2,
+            Id => 2,
+            Sequence => 2,
+        }
+    }
+}
+
+impl ICMPHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Ok(match (offset, len) {
+            (0, 1) => Self::Type,
+            (1, 1) => Self::Code,
+            (2, 2) => Self::Checksum,
+            (4, 2) => Self::Id,
+            (6, 2) => Self::Sequence,
+            _ => return Err(DecodeError::UnknownICMPHeaderField(offset, len)),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum ICMPv6HeaderField {
     Type,
     Code,
     Checksum,
+    /// Identifier of the echo requests and replies.
+    Id,
+    /// Sequence number of the echo requests and replies.
+    Sequence,
 }
 
 impl HeaderField for ICMPv6HeaderField {
@@ -359,6 +902,8 @@ impl HeaderField for ICMPv6HeaderField {
             Type => 0,
             Code => 1,
             Checksum => 2,
+            Id => 4,
+            Sequence => 6,
         }
     }
 
@@ -368,6 +913,8 @@ impl HeaderField for ICMPv6HeaderField {
             Type => 1,
             Code => 1,
             Checksum => 2,
+            Id => 2,
+            Sequence => 2,
         }
     }
 }
@@ -378,7 +925,257 @@ impl ICMPv6HeaderField {
             (0, 1) => Self::Type,
             (1, 1) => Self::Code,
             (2, 2) => Self::Checksum,
+            (4, 2) => Self::Id,
+            (6, 2) => Self::Sequence,
             _ => return Err(DecodeError::UnknownICMPv6HeaderField(offset, len)),
         })
     }
 }
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SctpHeaderField {
+    Sport,
+    Dport,
+    /// Verification tag.
+    Vtag,
+    Checksum,
+}
+
+impl HeaderField for SctpHeaderField {
+    fn offset(&self) -> u32 {
+        use self::SctpHeaderField::*;
+        match *self {
+            Sport => 0,
+            Dport => 2,
+            Vtag => 4,
+            Checksum => 8,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::SctpHeaderField::*;
+        match *self {
+            Sport => 2,
+            Dport => 2,
+            Vtag => 4,
+            Checksum => 4,
+        }
+    }
+}
+
+impl SctpHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Ok(match (offset, len) {
+            (0, 2) => Self::Sport,
+            (2, 2) => Self::Dport,
+            (4, 4) => Self::Vtag,
+            (8, 4) => Self::Checksum,
+            _ => return Err(DecodeError::UnknownSctpHeaderField(offset, len)),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DccpHeaderField {
+    Sport,
+    Dport,
+    Type,
+}
+
+impl HeaderField for DccpHeaderField {
+    fn offset(&self) -> u32 {
+        use self::DccpHeaderField::*;
+        match *self {
+            Sport => 0,
+            Dport => 2,
+            Type => 8,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::DccpHeaderField::*;
+        match *self {
+            Sport => 2,
+            Dport => 2,
+            Type => 1,
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::DccpHeaderField::*;
+        match *self {
+            Type => Some(&[0x1e]),
+            _ => None,
+        }
+    }
+}
+
+impl DccpHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], recognizing the packet type, which only spans 4 bits of
+    /// its byte, with the `mask` applied to it.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
+        Ok(match (offset, len, mask) {
+            (0, 2, _) => Self::Sport,
+            (2, 2, _) => Self::Dport,
+            (8, 1, Some([0x1e])) => Self::Type,
+            _ => return Err(DecodeError::UnknownDccpHeaderField(offset, len)),
+        })
+    }
+}
+
+/// Fields of the IPsec Authentication Header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum AhHeaderField {
+    NextHeader,
+    /// Length of the header, in 32-bit words minus 2.
+    HdrLength,
+    Reserved,
+    /// Security Parameters Index.
+    Spi,
+    Sequence,
+}
+
+impl HeaderField for AhHeaderField {
+    fn offset(&self) -> u32 {
+        use self::AhHeaderField::*;
+        match *self {
+            NextHeader => 0,
+            HdrLength => 1,
+            Reserved => 2,
+            Spi => 4,
+            Sequence => 8,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::AhHeaderField::*;
+        match *self {
+            NextHeader => 1,
+            HdrLength => 1,
+            Reserved => 2,
+            Spi => 4,
+            Sequence => 4,
+        }
+    }
+}
+
+impl AhHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Ok(match (offset, len) {
+            (0, 1) => Self::NextHeader,
+            (1, 1) => Self::HdrLength,
+            (2, 2) => Self::Reserved,
+            (4, 4) => Self::Spi,
+            (8, 4) => Self::Sequence,
+            _ => return Err(DecodeError::UnknownAhHeaderField(offset, len)),
+        })
+    }
+}
+
+/// Fields of the IPsec Encapsulating Security Payload header.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum EspHeaderField {
+    /// Security Parameters Index.
+    Spi,
+    Sequence,
+}
+
+impl HeaderField for EspHeaderField {
+    fn offset(&self) -> u32 {
+        use self::EspHeaderField::*;
+        match *self {
+            Spi => 0,
+            Sequence => 4,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::EspHeaderField::*;
+        match *self {
+            Spi => 4,
+            Sequence => 4,
+        }
+    }
+}
+
+impl EspHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Ok(match (offset, len) {
+            (0, 4) => Self::Spi,
+            (4, 4) => Self::Sequence,
+            _ => return Err(DecodeError::UnknownEspHeaderField(offset, len)),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum GreHeaderField {
+    /// The checksum, routing, key, sequence number and strict source route flags.
+    Flags,
+    Version,
+    /// EtherType of the encapsulated packet.
+    Protocol,
+}
+
+impl HeaderField for GreHeaderField {
+    fn offset(&self) -> u32 {
+        use self::GreHeaderField::*;
+        match *self {
+            Flags => 0,
+            Version => 1,
+            Protocol => 2,
+        }
+    }
+
+    fn len(&self) -> u32 {
+        use self::GreHeaderField::*;
+        match *self {
+            Flags => 1,
+            Version => 1,
+            Protocol => 2,
+        }
+    }
+
+    fn mask(&self) -> Option<&'static [u8]> {
+        use self::GreHeaderField::*;
+        match *self {
+            Flags => Some(&[0xf8]),
+            Version => Some(&[0x07]),
+            _ => None,
+        }
+    }
+}
+
+impl GreHeaderField {
+    pub fn from_raw_data(offset: u32, len: u32) -> Result<Self, DecodeError> {
+        Self::from_raw_data_with_mask(offset, len, None)
+    }
+
+    /// Like [`Self::from_raw_data`], recognizing the flags and the version, which only span
+    /// parts of their bytes, with the `mask` applied to them.
+    pub fn from_raw_data_with_mask(
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Result<Self, DecodeError> {
+        Ok(match (offset, len, mask) {
+            (0, 1, Some([0xf8])) => Self::Flags,
+            (1, 1, Some([0x07])) => Self::Version,
+            (2, 2, _) => Self::Protocol,
+            _ => return Err(DecodeError::UnknownGreHeaderField(offset, len)),
+        })
+    }
+}
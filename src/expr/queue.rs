@@ -0,0 +1,72 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::{Expression, Register};
+use crate::error::BuilderError;
+use crate::sys::{
+    NFTA_QUEUE_FLAGS, NFTA_QUEUE_NUM, NFTA_QUEUE_SREG_QNUM, NFTA_QUEUE_TOTAL,
+    NFT_QUEUE_FLAG_BYPASS, NFT_QUEUE_FLAG_CPU_FANOUT,
+};
+
+bitflags::bitflags! {
+    /// The flags of a [`Queue`] expression.
+    pub struct QueueFlags: u16 {
+        /// Accept the packets instead of dropping them when no program listens on the queue.
+        const BYPASS = NFT_QUEUE_FLAG_BYPASS as u16;
+        /// Pick the queue from the CPU handling the packet instead of the hash of its flow.
+        const FANOUT = NFT_QUEUE_FLAG_CPU_FANOUT as u16;
+    }
+}
+
+/// A queue expression sends the packets to a userspace program through NFQUEUE. The program
+/// then issues the verdict.
+///
+/// When several queues are given, the packets are spread over them according to the hash of
+/// their flow, or to the CPU handling them with [`QueueFlags::FANOUT`].
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Queue {
+    #[field(NFTA_QUEUE_NUM)]
+    num: u16,
+    #[field(NFTA_QUEUE_TOTAL)]
+    total: u16,
+    #[field(NFTA_QUEUE_FLAGS)]
+    flags: u16,
+    #[field(NFTA_QUEUE_SREG_QNUM)]
+    sreg_qnum: Register,
+}
+
+impl Queue {
+    /// Sends the packets to the queue `num`.
+    pub fn new(num: u16, flags: QueueFlags) -> Self {
+        Queue::default().with_num(num).with_flags(flags.bits())
+    }
+
+    /// Sends the packets to the queues `first` to `last`, inclusive
+    /// (`queue num first-last`). Fails with [`BuilderError::InvalidQueueRange`] if `last` is
+    /// lower than `first`, or if the range holds more queues than the kernel can address.
+    pub fn new_range(first: u16, last: u16, flags: QueueFlags) -> Result<Self, BuilderError> {
+        if last < first {
+            return Err(BuilderError::InvalidQueueRange);
+        }
+        let total = u16::try_from(last as u32 - first as u32 + 1)
+            .map_err(|_| BuilderError::InvalidQueueRange)?;
+        Ok(Queue::new(first, flags).with_total(total))
+    }
+
+    /// Sends the packets to the queue whose number is stored in `sreg`, for instance by a
+    /// [`Numgen`] or [`Hash`] expression.
+    ///
+    /// [`Numgen`]: super::Numgen
+    /// [`Hash`]: super::Hash
+    pub fn new_from_register(sreg: Register, flags: QueueFlags) -> Self {
+        Queue::default()
+            .with_sreg_qnum(sreg)
+            .with_flags(flags.bits())
+    }
+}
+
+impl Expression for Queue {
+    fn get_name() -> &'static str {
+        "queue"
+    }
+}
@@ -0,0 +1,50 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::Expression;
+use crate::sys::{
+    NFTA_QUOTA_BYTES, NFTA_QUOTA_CONSUMED, NFTA_QUOTA_FLAGS, NFT_QUOTA_F_DEPLETED, NFT_QUOTA_F_INV,
+};
+
+/// A quota expression matches packets until a given number of bytes went through it.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Quota {
+    #[field(NFTA_QUOTA_BYTES)]
+    bytes: u64,
+    #[field(NFTA_QUOTA_FLAGS)]
+    flags: u32,
+    #[field(NFTA_QUOTA_CONSUMED)]
+    consumed: u64,
+}
+
+impl Quota {
+    /// Returns a quota matching packets until `bytes` bytes went through it.
+    pub fn new(bytes: u64) -> Self {
+        Quota::default().with_bytes(bytes).with_flags(0u32)
+    }
+
+    /// Returns a quota matching packets once `bytes` bytes went through it.
+    pub fn new_over(bytes: u64) -> Self {
+        Quota::default()
+            .with_bytes(bytes)
+            .with_flags(NFT_QUOTA_F_INV)
+    }
+
+    /// Whether the quota matches the packets over the quota instead of those under it.
+    pub fn is_over(&self) -> bool {
+        self.get_flags()
+            .is_some_and(|flags| flags & NFT_QUOTA_F_INV != 0)
+    }
+
+    /// Whether the quota was exhausted, as reported by the kernel.
+    pub fn is_depleted(&self) -> bool {
+        self.get_flags()
+            .is_some_and(|flags| flags & NFT_QUOTA_F_DEPLETED != 0)
+    }
+}
+
+impl Expression for Quota {
+    fn get_name() -> &'static str {
+        "quota"
+    }
+}
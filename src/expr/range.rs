@@ -0,0 +1,63 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::error::BuilderError;
+use crate::parser_impls::NfNetlinkData;
+use crate::sys::{
+    NFTA_RANGE_FROM_DATA, NFTA_RANGE_OP, NFTA_RANGE_SREG, NFTA_RANGE_TO_DATA, NFT_RANGE_EQ,
+    NFT_RANGE_NEQ,
+};
+
+/// Range operator.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum RangeOp {
+    /// The value is in the range.
+    Eq = NFT_RANGE_EQ,
+    /// The value is outside of the range.
+    Neq = NFT_RANGE_NEQ,
+}
+
+/// Range expression. Checks whether the content of a register lies between two values,
+/// inclusive (`tcp dport 1000-2000`).
+///
+/// The values are compared byte by byte, so they must be in network byte order.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Range {
+    #[field(NFTA_RANGE_SREG)]
+    sreg: Register,
+    #[field(NFTA_RANGE_OP)]
+    op: RangeOp,
+    #[field(NFTA_RANGE_FROM_DATA)]
+    from_data: NfNetlinkData,
+    #[field(NFTA_RANGE_TO_DATA)]
+    to_data: NfNetlinkData,
+}
+
+impl Range {
+    /// Returns a new range expression checking the value loaded in the first register against
+    /// the range going from `from` to `to`, using the operator `op`.
+    pub fn new(
+        op: RangeOp,
+        from: impl Into<Vec<u8>>,
+        to: impl Into<Vec<u8>>,
+    ) -> Result<Self, BuilderError> {
+        let from = from.into();
+        let to = to.into();
+        if from.len() != to.len() {
+            return Err(BuilderError::IncompatibleLength);
+        }
+        Ok(Range::default()
+            .with_sreg(Register::Reg1)
+            .with_op(op)
+            .with_from_data(NfNetlinkData::default().with_value(from))
+            .with_to_data(NfNetlinkData::default().with_value(to)))
+    }
+}
+
+impl Expression for Range {
+    fn get_name() -> &'static str {
+        "range"
+    }
+}
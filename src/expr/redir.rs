@@ -0,0 +1,27 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::{Expression, Register};
+use crate::sys::{NFTA_REDIR_FLAGS, NFTA_REDIR_REG_PROTO_MAX, NFTA_REDIR_REG_PROTO_MIN};
+
+/// Redirects the packets to the local machine, by setting their destination address to the
+/// primary address of the input interface.
+///
+/// The destination port is changed to a port in the range going from the value of
+/// `reg_proto_min` to the value of `reg_proto_max`, or left untouched when no register is given.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct(nested = true)]
+pub struct Redir {
+    #[field(NFTA_REDIR_REG_PROTO_MIN)]
+    reg_proto_min: Register,
+    #[field(NFTA_REDIR_REG_PROTO_MAX)]
+    reg_proto_max: Register,
+    /// See [`NatFlags`](super::NatFlags).
+    #[field(NFTA_REDIR_FLAGS)]
+    flags: u32,
+}
+
+impl Expression for Redir {
+    fn get_name() -> &'static str {
+        "redir"
+    }
+}
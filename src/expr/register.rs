@@ -2,16 +2,184 @@ use std::fmt::Debug;
 
 use rustables_macros::nfnetlink_enum;
 
-use crate::sys::{NFT_REG_1, NFT_REG_2, NFT_REG_3, NFT_REG_4, NFT_REG_VERDICT};
+use crate::error::BuilderError;
+use crate::sys::{
+    NFT_REG32_00, NFT_REG32_01, NFT_REG32_02, NFT_REG32_03, NFT_REG32_04, NFT_REG32_05,
+    NFT_REG32_06, NFT_REG32_07, NFT_REG32_08, NFT_REG32_09, NFT_REG32_10, NFT_REG32_11,
+    NFT_REG32_12, NFT_REG32_13, NFT_REG32_14, NFT_REG32_15, NFT_REG32_SIZE, NFT_REG_1, NFT_REG_2,
+    NFT_REG_3, NFT_REG_4, NFT_REG_SIZE, NFT_REG_VERDICT,
+};
 
 /// A netfilter data register. The expressions store and read data to and from these when
 /// evaluating rule statements.
+///
+/// The data registers form a single 64 bytes long register file, which can be addressed either
+/// with the four legacy 16 bytes long registers `Reg1` to `Reg4`, or with the sixteen 32 bits
+/// long registers `Reg32_00` to `Reg32_15`. A value longer than a register spills over the
+/// following ones.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[nfnetlink_enum(u32)]
+#[allow(non_camel_case_types)]
 pub enum Register {
     Verdict = NFT_REG_VERDICT,
     Reg1 = NFT_REG_1,
     Reg2 = NFT_REG_2,
     Reg3 = NFT_REG_3,
     Reg4 = NFT_REG_4,
+    Reg32_00 = NFT_REG32_00,
+    Reg32_01 = NFT_REG32_01,
+    Reg32_02 = NFT_REG32_02,
+    Reg32_03 = NFT_REG32_03,
+    Reg32_04 = NFT_REG32_04,
+    Reg32_05 = NFT_REG32_05,
+    Reg32_06 = NFT_REG32_06,
+    Reg32_07 = NFT_REG32_07,
+    Reg32_08 = NFT_REG32_08,
+    Reg32_09 = NFT_REG32_09,
+    Reg32_10 = NFT_REG32_10,
+    Reg32_11 = NFT_REG32_11,
+    Reg32_12 = NFT_REG32_12,
+    Reg32_13 = NFT_REG32_13,
+    Reg32_14 = NFT_REG32_14,
+    Reg32_15 = NFT_REG32_15,
+}
+
+/// The 32 bits registers, in the order of the register file.
+const REGISTERS_32: [Register; NB_REGISTERS_32] = [
+    Register::Reg32_00,
+    Register::Reg32_01,
+    Register::Reg32_02,
+    Register::Reg32_03,
+    Register::Reg32_04,
+    Register::Reg32_05,
+    Register::Reg32_06,
+    Register::Reg32_07,
+    Register::Reg32_08,
+    Register::Reg32_09,
+    Register::Reg32_10,
+    Register::Reg32_11,
+    Register::Reg32_12,
+    Register::Reg32_13,
+    Register::Reg32_14,
+    Register::Reg32_15,
+];
+
+/// The legacy registers, in the order of the register file.
+const LEGACY_REGISTERS: [Register; 4] = [
+    Register::Reg1,
+    Register::Reg2,
+    Register::Reg3,
+    Register::Reg4,
+];
+
+const NB_REGISTERS_32: usize = 16;
+
+impl Register {
+    /// Returns the offset in bytes of this register in the register file, or `None` for the
+    /// verdict register.
+    pub fn data_offset(&self) -> Option<u32> {
+        if let Some(idx) = LEGACY_REGISTERS.iter().position(|reg| reg == self) {
+            Some(idx as u32 * NFT_REG_SIZE)
+        } else {
+            REGISTERS_32
+                .iter()
+                .position(|reg| reg == self)
+                .map(|idx| idx as u32 * NFT_REG32_SIZE)
+        }
+    }
+
+    /// Returns the register starting at `offset` bytes in the register file. This is a legacy
+    /// register when the offset is aligned on their size, as it is understood by every kernel,
+    /// and a 32 bits register otherwise.
+    pub fn from_data_offset(offset: u32) -> Option<Self> {
+        if offset % NFT_REG_SIZE == 0 {
+            LEGACY_REGISTERS
+                .get((offset / NFT_REG_SIZE) as usize)
+                .copied()
+        } else if offset % NFT_REG32_SIZE == 0 {
+            REGISTERS_32
+                .get((offset / NFT_REG32_SIZE) as usize)
+                .copied()
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether both registers designate the same location in the register file, as
+    /// `Reg1` and `Reg32_00` do.
+    pub fn same_location(&self, other: &Register) -> bool {
+        self.data_offset() == other.data_offset()
+    }
+}
+
+/// A value stored in the register file, starting at `reg` and spanning `len` bytes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RegisterValue {
+    reg: Register,
+    len: u32,
+}
+
+impl RegisterValue {
+    pub fn new(reg: Register, len: u32) -> Self {
+        RegisterValue { reg, len }
+    }
+
+    /// The register holding the beginning of the value.
+    pub fn get_reg(&self) -> Register {
+        self.reg
+    }
+
+    /// The length of the value, in bytes.
+    pub fn get_len(&self) -> u32 {
+        self.len
+    }
+}
+
+/// Keeps track of the registers in use while building a rule, so that several values can be live
+/// at the same time.
+///
+/// The register file is allocated by chunks of 32 bits, so that small values do not waste a whole
+/// legacy register.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisterAllocator {
+    used: [bool; NB_REGISTERS_32],
+}
+
+impl RegisterAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves enough contiguous space in the register file to hold a value of `len` bytes. May
+    /// return BuilderError::NoRegisterAvailable if the register file is too fragmented or full.
+    pub fn allocate(&mut self, len: u32) -> Result<RegisterValue, BuilderError> {
+        let nb_regs = nb_registers(len);
+        if nb_regs > self.used.len() {
+            return Err(BuilderError::NoRegisterAvailable);
+        }
+        let start = (0..=self.used.len() - nb_regs)
+            .find(|start| self.used[*start..*start + nb_regs].iter().all(|used| !used))
+            .ok_or(BuilderError::NoRegisterAvailable)?;
+        for used in &mut self.used[start..start + nb_regs] {
+            *used = true;
+        }
+        let reg = Register::from_data_offset(start as u32 * NFT_REG32_SIZE).unwrap();
+        Ok(RegisterValue::new(reg, len))
+    }
+
+    /// Releases the registers holding `value`, so they can be reused by the next allocations.
+    pub fn free(&mut self, value: RegisterValue) {
+        if let Some(offset) = value.reg.data_offset() {
+            let start = (offset / NFT_REG32_SIZE) as usize;
+            let end = (start + nb_registers(value.len)).min(self.used.len());
+            for used in &mut self.used[start..end] {
+                *used = false;
+            }
+        }
+    }
+}
+
+/// Number of 32 bits registers needed to hold a value of `len` bytes.
+fn nb_registers(len: u32) -> usize {
+    len.max(1).div_ceil(NFT_REG32_SIZE) as usize
 }
@@ -13,11 +13,25 @@ impl Expression for Reject {
 #[derive(Clone, PartialEq, Eq, Default, Debug, Hash)]
 #[nfnetlink_struct]
 /// A reject expression that defines the type of rejection message sent when discarding a packet.
+///
+/// The ICMP code is an [`IcmpCode`] for [`RejectType::IcmpxUnreach`], and an [`Icmpv4Code`] or an
+/// [`Icmpv6Code`], depending on the family of the packet, for [`RejectType::IcmpUnreach`].
 pub struct Reject {
     #[field(sys::NFTA_REJECT_TYPE, name_in_functions = "type")]
     reject_type: RejectType,
     #[field(sys::NFTA_REJECT_ICMP_CODE)]
-    icmp_code: IcmpCode,
+    icmp_code: u8,
+}
+
+impl Reject {
+    /// Returns the ICMPX code of a [`RejectType::IcmpxUnreach`] reject, while
+    /// [`Reject::get_icmp_code`] returns the raw code whatever the type of the reject.
+    pub fn get_icmpx_code(&self) -> Option<IcmpCode> {
+        match self.get_type() {
+            Some(RejectType::IcmpxUnreach) => IcmpCode::try_from(*self.get_icmp_code()?).ok(),
+            _ => None,
+        }
+    }
 }
 
 /// An ICMP reject code.
@@ -38,3 +52,122 @@ pub enum IcmpCode {
     HostUnreach = sys::NFT_REJECT_ICMPX_HOST_UNREACH,
     AdminProhibited = sys::NFT_REJECT_ICMPX_ADMIN_PROHIBITED,
 }
+
+impl From<IcmpCode> for u8 {
+    fn from(code: IcmpCode) -> u8 {
+        code as u8
+    }
+}
+
+// from the ICMP_* codes of the destination unreachable messages in linux/icmp.h
+const ICMP_NET_UNREACH: u8 = 0;
+const ICMP_HOST_UNREACH: u8 = 1;
+const ICMP_PROT_UNREACH: u8 = 2;
+const ICMP_PORT_UNREACH: u8 = 3;
+const ICMP_FRAG_NEEDED: u8 = 4;
+const ICMP_SR_FAILED: u8 = 5;
+const ICMP_NET_UNKNOWN: u8 = 6;
+const ICMP_HOST_UNKNOWN: u8 = 7;
+const ICMP_HOST_ISOLATED: u8 = 8;
+const ICMP_NET_ANO: u8 = 9;
+const ICMP_HOST_ANO: u8 = 10;
+const ICMP_NET_UNR_TOS: u8 = 11;
+const ICMP_HOST_UNR_TOS: u8 = 12;
+const ICMP_PKT_FILTERED: u8 = 13;
+const ICMP_PREC_VIOLATION: u8 = 14;
+const ICMP_PREC_CUTOFF: u8 = 15;
+
+/// The code of an ICMP destination unreachable message.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum Icmpv4Code {
+    NetUnreach = ICMP_NET_UNREACH,
+    HostUnreach = ICMP_HOST_UNREACH,
+    ProtUnreach = ICMP_PROT_UNREACH,
+    PortUnreach = ICMP_PORT_UNREACH,
+    FragNeeded = ICMP_FRAG_NEEDED,
+    SrFailed = ICMP_SR_FAILED,
+    NetUnknown = ICMP_NET_UNKNOWN,
+    HostUnknown = ICMP_HOST_UNKNOWN,
+    HostIsolated = ICMP_HOST_ISOLATED,
+    NetProhibited = ICMP_NET_ANO,
+    HostProhibited = ICMP_HOST_ANO,
+    NetTosUnreach = ICMP_NET_UNR_TOS,
+    HostTosUnreach = ICMP_HOST_UNR_TOS,
+    AdminProhibited = ICMP_PKT_FILTERED,
+    HostPrecViolation = ICMP_PREC_VIOLATION,
+    PrecCutoff = ICMP_PREC_CUTOFF,
+}
+
+impl From<Icmpv4Code> for u8 {
+    fn from(code: Icmpv4Code) -> u8 {
+        code as u8
+    }
+}
+
+impl From<IcmpCode> for Icmpv4Code {
+    /// The ICMP code used by the kernel in place of an ICMPX code.
+    fn from(code: IcmpCode) -> Self {
+        match code {
+            IcmpCode::NoRoute => Icmpv4Code::NetUnreach,
+            IcmpCode::PortUnreach => Icmpv4Code::PortUnreach,
+            IcmpCode::HostUnreach => Icmpv4Code::HostUnreach,
+            IcmpCode::AdminProhibited => Icmpv4Code::AdminProhibited,
+        }
+    }
+}
+
+// from the ICMPV6_* codes of the destination unreachable messages in linux/icmpv6.h
+const ICMPV6_NOROUTE: u8 = 0;
+const ICMPV6_ADM_PROHIBITED: u8 = 1;
+const ICMPV6_NOT_NEIGHBOUR: u8 = 2;
+const ICMPV6_ADDR_UNREACH: u8 = 3;
+const ICMPV6_PORT_UNREACH: u8 = 4;
+const ICMPV6_POLICY_FAIL: u8 = 5;
+const ICMPV6_REJECT_ROUTE: u8 = 6;
+
+/// The code of an ICMPv6 destination unreachable message.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum Icmpv6Code {
+    NoRoute = ICMPV6_NOROUTE,
+    AdminProhibited = ICMPV6_ADM_PROHIBITED,
+    BeyondScope = ICMPV6_NOT_NEIGHBOUR,
+    AddrUnreach = ICMPV6_ADDR_UNREACH,
+    PortUnreach = ICMPV6_PORT_UNREACH,
+    PolicyFail = ICMPV6_POLICY_FAIL,
+    RejectRoute = ICMPV6_REJECT_ROUTE,
+}
+
+impl From<Icmpv6Code> for u8 {
+    fn from(code: Icmpv6Code) -> u8 {
+        code as u8
+    }
+}
+
+impl From<IcmpCode> for Icmpv6Code {
+    /// The ICMPv6 code used by the kernel in place of an ICMPX code.
+    fn from(code: IcmpCode) -> Self {
+        match code {
+            IcmpCode::NoRoute => Icmpv6Code::NoRoute,
+            IcmpCode::PortUnreach => Icmpv6Code::PortUnreach,
+            IcmpCode::HostUnreach => Icmpv6Code::AddrUnreach,
+            IcmpCode::AdminProhibited => Icmpv6Code::AdminProhibited,
+        }
+    }
+}
+
+/// How the packets are rejected, see [`Rule::reject`].
+///
+/// [`Rule::reject`]: crate::Rule::reject
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RejectKind {
+    /// An ICMP or ICMPv6 error, depending on the family of the packet.
+    Icmpx(IcmpCode),
+    /// An ICMP error, for IPv4 packets only.
+    Icmp(Icmpv4Code),
+    /// An ICMPv6 error, for IPv6 packets only.
+    Icmpv6(Icmpv6Code),
+    /// A TCP reset, for TCP packets only.
+    TcpReset,
+}
@@ -0,0 +1,57 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_RT_DREG, NFTA_RT_KEY, NFT_RT_CLASSID, NFT_RT_NEXTHOP4, NFT_RT_NEXTHOP6, NFT_RT_TCPMSS,
+    NFT_RT_XFRM,
+};
+
+/// The routing information retrieved by a [`Rt`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum RtKey {
+    /// Routing realm.
+    ClassId = NFT_RT_CLASSID,
+    /// IPv4 address of the next hop.
+    NextHop4 = NFT_RT_NEXTHOP4,
+    /// IPv6 address of the next hop.
+    NextHop6 = NFT_RT_NEXTHOP6,
+    /// TCP maximum segment size of the route, derived from its MTU.
+    TcpMss = NFT_RT_TCPMSS,
+    /// Whether the packet will be transformed by IPsec.
+    Xfrm = NFT_RT_XFRM,
+}
+
+impl RtKey {
+    /// Length in bytes of the value loaded in a register for this key.
+    pub fn value_len(&self) -> u32 {
+        match *self {
+            RtKey::ClassId | RtKey::NextHop4 => 4,
+            RtKey::NextHop6 => 16,
+            RtKey::TcpMss => 2,
+            RtKey::Xfrm => 1,
+        }
+    }
+}
+
+/// A rt expression retrieves information about the route of the packet.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Rt {
+    #[field(NFTA_RT_DREG)]
+    dreg: Register,
+    #[field(NFTA_RT_KEY)]
+    key: RtKey,
+}
+
+impl Rt {
+    pub fn new(key: RtKey) -> Self {
+        Rt::default().with_dreg(Register::Reg1).with_key(key)
+    }
+}
+
+impl Expression for Rt {
+    fn get_name() -> &'static str {
+        "rt"
+    }
+}
@@ -0,0 +1,62 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_SOCKET_DREG, NFTA_SOCKET_KEY, NFTA_SOCKET_LEVEL, NFT_SOCKET_CGROUPV2, NFT_SOCKET_MARK,
+    NFT_SOCKET_TRANSPARENT, NFT_SOCKET_WILDCARD,
+};
+
+/// The information retrieved by a [`Socket`] expression.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum SocketKey {
+    /// Whether the socket has the `IP_TRANSPARENT` option.
+    Transparent = NFT_SOCKET_TRANSPARENT,
+    /// Mark of the socket.
+    Mark = NFT_SOCKET_MARK,
+    /// Whether the socket is bound to the wildcard address.
+    Wildcard = NFT_SOCKET_WILDCARD,
+    /// Identifier of the cgroup v2 of the socket, at the level set on the expression.
+    CgroupV2 = NFT_SOCKET_CGROUPV2,
+}
+
+impl SocketKey {
+    /// Length in bytes of the value loaded in a register for this key.
+    pub fn value_len(&self) -> u32 {
+        match *self {
+            SocketKey::Transparent | SocketKey::Wildcard => 1,
+            SocketKey::Mark => 4,
+            SocketKey::CgroupV2 => 8,
+        }
+    }
+}
+
+/// A socket expression retrieves information about the local socket the packet belongs to. The
+/// rule does not match if there is no such socket.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Socket {
+    #[field(NFTA_SOCKET_KEY)]
+    key: SocketKey,
+    #[field(NFTA_SOCKET_DREG)]
+    dreg: Register,
+    #[field(NFTA_SOCKET_LEVEL)]
+    level: u32,
+}
+
+impl Socket {
+    pub fn new(key: SocketKey) -> Self {
+        Socket::default().with_key(key).with_dreg(Register::Reg1)
+    }
+
+    /// Retrieves the identifier of the ancestor at `level` of the cgroup v2 of the socket.
+    pub fn new_cgroupv2(level: u32) -> Self {
+        Socket::new(SocketKey::CgroupV2).with_level(level)
+    }
+}
+
+impl Expression for Socket {
+    fn get_name() -> &'static str {
+        "socket"
+    }
+}
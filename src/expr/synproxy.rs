@@ -0,0 +1,65 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::Expression;
+use crate::sys::{NFTA_SYNPROXY_FLAGS, NFTA_SYNPROXY_MSS, NFTA_SYNPROXY_WSCALE};
+
+// from linux/netfilter/nf_synproxy.h
+const NF_SYNPROXY_OPT_MSS: u32 = 0x01;
+const NF_SYNPROXY_OPT_WSCALE: u32 = 0x02;
+const NF_SYNPROXY_OPT_SACK_PERM: u32 = 0x04;
+const NF_SYNPROXY_OPT_TIMESTAMP: u32 = 0x08;
+const NF_SYNPROXY_OPT_ECN: u32 = 0x10;
+
+bitflags::bitflags! {
+    /// The TCP options a [`Synproxy`] announces in the SYN-ACK packets it answers with.
+    pub struct SynproxyFlags: u32 {
+        /// Announce the maximum segment size of the [`Synproxy`].
+        const MSS = NF_SYNPROXY_OPT_MSS;
+        /// Announce the window scale of the [`Synproxy`].
+        const WSCALE = NF_SYNPROXY_OPT_WSCALE;
+        /// Announce the selective acknowledgement support.
+        const SACK_PERM = NF_SYNPROXY_OPT_SACK_PERM;
+        /// Announce the timestamp option, needed to pass the window scale and SACK options.
+        const TIMESTAMP = NF_SYNPROXY_OPT_TIMESTAMP;
+        const ECN = NF_SYNPROXY_OPT_ECN;
+    }
+}
+
+/// A synproxy expression answers the TCP SYN packets with SYN cookies, and only opens the
+/// connection to the server once the client completed the handshake, which protects the server
+/// from SYN floods.
+///
+/// The packets must not be tracked when they reach the expression, see [`Rule::notrack`] and
+/// [`Rule::synproxy`].
+///
+/// [`Rule::notrack`]: crate::Rule::notrack
+/// [`Rule::synproxy`]: crate::Rule::synproxy
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Synproxy {
+    #[field(NFTA_SYNPROXY_MSS)]
+    mss: u16,
+    #[field(NFTA_SYNPROXY_WSCALE)]
+    wscale: u8,
+    /// See [`SynproxyFlags`].
+    #[field(NFTA_SYNPROXY_FLAGS)]
+    flags: u32,
+}
+
+impl Synproxy {
+    /// Returns a synproxy announcing a maximum segment size of `mss` and a window scale of
+    /// `wscale`, which should match those of the protected servers, along with the options in
+    /// `flags` (`synproxy mss 1460 wscale 7 timestamp sack-perm`).
+    pub fn new(mss: u16, wscale: u8, flags: SynproxyFlags) -> Self {
+        Synproxy::default()
+            .with_mss(mss)
+            .with_wscale(wscale)
+            .with_flags((flags | SynproxyFlags::MSS | SynproxyFlags::WSCALE).bits())
+    }
+}
+
+impl Expression for Synproxy {
+    fn get_name() -> &'static str {
+        "synproxy"
+    }
+}
@@ -0,0 +1,34 @@
+use rustables_macros::nfnetlink_struct;
+
+use super::{Expression, Register};
+use crate::sys::{NFTA_TPROXY_FAMILY, NFTA_TPROXY_REG_ADDR, NFTA_TPROXY_REG_PORT};
+use crate::ProtocolFamily;
+
+/// A tproxy expression diverts the packet to a local socket, without changing its destination
+/// address, so that a transparent proxy can handle it.
+///
+/// The address and port of the socket are read from registers. Either may be omitted to keep the
+/// one of the packet, in which case the family can be [`ProtocolFamily::Unspec`] in `inet`
+/// tables.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Tproxy {
+    #[field(NFTA_TPROXY_FAMILY)]
+    family: ProtocolFamily,
+    #[field(NFTA_TPROXY_REG_ADDR)]
+    reg_addr: Register,
+    #[field(NFTA_TPROXY_REG_PORT)]
+    reg_port: Register,
+}
+
+impl Tproxy {
+    pub fn new(family: ProtocolFamily) -> Self {
+        Tproxy::default().with_family(family)
+    }
+}
+
+impl Expression for Tproxy {
+    fn get_name() -> &'static str {
+        "tproxy"
+    }
+}
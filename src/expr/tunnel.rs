@@ -0,0 +1,58 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_TUNNEL_DREG, NFTA_TUNNEL_KEY, NFTA_TUNNEL_MODE, NFT_TUNNEL_ID, NFT_TUNNEL_MODE_NONE,
+    NFT_TUNNEL_MODE_RX, NFT_TUNNEL_MODE_TX, NFT_TUNNEL_PATH,
+};
+
+/// The tunnel metadata loaded by a [`Tunnel`] expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum TunnelKey {
+    /// Whether the packet carries tunnel metadata, as a boolean (`tunnel path`).
+    Path = NFT_TUNNEL_PATH,
+    /// Tunnel id of the packet, e.g. the VNI for VXLAN (`tunnel id`).
+    Id = NFT_TUNNEL_ID,
+}
+
+/// Which tunnel metadata of the packet a [`Tunnel`] expression looks at.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum TunnelMode {
+    /// Whichever is attached to the packet.
+    None = NFT_TUNNEL_MODE_NONE,
+    /// The metadata of the tunnel the packet was received from.
+    Rx = NFT_TUNNEL_MODE_RX,
+    /// The metadata of the tunnel the packet will be sent through.
+    Tx = NFT_TUNNEL_MODE_TX,
+}
+
+/// A tunnel expression loads the metadata attached to the packets by the collect-metadata mode
+/// of the tunnel devices (`ip link add vxlan0 type vxlan external`), only available in the
+/// `netdev` family.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Tunnel {
+    #[field(NFTA_TUNNEL_KEY)]
+    key: TunnelKey,
+    #[field(NFTA_TUNNEL_DREG)]
+    dreg: Register,
+    #[field(NFTA_TUNNEL_MODE)]
+    mode: TunnelMode,
+}
+
+impl Tunnel {
+    pub fn new(key: TunnelKey, mode: TunnelMode) -> Self {
+        Tunnel::default()
+            .with_key(key)
+            .with_dreg(Register::Reg1)
+            .with_mode(mode)
+    }
+}
+
+impl Expression for Tunnel {
+    fn get_name() -> &'static str {
+        "tunnel"
+    }
+}
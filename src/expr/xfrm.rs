@@ -0,0 +1,77 @@
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use super::{Expression, Register};
+use crate::sys::{
+    NFTA_XFRM_DIR, NFTA_XFRM_DREG, NFTA_XFRM_KEY, NFTA_XFRM_SPNUM, NFT_XFRM_KEY_DADDR_IP4,
+    NFT_XFRM_KEY_DADDR_IP6, NFT_XFRM_KEY_REQID, NFT_XFRM_KEY_SADDR_IP4, NFT_XFRM_KEY_SADDR_IP6,
+    NFT_XFRM_KEY_SPI,
+};
+
+// from linux/xfrm.h
+const XFRM_POLICY_IN: u8 = 0;
+const XFRM_POLICY_OUT: u8 = 1;
+
+/// The information about the IPsec state of a packet loaded by an [`Xfrm`] expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum XfrmKey {
+    /// Destination address of the IPv4 tunnel.
+    DaddrIp4 = NFT_XFRM_KEY_DADDR_IP4,
+    /// Destination address of the IPv6 tunnel.
+    DaddrIp6 = NFT_XFRM_KEY_DADDR_IP6,
+    /// Source address of the IPv4 tunnel.
+    SaddrIp4 = NFT_XFRM_KEY_SADDR_IP4,
+    /// Source address of the IPv6 tunnel.
+    SaddrIp6 = NFT_XFRM_KEY_SADDR_IP6,
+    /// Request id of the security association, in host byte order.
+    Reqid = NFT_XFRM_KEY_REQID,
+    /// Security parameter index of the security association, in network byte order.
+    Spi = NFT_XFRM_KEY_SPI,
+}
+
+/// Whether an [`Xfrm`] expression looks at the state the packet was decapsulated with, or the
+/// one it will be encapsulated with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum XfrmDirection {
+    /// The packet was received through IPsec (`ipsec in`), in the prerouting, input and
+    /// forward hooks.
+    In = XFRM_POLICY_IN,
+    /// The packet will be sent through IPsec (`ipsec out`), in the forward, output and
+    /// postrouting hooks.
+    Out = XFRM_POLICY_OUT,
+}
+
+/// An xfrm expression loads information about the IPsec security association of the packet,
+/// for instance to only accept the traffic decapsulated from a given tunnel
+/// (`ipsec in reqid 1`). Packets not going through IPsec do not match.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Xfrm {
+    #[field(NFTA_XFRM_DREG)]
+    dreg: Register,
+    #[field(NFTA_XFRM_KEY)]
+    key: XfrmKey,
+    #[field(NFTA_XFRM_DIR)]
+    dir: XfrmDirection,
+    /// Index of the transformation in the stack of states of the packet, 0 for the outermost
+    /// one (`ipsec in spnum 1`).
+    #[field(NFTA_XFRM_SPNUM)]
+    spnum: u32,
+}
+
+impl Xfrm {
+    pub fn new(key: XfrmKey, dir: XfrmDirection) -> Self {
+        Xfrm::default()
+            .with_dreg(Register::Reg1)
+            .with_key(key)
+            .with_dir(dir)
+            .with_spnum(0u32)
+    }
+}
+
+impl Expression for Xfrm {
+    fn get_name() -> &'static str {
+        "xfrm"
+    }
+}
@@ -64,10 +64,17 @@ mod chain;
 pub use chain::{list_chains_for_table, list_chains_for_table_async};
 pub use chain::{Chain, ChainPolicy, ChainPriority, ChainType, Hook, HookClass};
 
+mod object;
+pub use object::{list_objects_for_table, list_objects_for_table_async};
+pub use object::{Object, ObjectData, ObjectType, Secmark};
+
 pub mod error;
 
 pub mod query;
 
+pub mod nflog;
+pub mod nfqueue;
+
 pub mod nlmsg;
 pub(crate) mod parser;
 pub(crate) mod parser_impls;
@@ -79,13 +86,21 @@ pub use rule::Rule;
 pub mod expr;
 
 mod rule_methods;
-pub use rule_methods::{iface_index, Protocol};
+pub use rule_methods::{iface_index, NatAddress, Protocol};
+
+mod rule_builder;
+pub use rule_builder::{LoadSource, RuleBuilder};
+
+mod rule_decode;
+pub use rule_decode::{DecodedRule, Match};
 
 pub mod set;
 pub use set::Set;
 
 pub mod sys;
 
+pub mod trace;
+
 pub mod util;
 
 
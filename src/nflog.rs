@@ -0,0 +1,452 @@
+//! Userspace side of the nfnetlink_log subsystem: binds a netlink socket to the NFLOG groups
+//! packets are sent to by [`Log`] expressions, and decodes the packets received on it.
+//!
+//! [`Log`]: crate::expr::Log
+
+use std::convert::TryFrom;
+
+use netlink_sys::{AsyncSocket, AsyncSocketExt, Socket};
+use nix::sys::socket::MsgFlags;
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use crate::{
+    error::{BuilderError, DecodeError, QueryError},
+    nlmsg::{NfNetlinkAttribute, NfNetlinkDeserializable, NfNetlinkWriter},
+    parser::parse_subsystem_object,
+    query::{
+        recv_and_process, recv_and_process_async, recv_subsystem_objects,
+        recv_subsystem_objects_async,
+    },
+    sys::{
+        NFNL_SUBSYS_ULOG, NFULA_CFG_CMD, NFULA_CFG_FLAGS, NFULA_CFG_MODE, NFULA_CFG_NLBUFSIZ,
+        NFULA_CFG_QTHRESH, NFULA_CFG_TIMEOUT, NFULA_CT, NFULA_CT_INFO, NFULA_GID, NFULA_HWADDR,
+        NFULA_HWHEADER, NFULA_HWLEN, NFULA_HWTYPE, NFULA_IFINDEX_INDEV, NFULA_IFINDEX_OUTDEV,
+        NFULA_IFINDEX_PHYSINDEV, NFULA_IFINDEX_PHYSOUTDEV, NFULA_L2HDR, NFULA_MARK,
+        NFULA_PACKET_HDR, NFULA_PAYLOAD, NFULA_PREFIX, NFULA_SEQ, NFULA_SEQ_GLOBAL,
+        NFULA_TIMESTAMP, NFULA_UID, NFULA_VLAN, NFULA_VLAN_PROTO, NFULA_VLAN_TCI,
+        NFULNL_CFG_CMD_BIND, NFULNL_CFG_CMD_PF_BIND, NFULNL_CFG_CMD_PF_UNBIND,
+        NFULNL_CFG_CMD_UNBIND, NFULNL_CFG_F_CONNTRACK, NFULNL_CFG_F_SEQ, NFULNL_CFG_F_SEQ_GLOBAL,
+        NFULNL_COPY_META, NFULNL_COPY_NONE, NFULNL_COPY_PACKET, NFULNL_MSG_CONFIG,
+        NFULNL_MSG_PACKET, NLM_F_ACK,
+    },
+    ProtocolFamily,
+};
+
+/// How much of the packets is copied to userspace.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum CopyMode {
+    /// Nothing is copied.
+    None = NFULNL_COPY_NONE,
+    /// Only the metadata of the packets is copied.
+    Meta = NFULNL_COPY_META,
+    /// The metadata and the payload of the packets are copied.
+    Packet = NFULNL_COPY_PACKET,
+}
+
+/// The commands of a NFLOG or NFQUEUE configuration message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u8)]
+pub enum ConfigCommand {
+    /// Binds the socket to the group (or queue).
+    Bind = NFULNL_CFG_CMD_BIND,
+    /// Unbinds the socket from the group (or queue).
+    Unbind = NFULNL_CFG_CMD_UNBIND,
+    /// Registers the subsystem as the logger of a protocol family. This is a no-op since Linux
+    /// 3.8, but older kernels require it.
+    PfBind = NFULNL_CFG_CMD_PF_BIND,
+    /// Unregisters the subsystem as the logger of a protocol family.
+    PfUnbind = NFULNL_CFG_CMD_PF_UNBIND,
+}
+
+/// The copy mode and range of a NFLOG group or NFQUEUE queue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CopyParams {
+    pub mode: CopyMode,
+    /// Maximum number of bytes of payload copied, when `mode` is `CopyMode::Packet`.
+    pub range: u32,
+}
+
+impl CopyParams {
+    pub fn new(mode: CopyMode, range: u32) -> Self {
+        CopyParams { mode, range }
+    }
+}
+
+impl NfNetlinkAttribute for CopyParams {
+    fn get_size(&self) -> usize {
+        6
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        addr[0..4].copy_from_slice(&self.range.to_be_bytes());
+        addr[4] = self.mode as u8;
+        addr[5] = 0;
+    }
+}
+
+impl NfNetlinkDeserializable for CopyParams {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        // the NFQUEUE parameters don't hold the trailing padding byte
+        if buf.len() < 5 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (range, buf) = u32::deserialize(buf)?;
+        let (mode, _) = CopyMode::deserialize(buf)?;
+        Ok((CopyParams { mode, range }, &[]))
+    }
+}
+
+/// The hardware address of the device a packet was received from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct HardwareAddress {
+    len: u16,
+    addr: [u8; 8],
+}
+
+impl HardwareAddress {
+    pub fn new(address: &[u8]) -> Result<Self, BuilderError> {
+        let mut addr = [0; 8];
+        if address.len() > addr.len() {
+            return Err(BuilderError::IncompatibleLength);
+        }
+        addr[..address.len()].copy_from_slice(address);
+        Ok(HardwareAddress {
+            len: address.len() as u16,
+            addr,
+        })
+    }
+
+    pub fn address(&self) -> &[u8] {
+        &self.addr[..(self.len as usize).min(self.addr.len())]
+    }
+}
+
+impl NfNetlinkAttribute for HardwareAddress {
+    fn get_size(&self) -> usize {
+        12
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        addr[0..2].copy_from_slice(&self.len.to_be_bytes());
+        addr[2..4].copy_from_slice(&[0, 0]);
+        addr[4..12].copy_from_slice(&self.addr);
+    }
+}
+
+impl NfNetlinkDeserializable for HardwareAddress {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        if buf.len() < 12 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (len, _) = u16::deserialize(buf)?;
+        let mut addr = [0; 8];
+        addr.copy_from_slice(&buf[4..12]);
+        Ok((HardwareAddress { len, addr }, &buf[12..]))
+    }
+}
+
+/// The time at which a packet was received.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct PacketTimestamp {
+    pub sec: u64,
+    pub usec: u64,
+}
+
+impl NfNetlinkAttribute for PacketTimestamp {
+    fn get_size(&self) -> usize {
+        16
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        self.sec.write_payload(addr);
+        self.usec.write_payload(&mut addr[8..]);
+    }
+}
+
+impl NfNetlinkDeserializable for PacketTimestamp {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        if buf.len() < 16 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (sec, buf) = u64::deserialize(buf)?;
+        let (usec, buf) = u64::deserialize(buf)?;
+        Ok((PacketTimestamp { sec, usec }, buf))
+    }
+}
+
+/// The VLAN tag of a packet, which the kernel reports for the packets of the bridge family.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Hash)]
+#[nfnetlink_struct(nested = true)]
+pub struct PacketVlan {
+    /// The ethertype of the tag (802.1Q or 802.1ad).
+    #[field(NFULA_VLAN_PROTO)]
+    protocol: u16,
+    /// The tag control information: priority, drop eligible indicator and VLAN id.
+    #[field(NFULA_VLAN_TCI)]
+    tci: u16,
+}
+
+// from the ip_conntrack_info enum of the kernel
+const IP_CT_ESTABLISHED: u32 = 0;
+const IP_CT_RELATED: u32 = 1;
+const IP_CT_NEW: u32 = 2;
+const IP_CT_ESTABLISHED_REPLY: u32 = 3;
+const IP_CT_RELATED_REPLY: u32 = 4;
+const IP_CT_UNTRACKED: u32 = 7;
+
+/// The relation of a packet to its connection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum CtInfo {
+    Established = IP_CT_ESTABLISHED,
+    Related = IP_CT_RELATED,
+    New = IP_CT_NEW,
+    EstablishedReply = IP_CT_ESTABLISHED_REPLY,
+    RelatedReply = IP_CT_RELATED_REPLY,
+    Untracked = IP_CT_UNTRACKED,
+}
+
+bitflags::bitflags! {
+    /// The flags of a NFLOG group, as set with [`NflogConfig::with_flags`].
+    pub struct NflogConfigFlags: u16 {
+        /// Adds a sequence number, local to the group, to the packets.
+        const SEQ = NFULNL_CFG_F_SEQ as u16;
+        /// Adds a sequence number, global to all the groups, to the packets.
+        const SEQ_GLOBAL = NFULNL_CFG_F_SEQ_GLOBAL as u16;
+        /// Adds the conntrack information of the packets.
+        const CONNTRACK = NFULNL_CFG_F_CONNTRACK as u16;
+    }
+}
+
+/// A configuration message for a NFLOG group.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_deserialize = false)]
+pub struct NflogConfig {
+    group: u16,
+    family: ProtocolFamily,
+    #[field(NFULA_CFG_CMD)]
+    command: ConfigCommand,
+    #[field(NFULA_CFG_MODE)]
+    copy_params: CopyParams,
+    /// Size of the kernel buffer packets are batched in before being sent, in bytes.
+    #[field(NFULA_CFG_NLBUFSIZ)]
+    buffer_size: u32,
+    /// Maximum time packets are held in the kernel buffer, in hundredths of a second.
+    #[field(NFULA_CFG_TIMEOUT)]
+    timeout: u32,
+    /// Maximum number of packets held in the kernel buffer.
+    #[field(NFULA_CFG_QTHRESH)]
+    qthreshold: u32,
+    /// See [`NflogConfigFlags`].
+    #[field(NFULA_CFG_FLAGS)]
+    flags: u16,
+}
+
+impl NflogConfig {
+    /// Creates an empty configuration message for the NFLOG group `group`.
+    pub fn new(group: u16) -> Self {
+        NflogConfig {
+            group,
+            ..Default::default()
+        }
+    }
+
+    /// Binds the socket the message is sent on to the NFLOG group `group`.
+    pub fn bind(group: u16) -> Self {
+        Self::new(group).with_command(ConfigCommand::Bind)
+    }
+
+    /// Unbinds the socket the message is sent on from the NFLOG group `group`.
+    pub fn unbind(group: u16) -> Self {
+        Self::new(group).with_command(ConfigCommand::Unbind)
+    }
+
+    pub fn get_group(&self) -> u16 {
+        self.group
+    }
+
+    pub fn get_family(&self) -> ProtocolFamily {
+        self.family
+    }
+
+    /// Sets the protocol family of the message, which is only meaningful for the
+    /// `ConfigCommand::PfBind` and `ConfigCommand::PfUnbind` commands.
+    pub fn with_family(mut self, family: ProtocolFamily) -> Self {
+        self.family = family;
+        self
+    }
+
+    pub fn write(&self, writer: &mut NfNetlinkWriter, seq: u32) -> Result<(), BuilderError> {
+        self.check_size()?;
+        writer.write_subsystem_header(
+            NFNL_SUBSYS_ULOG as u8,
+            NFULNL_MSG_CONFIG as u16,
+            self.family,
+            NLM_F_ACK as u16,
+            seq,
+            Some(self.group.to_be()),
+        );
+        let buf = writer.add_data_zeroed(self.get_size());
+        self.write_payload(buf);
+        writer.finalize_writing_object();
+        Ok(())
+    }
+
+    /// Sends the message on `sock`, and waits for the kernel to acknowledge it.
+    pub fn send(&self, sock: &mut Socket) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        self.write(&mut NfNetlinkWriter::new(&mut buf), 0)?;
+        if sock.send(&buf, MsgFlags::empty().bits())? != buf.len() {
+            return Err(QueryError::TruncatedSend.into());
+        }
+        recv_and_process(sock, Some(0), None, &mut ())
+    }
+
+    pub async fn send_async<S: AsyncSocket>(&self, sock: &mut S) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        self.write(&mut NfNetlinkWriter::new(&mut buf), 0)?;
+        if sock.send(&buf).await? != buf.len() {
+            return Err(QueryError::TruncatedSend.into());
+        }
+        recv_and_process_async(sock, Some(0), None, &mut ()).await
+    }
+}
+
+/// The header of a packet sent to a NFLOG group.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct NflogPacketHeader {
+    /// The ethertype of the packet.
+    pub hw_protocol: u16,
+    /// The netfilter hook the packet was logged in.
+    pub hook: u8,
+}
+
+impl NfNetlinkAttribute for NflogPacketHeader {
+    fn get_size(&self) -> usize {
+        4
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        self.hw_protocol.write_payload(addr);
+        addr[2] = self.hook;
+        addr[3] = 0;
+    }
+}
+
+impl NfNetlinkDeserializable for NflogPacketHeader {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        if buf.len() < 4 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (hw_protocol, buf) = u16::deserialize(buf)?;
+        Ok((
+            NflogPacketHeader {
+                hw_protocol,
+                hook: buf[0],
+            },
+            &buf[2..],
+        ))
+    }
+}
+
+/// A packet received from a NFLOG group.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_deserialize = false)]
+pub struct NflogPacket {
+    group: u16,
+    family: ProtocolFamily,
+    #[field(NFULA_PACKET_HDR)]
+    header: NflogPacketHeader,
+    #[field(NFULA_MARK)]
+    mark: u32,
+    #[field(NFULA_TIMESTAMP)]
+    timestamp: PacketTimestamp,
+    /// Index of the input interface.
+    #[field(NFULA_IFINDEX_INDEV)]
+    indev: u32,
+    /// Index of the output interface.
+    #[field(NFULA_IFINDEX_OUTDEV)]
+    outdev: u32,
+    /// Index of the physical input interface, for bridged packets.
+    #[field(NFULA_IFINDEX_PHYSINDEV)]
+    physindev: u32,
+    /// Index of the physical output interface, for bridged packets.
+    #[field(NFULA_IFINDEX_PHYSOUTDEV)]
+    physoutdev: u32,
+    #[field(NFULA_HWADDR)]
+    hwaddr: HardwareAddress,
+    /// Network header and payload of the packet, truncated to the copy range.
+    #[field(NFULA_PAYLOAD)]
+    payload: Vec<u8>,
+    /// Prefix of the [`Log`](crate::expr::Log) expression that sent the packet.
+    #[field(NFULA_PREFIX)]
+    prefix: String,
+    /// User id of the socket the packet belongs to.
+    #[field(NFULA_UID)]
+    uid: u32,
+    /// Sequence number, see [`NflogConfigFlags::SEQ`].
+    #[field(NFULA_SEQ)]
+    seq: u32,
+    /// Sequence number, see [`NflogConfigFlags::SEQ_GLOBAL`].
+    #[field(NFULA_SEQ_GLOBAL)]
+    seq_global: u32,
+    /// Group id of the socket the packet belongs to.
+    #[field(NFULA_GID)]
+    gid: u32,
+    /// Type of the link layer (`ARPHRD_*` constants).
+    #[field(NFULA_HWTYPE)]
+    hwtype: u16,
+    /// Link layer header of the packet.
+    #[field(NFULA_HWHEADER)]
+    hwheader: Vec<u8>,
+    #[field(NFULA_HWLEN)]
+    hwlen: u16,
+    /// Raw conntrack attributes of the connection, see [`NflogConfigFlags::CONNTRACK`].
+    #[field(NFULA_CT)]
+    ct: Vec<u8>,
+    #[field(NFULA_CT_INFO)]
+    ct_info: CtInfo,
+    #[field(NFULA_VLAN)]
+    vlan: PacketVlan,
+    /// Link layer header of the packet, in the bridge family.
+    #[field(NFULA_L2HDR)]
+    l2hdr: Vec<u8>,
+}
+
+impl NflogPacket {
+    /// The NFLOG group the packet was sent to.
+    pub fn get_group(&self) -> u16 {
+        self.group
+    }
+
+    pub fn get_family(&self) -> ProtocolFamily {
+        self.family
+    }
+}
+
+impl NfNetlinkDeserializable for NflogPacket {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        let (mut obj, nfgenmsg, remaining_data) =
+            parse_subsystem_object::<Self>(buf, NFNL_SUBSYS_ULOG as u8, &[NFULNL_MSG_PACKET])?;
+        obj.family = ProtocolFamily::try_from(nfgenmsg.nfgen_family as i32)?;
+        obj.group = u16::from_be(nfgenmsg.res_id);
+
+        Ok((obj, remaining_data))
+    }
+}
+
+/// Waits for packets on a socket bound to NFLOG groups, and calls `cb` on each of them.
+pub fn recv_packets(
+    sock: &mut Socket,
+    cb: &mut dyn FnMut(NflogPacket) -> Result<(), QueryError>,
+) -> anyhow::Result<()> {
+    recv_subsystem_objects(sock, NFNL_SUBSYS_ULOG as u8, cb)
+}
+
+pub async fn recv_packets_async<S: AsyncSocket>(
+    sock: &mut S,
+    cb: &mut (dyn FnMut(NflogPacket) -> Result<(), QueryError> + Send),
+) -> anyhow::Result<()> {
+    recv_subsystem_objects_async(sock, NFNL_SUBSYS_ULOG as u8, cb).await
+}
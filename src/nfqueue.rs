@@ -0,0 +1,481 @@
+//! Userspace side of the nfnetlink_queue subsystem: binds a netlink socket to the queues packets
+//! are sent to by [`VerdictKind::Queue`] verdicts or [`Queue`] expressions, decodes the packets
+//! received on it, and sends back the verdicts on those packets.
+//!
+//! [`VerdictKind::Queue`]: crate::expr::VerdictKind::Queue
+//! [`Queue`]: crate::expr::Queue
+
+use std::convert::TryFrom;
+
+use netlink_sys::{AsyncSocket, AsyncSocketExt, Socket};
+use nix::sys::socket::MsgFlags;
+use rustables_macros::nfnetlink_struct;
+
+use crate::{
+    error::{BuilderError, DecodeError, QueryError},
+    nflog::{ConfigCommand, CopyParams, CtInfo, HardwareAddress, PacketTimestamp, PacketVlan},
+    nlmsg::{NfNetlinkAttribute, NfNetlinkDeserializable, NfNetlinkWriter},
+    parser::parse_subsystem_object,
+    query::{
+        recv_and_process, recv_and_process_async, recv_subsystem_objects,
+        recv_subsystem_objects_async,
+    },
+    sys::{
+        NFNL_SUBSYS_QUEUE, NFQA_CAP_LEN, NFQA_CFG_CMD, NFQA_CFG_FLAGS, NFQA_CFG_F_CONNTRACK,
+        NFQA_CFG_F_FAIL_OPEN, NFQA_CFG_F_GSO, NFQA_CFG_F_SECCTX, NFQA_CFG_F_UID_GID, NFQA_CFG_MASK,
+        NFQA_CFG_PARAMS, NFQA_CFG_QUEUE_MAXLEN, NFQA_CT, NFQA_CT_INFO, NFQA_GID, NFQA_HWADDR,
+        NFQA_IFINDEX_INDEV, NFQA_IFINDEX_OUTDEV, NFQA_IFINDEX_PHYSINDEV, NFQA_IFINDEX_PHYSOUTDEV,
+        NFQA_L2HDR, NFQA_MARK, NFQA_PACKET_HDR, NFQA_PAYLOAD, NFQA_PRIORITY, NFQA_SECCTX,
+        NFQA_SKB_CSUMNOTREADY, NFQA_SKB_CSUM_NOTVERIFIED, NFQA_SKB_GSO, NFQA_SKB_INFO,
+        NFQA_TIMESTAMP, NFQA_UID, NFQA_VERDICT_HDR, NFQA_VLAN, NFQNL_MSG_CONFIG, NFQNL_MSG_PACKET,
+        NFQNL_MSG_VERDICT, NLM_F_ACK,
+    },
+    ProtocolFamily,
+};
+
+/// The command of a NFQUEUE configuration message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NfqueueCommand {
+    pub command: ConfigCommand,
+    /// Protocol family of the `ConfigCommand::PfBind` and `ConfigCommand::PfUnbind` commands.
+    pub family: ProtocolFamily,
+}
+
+impl NfqueueCommand {
+    pub fn new(command: ConfigCommand) -> Self {
+        NfqueueCommand {
+            command,
+            family: ProtocolFamily::Unspec,
+        }
+    }
+}
+
+impl NfNetlinkAttribute for NfqueueCommand {
+    fn get_size(&self) -> usize {
+        4
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        addr[0] = self.command as u8;
+        addr[1] = 0;
+        (self.family as u16).write_payload(&mut addr[2..]);
+    }
+}
+
+impl NfNetlinkDeserializable for NfqueueCommand {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        if buf.len() < 4 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (command, _) = ConfigCommand::deserialize(buf)?;
+        let (family, buf) = u16::deserialize(&buf[2..])?;
+        Ok((
+            NfqueueCommand {
+                command,
+                family: ProtocolFamily::try_from(family as i32)?,
+            },
+            buf,
+        ))
+    }
+}
+
+bitflags::bitflags! {
+    /// The flags of a NFQUEUE queue, as set with [`NfqueueConfig::with_config_flags`].
+    pub struct NfqueueConfigFlags: u32 {
+        /// Accepts the packets instead of dropping them when the queue is full.
+        const FAIL_OPEN = NFQA_CFG_F_FAIL_OPEN;
+        /// Adds the conntrack information of the packets.
+        const CONNTRACK = NFQA_CFG_F_CONNTRACK;
+        /// Sends GSO packets without segmenting them first.
+        const GSO = NFQA_CFG_F_GSO;
+        /// Adds the user and group ids of the socket the packets belong to.
+        const UID_GID = NFQA_CFG_F_UID_GID;
+        /// Adds the security context of the packets.
+        const SECCTX = NFQA_CFG_F_SECCTX;
+    }
+}
+
+bitflags::bitflags! {
+    /// The state of the socket buffer of a queued packet.
+    pub struct NfqueueSkbInfo: u32 {
+        /// The checksum of the packet isn't computed yet.
+        const CSUM_NOT_READY = NFQA_SKB_CSUMNOTREADY;
+        /// The packet is a GSO packet.
+        const GSO = NFQA_SKB_GSO;
+        /// The checksum of the packet wasn't verified.
+        const CSUM_NOT_VERIFIED = NFQA_SKB_CSUM_NOTVERIFIED;
+    }
+}
+
+/// A configuration message for a NFQUEUE queue.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_deserialize = false)]
+pub struct NfqueueConfig {
+    queue_num: u16,
+    #[field(NFQA_CFG_CMD)]
+    command: NfqueueCommand,
+    #[field(NFQA_CFG_PARAMS)]
+    copy_params: CopyParams,
+    /// Maximum number of packets waiting for a verdict in the queue.
+    #[field(NFQA_CFG_QUEUE_MAXLEN)]
+    queue_maxlen: u32,
+    /// The flags changed by the message, see [`NfqueueConfigFlags`].
+    #[field(NFQA_CFG_MASK)]
+    mask: u32,
+    /// The new value of the flags in `mask`, see [`NfqueueConfigFlags`].
+    #[field(NFQA_CFG_FLAGS)]
+    flags: u32,
+}
+
+impl NfqueueConfig {
+    /// Creates an empty configuration message for the queue `queue_num`.
+    pub fn new(queue_num: u16) -> Self {
+        NfqueueConfig {
+            queue_num,
+            ..Default::default()
+        }
+    }
+
+    /// Binds the socket the message is sent on to the queue `queue_num`.
+    pub fn bind(queue_num: u16) -> Self {
+        Self::new(queue_num).with_command(NfqueueCommand::new(ConfigCommand::Bind))
+    }
+
+    /// Unbinds the socket the message is sent on from the queue `queue_num`.
+    pub fn unbind(queue_num: u16) -> Self {
+        Self::new(queue_num).with_command(NfqueueCommand::new(ConfigCommand::Unbind))
+    }
+
+    pub fn get_queue_num(&self) -> u16 {
+        self.queue_num
+    }
+
+    /// Sets the flags of `mask` to their value in `flags`, the other flags of the queue being
+    /// left unchanged.
+    pub fn with_config_flags(self, mask: NfqueueConfigFlags, flags: NfqueueConfigFlags) -> Self {
+        self.with_mask(mask.bits()).with_flags(flags.bits())
+    }
+
+    pub fn write(&self, writer: &mut NfNetlinkWriter, seq: u32) -> Result<(), BuilderError> {
+        self.check_size()?;
+        writer.write_subsystem_header(
+            NFNL_SUBSYS_QUEUE as u8,
+            NFQNL_MSG_CONFIG as u16,
+            ProtocolFamily::Unspec,
+            NLM_F_ACK as u16,
+            seq,
+            Some(self.queue_num.to_be()),
+        );
+        let buf = writer.add_data_zeroed(self.get_size());
+        self.write_payload(buf);
+        writer.finalize_writing_object();
+        Ok(())
+    }
+
+    /// Sends the message on `sock`, and waits for the kernel to acknowledge it.
+    pub fn send(&self, sock: &mut Socket) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        self.write(&mut NfNetlinkWriter::new(&mut buf), 0)?;
+        if sock.send(&buf, MsgFlags::empty().bits())? != buf.len() {
+            return Err(QueryError::TruncatedSend.into());
+        }
+        recv_and_process(sock, Some(0), None, &mut ())
+    }
+
+    pub async fn send_async<S: AsyncSocket>(&self, sock: &mut S) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        self.write(&mut NfNetlinkWriter::new(&mut buf), 0)?;
+        if sock.send(&buf).await? != buf.len() {
+            return Err(QueryError::TruncatedSend.into());
+        }
+        recv_and_process_async(sock, Some(0), None, &mut ()).await
+    }
+}
+
+/// The header of a packet sent to a NFQUEUE queue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct NfqueuePacketHeader {
+    /// Identifier of the packet in the queue, that the verdict must refer to.
+    pub packet_id: u32,
+    /// The ethertype of the packet.
+    pub hw_protocol: u16,
+    /// The netfilter hook the packet was queued in.
+    pub hook: u8,
+}
+
+impl NfNetlinkAttribute for NfqueuePacketHeader {
+    fn get_size(&self) -> usize {
+        7
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        self.packet_id.write_payload(addr);
+        self.hw_protocol.write_payload(&mut addr[4..]);
+        addr[6] = self.hook;
+    }
+}
+
+impl NfNetlinkDeserializable for NfqueuePacketHeader {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        if buf.len() < 7 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (packet_id, buf) = u32::deserialize(buf)?;
+        let (hw_protocol, buf) = u16::deserialize(buf)?;
+        Ok((
+            NfqueuePacketHeader {
+                packet_id,
+                hw_protocol,
+                hook: buf[0],
+            },
+            &buf[1..],
+        ))
+    }
+}
+
+/// A packet received from a NFQUEUE queue, waiting for a verdict.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_deserialize = false)]
+pub struct NfqueuePacket {
+    queue_num: u16,
+    family: ProtocolFamily,
+    #[field(NFQA_PACKET_HDR)]
+    header: NfqueuePacketHeader,
+    #[field(NFQA_MARK)]
+    mark: u32,
+    #[field(NFQA_TIMESTAMP)]
+    timestamp: PacketTimestamp,
+    /// Index of the input interface.
+    #[field(NFQA_IFINDEX_INDEV)]
+    indev: u32,
+    /// Index of the output interface.
+    #[field(NFQA_IFINDEX_OUTDEV)]
+    outdev: u32,
+    /// Index of the physical input interface, for bridged packets.
+    #[field(NFQA_IFINDEX_PHYSINDEV)]
+    physindev: u32,
+    /// Index of the physical output interface, for bridged packets.
+    #[field(NFQA_IFINDEX_PHYSOUTDEV)]
+    physoutdev: u32,
+    #[field(NFQA_HWADDR)]
+    hwaddr: HardwareAddress,
+    /// Network header and payload of the packet, truncated to the copy range.
+    #[field(NFQA_PAYLOAD)]
+    payload: Vec<u8>,
+    /// Raw conntrack attributes of the connection, see [`NfqueueConfigFlags::CONNTRACK`].
+    #[field(NFQA_CT)]
+    ct: Vec<u8>,
+    #[field(NFQA_CT_INFO)]
+    ct_info: CtInfo,
+    /// Length of the packet, when the payload was truncated.
+    #[field(NFQA_CAP_LEN)]
+    cap_len: u32,
+    /// See [`NfqueueSkbInfo`].
+    #[field(NFQA_SKB_INFO)]
+    skb_info: u32,
+    /// User id of the socket the packet belongs to, see [`NfqueueConfigFlags::UID_GID`].
+    #[field(NFQA_UID)]
+    uid: u32,
+    /// Group id of the socket the packet belongs to, see [`NfqueueConfigFlags::UID_GID`].
+    #[field(NFQA_GID)]
+    gid: u32,
+    /// Security context of the packet, see [`NfqueueConfigFlags::SECCTX`].
+    #[field(NFQA_SECCTX)]
+    secctx: String,
+    /// Link layer header of the packet, in the bridge family.
+    #[field(NFQA_L2HDR)]
+    l2hdr: Vec<u8>,
+    /// VLAN tag of the packet, in the bridge family. The attributes of the tag are numbered
+    /// like the ones of NFLOG.
+    #[field(NFQA_VLAN)]
+    vlan: PacketVlan,
+    /// Priority of the packet (`skb->priority`), when it is not zero.
+    #[field(NFQA_PRIORITY)]
+    priority: u32,
+}
+
+impl NfqueuePacket {
+    /// The queue the packet was sent to.
+    pub fn get_queue_num(&self) -> u16 {
+        self.queue_num
+    }
+
+    pub fn get_family(&self) -> ProtocolFamily {
+        self.family
+    }
+
+    /// Creates a verdict on this packet, or returns `None` if the packet has no header to
+    /// identify it.
+    pub fn verdict(&self, verdict: QueueVerdict) -> Option<NfqueueVerdict> {
+        let header = self.get_header()?;
+        Some(NfqueueVerdict::new(
+            self.queue_num,
+            header.packet_id,
+            verdict,
+        ))
+    }
+}
+
+impl NfNetlinkDeserializable for NfqueuePacket {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        let (mut obj, nfgenmsg, remaining_data) =
+            parse_subsystem_object::<Self>(buf, NFNL_SUBSYS_QUEUE as u8, &[NFQNL_MSG_PACKET])?;
+        obj.family = ProtocolFamily::try_from(nfgenmsg.nfgen_family as i32)?;
+        obj.queue_num = u16::from_be(nfgenmsg.res_id);
+
+        Ok((obj, remaining_data))
+    }
+}
+
+/// The fate of a queued packet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QueueVerdict {
+    Drop,
+    Accept,
+    /// Sends the packet through the current hook again.
+    Repeat,
+    /// Accepts the packet, without traversing the next chains of the hook.
+    Stop,
+    /// Sends the packet to another queue.
+    Queue(u16),
+}
+
+impl QueueVerdict {
+    fn to_raw(self) -> u32 {
+        match self {
+            QueueVerdict::Drop => libc::NF_DROP as u32,
+            QueueVerdict::Accept => libc::NF_ACCEPT as u32,
+            QueueVerdict::Repeat => libc::NF_REPEAT as u32,
+            QueueVerdict::Stop => libc::NF_STOP as u32,
+            // the queue number is stored in the upper 16 bits of the verdict
+            QueueVerdict::Queue(queue_num) => libc::NF_QUEUE as u32 | (queue_num as u32) << 16,
+        }
+    }
+
+    fn from_raw(raw: u32) -> Result<Self, DecodeError> {
+        match (raw & 0xffff) as i32 {
+            libc::NF_DROP => Ok(QueueVerdict::Drop),
+            libc::NF_ACCEPT => Ok(QueueVerdict::Accept),
+            libc::NF_REPEAT => Ok(QueueVerdict::Repeat),
+            libc::NF_STOP => Ok(QueueVerdict::Stop),
+            libc::NF_QUEUE => Ok(QueueVerdict::Queue((raw >> 16) as u16)),
+            _ => Err(DecodeError::UnknownQueueVerdict(raw)),
+        }
+    }
+}
+
+/// The verdict on a queued packet, and the identifier of that packet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NfqueueVerdictHeader {
+    pub verdict: QueueVerdict,
+    pub packet_id: u32,
+}
+
+impl NfNetlinkAttribute for NfqueueVerdictHeader {
+    fn get_size(&self) -> usize {
+        8
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        self.verdict.to_raw().write_payload(addr);
+        self.packet_id.write_payload(&mut addr[4..]);
+    }
+}
+
+impl NfNetlinkDeserializable for NfqueueVerdictHeader {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        if buf.len() < 8 {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        let (verdict, buf) = u32::deserialize(buf)?;
+        let (packet_id, buf) = u32::deserialize(buf)?;
+        Ok((
+            NfqueueVerdictHeader {
+                verdict: QueueVerdict::from_raw(verdict)?,
+                packet_id,
+            },
+            buf,
+        ))
+    }
+}
+
+/// A verdict message for a queued packet, that can also rewrite the mark or the payload of the
+/// packet.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_deserialize = false)]
+pub struct NfqueueVerdict {
+    queue_num: u16,
+    #[field(NFQA_VERDICT_HDR)]
+    header: NfqueueVerdictHeader,
+    /// New mark of the packet.
+    #[field(NFQA_MARK)]
+    mark: u32,
+    /// New network header and payload of the packet.
+    #[field(NFQA_PAYLOAD)]
+    payload: Vec<u8>,
+}
+
+impl NfqueueVerdict {
+    pub fn new(queue_num: u16, packet_id: u32, verdict: QueueVerdict) -> Self {
+        NfqueueVerdict {
+            queue_num,
+            ..Default::default()
+        }
+        .with_header(NfqueueVerdictHeader { verdict, packet_id })
+    }
+
+    pub fn get_queue_num(&self) -> u16 {
+        self.queue_num
+    }
+
+    pub fn write(&self, writer: &mut NfNetlinkWriter, seq: u32) -> Result<(), BuilderError> {
+        self.check_size()?;
+        // like libnetfilter_queue, do not request an acknowledgement for each verdict
+        writer.write_subsystem_header(
+            NFNL_SUBSYS_QUEUE as u8,
+            NFQNL_MSG_VERDICT as u16,
+            ProtocolFamily::Unspec,
+            0,
+            seq,
+            Some(self.queue_num.to_be()),
+        );
+        let buf = writer.add_data_zeroed(self.get_size());
+        self.write_payload(buf);
+        writer.finalize_writing_object();
+        Ok(())
+    }
+
+    pub fn send(&self, sock: &mut Socket) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        self.write(&mut NfNetlinkWriter::new(&mut buf), 0)?;
+        if sock.send(&buf, MsgFlags::empty().bits())? != buf.len() {
+            return Err(QueryError::TruncatedSend.into());
+        }
+        Ok(())
+    }
+
+    pub async fn send_async<S: AsyncSocket>(&self, sock: &mut S) -> anyhow::Result<()> {
+        let mut buf = Vec::new();
+        self.write(&mut NfNetlinkWriter::new(&mut buf), 0)?;
+        if sock.send(&buf).await? != buf.len() {
+            return Err(QueryError::TruncatedSend.into());
+        }
+        Ok(())
+    }
+}
+
+/// Waits for packets on a socket bound to NFQUEUE queues, and calls `cb` on each of them.
+/// Every packet must then receive a verdict, see [`NfqueuePacket::verdict`].
+pub fn recv_packets(
+    sock: &mut Socket,
+    cb: &mut dyn FnMut(NfqueuePacket) -> Result<(), QueryError>,
+) -> anyhow::Result<()> {
+    recv_subsystem_objects(sock, NFNL_SUBSYS_QUEUE as u8, cb)
+}
+
+pub async fn recv_packets_async<S: AsyncSocket>(
+    sock: &mut S,
+    cb: &mut (dyn FnMut(NfqueuePacket) -> Result<(), QueryError> + Send),
+) -> anyhow::Result<()> {
+    recv_subsystem_objects_async(sock, NFNL_SUBSYS_QUEUE as u8, cb).await
+}
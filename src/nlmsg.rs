@@ -1,7 +1,7 @@
 use std::{fmt::Debug, mem::size_of};
 
 use crate::{
-    error::DecodeError,
+    error::{BuilderError, DecodeError},
     sys::{
         nfgenmsg, nlmsghdr, NFNETLINK_V0, NFNL_MSG_BATCH_BEGIN, NFNL_MSG_BATCH_END,
         NFNL_SUBSYS_NFTABLES, NLMSG_ALIGNTO, NLM_F_ACK, NLM_F_CREATE,
@@ -73,6 +73,27 @@ impl<'a> NfNetlinkWriter<'a> {
         flags: u16,
         seq: u32,
         ressource_id: Option<u16>,
+    ) {
+        self.write_subsystem_header(
+            NFNL_SUBSYS_NFTABLES as u8,
+            msg_type,
+            family,
+            flags,
+            seq,
+            ressource_id,
+        );
+    }
+
+    /// Writes the headers of a message for the nfnetlink subsystem `subsys` (e.g.
+    /// `NFNL_SUBSYS_ULOG` or `NFNL_SUBSYS_QUEUE`), `write_header` being restricted to nftables.
+    pub fn write_subsystem_header(
+        &mut self,
+        subsys: u8,
+        msg_type: u16,
+        family: ProtocolFamily,
+        flags: u16,
+        seq: u32,
+        ressource_id: Option<u16>,
     ) {
         if self.headers.is_some() {
             error!("Calling write_header while still holding headers open!?");
@@ -89,7 +110,7 @@ impl<'a> NfNetlinkWriter<'a> {
         hdr.nlmsg_type = msg_type;
         // batch messages are not specific to the nftables subsystem
         if msg_type != NFNL_MSG_BATCH_BEGIN as u16 && msg_type != NFNL_MSG_BATCH_END as u16 {
-            hdr.nlmsg_type |= (NFNL_SUBSYS_NFTABLES as u16) << 8;
+            hdr.nlmsg_type |= (subsys as u16) << 8;
         }
         hdr.nlmsg_flags = libc::NLM_F_REQUEST as u16 | flags;
         hdr.nlmsg_seq = seq;
@@ -129,7 +150,15 @@ pub trait NfNetlinkObject:
     const MSG_TYPE_ADD: u32;
     const MSG_TYPE_DEL: u32;
 
-    fn add_or_remove<'a>(&self, writer: &mut NfNetlinkWriter<'a>, msg_type: MsgType, seq: u32) {
+    fn add_or_remove<'a>(
+        &self,
+        writer: &mut NfNetlinkWriter<'a>,
+        msg_type: MsgType,
+        seq: u32,
+    ) -> Result<(), BuilderError> {
+        // refuse to write attributes whose length would overflow nla_len
+        self.check_size()?;
+
         let raw_msg_type = match msg_type {
             MsgType::Add => Self::MSG_TYPE_ADD,
             MsgType::Del => Self::MSG_TYPE_DEL,
@@ -148,6 +177,7 @@ pub trait NfNetlinkObject:
         let buf = writer.add_data_zeroed(self.get_size());
         self.write_payload(buf);
         writer.finalize_writing_object();
+        Ok(())
     }
 
     fn get_family(&self) -> ProtocolFamily;
@@ -180,6 +210,12 @@ pub trait NfNetlinkAttribute: Debug + Sized {
         size_of::<Self>()
     }
 
+    // check that the attributes nested in this object fit in the 16 bits of their nla_len, the
+    // default impl do nothing, because most types do not hold nested attributes
+    fn check_size(&self) -> Result<(), BuilderError> {
+        Ok(())
+    }
+
     // example body: std::ptr::copy_nonoverlapping(self as *const Self as *const u8, addr.as_mut_ptr(), self.get_size());
     fn write_payload(&self, addr: &mut [u8]);
 }
@@ -0,0 +1,321 @@
+use netlink_sys::{AsyncSocket, Socket};
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use crate::error::{BuilderError, DecodeError, QueryError};
+use crate::expr::{Connlimit, Counter, Limit, Quota, Synproxy};
+use crate::nlmsg::{
+    AttributeDecoder, NetlinkType, NfNetlinkAttribute, NfNetlinkDeserializable, NfNetlinkObject,
+};
+use crate::sys::{
+    NFTA_OBJ_DATA, NFTA_OBJ_HANDLE, NFTA_OBJ_NAME, NFTA_OBJ_TABLE, NFTA_OBJ_TYPE, NFTA_OBJ_USE,
+    NFTA_OBJ_USERDATA, NFTA_SECMARK_CTX, NFT_MSG_DELOBJ, NFT_MSG_GETOBJ, NFT_MSG_NEWOBJ,
+    NFT_OBJECT_CONNLIMIT, NFT_OBJECT_COUNTER, NFT_OBJECT_CT_EXPECT, NFT_OBJECT_CT_HELPER,
+    NFT_OBJECT_CT_TIMEOUT, NFT_OBJECT_LIMIT, NFT_OBJECT_QUOTA, NFT_OBJECT_SECMARK,
+    NFT_OBJECT_SYNPROXY, NFT_OBJECT_TUNNEL, NFT_SECMARK_CTX_MAXLEN,
+};
+use crate::{Batch, ProtocolFamily, Table};
+
+/// The type of a stateful object.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum ObjectType {
+    Counter = NFT_OBJECT_COUNTER,
+    Quota = NFT_OBJECT_QUOTA,
+    CtHelper = NFT_OBJECT_CT_HELPER,
+    Limit = NFT_OBJECT_LIMIT,
+    Connlimit = NFT_OBJECT_CONNLIMIT,
+    Tunnel = NFT_OBJECT_TUNNEL,
+    CtTimeout = NFT_OBJECT_CT_TIMEOUT,
+    Secmark = NFT_OBJECT_SECMARK,
+    CtExpect = NFT_OBJECT_CT_EXPECT,
+    Synproxy = NFT_OBJECT_SYNPROXY,
+}
+
+/// The security context a secmark object labels the packets with, for SELinux policies.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_struct]
+pub struct Secmark {
+    #[field(NFTA_SECMARK_CTX)]
+    ctx: String,
+}
+
+impl Secmark {
+    /// Returns a secmark for the security context `ctx`, e.g.
+    /// `system_u:object_r:http_packet_t:s0`, which must be at most `NFT_SECMARK_CTX_MAXLEN`
+    /// bytes long.
+    pub fn new(ctx: impl Into<String>) -> Result<Self, BuilderError> {
+        let ctx = ctx.into();
+        if ctx.len() > NFT_SECMARK_CTX_MAXLEN as usize {
+            return Err(BuilderError::TooLongSecmarkContext);
+        }
+        Ok(Secmark::default().with_ctx(ctx))
+    }
+}
+
+/// The configuration of a stateful object, whose attributes are those of the expression of the
+/// same name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ObjectData {
+    Counter(Counter),
+    Quota(Quota),
+    Limit(Limit),
+    Connlimit(Connlimit),
+    Synproxy(Synproxy),
+    Secmark(Secmark),
+    /// The attributes of the object types that are not handled yet.
+    Raw(Vec<u8>),
+}
+
+impl ObjectData {
+    /// The type of object this configuration is for, unknown for the raw attributes.
+    pub fn get_object_type(&self) -> Option<ObjectType> {
+        match self {
+            ObjectData::Counter(_) => Some(ObjectType::Counter),
+            ObjectData::Quota(_) => Some(ObjectType::Quota),
+            ObjectData::Limit(_) => Some(ObjectType::Limit),
+            ObjectData::Connlimit(_) => Some(ObjectType::Connlimit),
+            ObjectData::Synproxy(_) => Some(ObjectType::Synproxy),
+            ObjectData::Secmark(_) => Some(ObjectType::Secmark),
+            ObjectData::Raw(_) => None,
+        }
+    }
+
+    fn deserialize_for(obj_type: ObjectType, buf: &[u8]) -> Result<Self, DecodeError> {
+        let (data, remaining) = match obj_type {
+            ObjectType::Counter => {
+                let (val, remaining) = Counter::deserialize(buf)?;
+                (ObjectData::Counter(val), remaining)
+            }
+            ObjectType::Quota => {
+                let (val, remaining) = Quota::deserialize(buf)?;
+                (ObjectData::Quota(val), remaining)
+            }
+            ObjectType::Limit => {
+                let (val, remaining) = Limit::deserialize(buf)?;
+                (ObjectData::Limit(val), remaining)
+            }
+            ObjectType::Connlimit => {
+                let (val, remaining) = Connlimit::deserialize(buf)?;
+                (ObjectData::Connlimit(val), remaining)
+            }
+            ObjectType::Synproxy => {
+                let (val, remaining) = Synproxy::deserialize(buf)?;
+                (ObjectData::Synproxy(val), remaining)
+            }
+            ObjectType::Secmark => {
+                let (val, remaining) = Secmark::deserialize(buf)?;
+                (ObjectData::Secmark(val), remaining)
+            }
+            _ => {
+                info!(
+                    "Unhandled object type {:?}, keeping its raw attributes",
+                    obj_type
+                );
+                (ObjectData::Raw(buf.to_vec()), &[][..])
+            }
+        };
+        if !remaining.is_empty() {
+            return Err(DecodeError::InvalidDataSize);
+        }
+        Ok(data)
+    }
+}
+
+impl NfNetlinkAttribute for ObjectData {
+    fn is_nested(&self) -> bool {
+        true
+    }
+
+    fn get_size(&self) -> usize {
+        match self {
+            ObjectData::Counter(val) => val.get_size(),
+            ObjectData::Quota(val) => val.get_size(),
+            ObjectData::Limit(val) => val.get_size(),
+            ObjectData::Connlimit(val) => val.get_size(),
+            ObjectData::Synproxy(val) => val.get_size(),
+            ObjectData::Secmark(val) => val.get_size(),
+            ObjectData::Raw(val) => val.get_size(),
+        }
+    }
+
+    fn check_size(&self) -> Result<(), BuilderError> {
+        match self {
+            ObjectData::Counter(val) => val.check_size(),
+            ObjectData::Quota(val) => val.check_size(),
+            ObjectData::Limit(val) => val.check_size(),
+            ObjectData::Connlimit(val) => val.check_size(),
+            ObjectData::Synproxy(val) => val.check_size(),
+            ObjectData::Secmark(val) => val.check_size(),
+            ObjectData::Raw(val) => val.check_size(),
+        }
+    }
+
+    fn write_payload(&self, addr: &mut [u8]) {
+        match self {
+            ObjectData::Counter(val) => val.write_payload(addr),
+            ObjectData::Quota(val) => val.write_payload(addr),
+            ObjectData::Limit(val) => val.write_payload(addr),
+            ObjectData::Connlimit(val) => val.write_payload(addr),
+            ObjectData::Synproxy(val) => val.write_payload(addr),
+            ObjectData::Secmark(val) => val.write_payload(addr),
+            ObjectData::Raw(val) => val.write_payload(addr),
+        }
+    }
+}
+
+impl From<Counter> for ObjectData {
+    fn from(val: Counter) -> Self {
+        ObjectData::Counter(val)
+    }
+}
+
+impl From<Quota> for ObjectData {
+    fn from(val: Quota) -> Self {
+        ObjectData::Quota(val)
+    }
+}
+
+impl From<Limit> for ObjectData {
+    fn from(val: Limit) -> Self {
+        ObjectData::Limit(val)
+    }
+}
+
+impl From<Connlimit> for ObjectData {
+    fn from(val: Connlimit) -> Self {
+        ObjectData::Connlimit(val)
+    }
+}
+
+impl From<Synproxy> for ObjectData {
+    fn from(val: Synproxy) -> Self {
+        ObjectData::Synproxy(val)
+    }
+}
+
+impl From<Secmark> for ObjectData {
+    fn from(val: Secmark) -> Self {
+        ObjectData::Secmark(val)
+    }
+}
+
+/// A named stateful object, which resides inside a [`Table`] and can be shared between the
+/// rules of that table, e.g. `synproxy "web" { mss 1460; wscale 7; }`.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_decoder = false, derive_deserialize = false)]
+pub struct Object {
+    family: ProtocolFamily,
+    #[field(NFTA_OBJ_TABLE)]
+    table: String,
+    #[field(NFTA_OBJ_NAME)]
+    name: String,
+    #[field(NFTA_OBJ_TYPE, name_in_functions = "type")]
+    obj_type: ObjectType,
+    #[field(NFTA_OBJ_DATA)]
+    data: ObjectData,
+    /// Number of rules referencing the object, as reported by the kernel.
+    #[field(NFTA_OBJ_USE, name_in_functions = "use")]
+    use_count: u32,
+    #[field(NFTA_OBJ_HANDLE)]
+    handle: u64,
+    #[field(NFTA_OBJ_USERDATA)]
+    userdata: Vec<u8>,
+}
+
+impl Object {
+    /// Creates a new object inside the given [`Table`].
+    pub fn new(table: &Table) -> Object {
+        let mut obj = Object::default();
+        obj.family = table.get_family();
+
+        if let Some(table_name) = table.get_name() {
+            obj.set_table(table_name);
+        }
+
+        obj
+    }
+
+    /// Sets the configuration of the object, along with its type.
+    pub fn with_object_data(mut self, data: impl Into<ObjectData>) -> Self {
+        let data = data.into();
+        if let Some(obj_type) = data.get_object_type() {
+            self.set_type(obj_type);
+        }
+        self.set_data(data);
+        self
+    }
+
+    /// Appends this object to `batch`
+    pub fn add_to_batch(self, batch: &mut Batch) -> Result<Self, BuilderError> {
+        batch.add(&self, crate::MsgType::Add)?;
+        Ok(self)
+    }
+}
+
+impl AttributeDecoder for Object {
+    fn decode_attribute(&mut self, attr_type: NetlinkType, buf: &[u8]) -> Result<(), DecodeError> {
+        debug!("Decoding attribute {} in an object", attr_type);
+        match attr_type {
+            NFTA_OBJ_TABLE => self.table = Some(String::deserialize(buf)?.0),
+            NFTA_OBJ_NAME => self.name = Some(String::deserialize(buf)?.0),
+            NFTA_OBJ_TYPE => self.obj_type = Some(ObjectType::deserialize(buf)?.0),
+            NFTA_OBJ_DATA => {
+                // the kernel sends the type of the object before its data
+                let obj_type = self.obj_type.ok_or(DecodeError::MissingObjectType)?;
+                self.data = Some(ObjectData::deserialize_for(obj_type, buf)?);
+            }
+            NFTA_OBJ_USE => self.use_count = Some(u32::deserialize(buf)?.0),
+            NFTA_OBJ_HANDLE => self.handle = Some(u64::deserialize(buf)?.0),
+            NFTA_OBJ_USERDATA => self.userdata = Some(Vec::<u8>::deserialize(buf)?.0),
+            _ => return Err(DecodeError::UnsupportedAttributeType(attr_type)),
+        }
+        Ok(())
+    }
+}
+
+impl NfNetlinkObject for Object {
+    const MSG_TYPE_ADD: u32 = NFT_MSG_NEWOBJ;
+    const MSG_TYPE_DEL: u32 = NFT_MSG_DELOBJ;
+
+    fn get_family(&self) -> ProtocolFamily {
+        self.family
+    }
+
+    fn set_family(&mut self, family: ProtocolFamily) {
+        self.family = family;
+    }
+}
+
+pub fn list_objects_for_table(table: &Table, sock: &mut Socket) -> anyhow::Result<Vec<Object>> {
+    let mut result = Vec::new();
+    crate::query::list_objects_with_data(
+        NFT_MSG_GETOBJ as u16,
+        &|obj: Object, objects: &mut Vec<Object>| {
+            objects.push(obj);
+            Ok::<(), QueryError>(())
+        },
+        Some(&Object::new(table)),
+        &mut result,
+        sock,
+    )?;
+    Ok(result)
+}
+
+pub async fn list_objects_for_table_async<S: AsyncSocket>(
+    table: &Table,
+    sock: &mut S,
+) -> anyhow::Result<Vec<Object>> {
+    let mut result = Vec::new();
+    crate::query::list_objects_with_data_async(
+        NFT_MSG_GETOBJ as u16,
+        &|obj: Object, objects: &mut Vec<Object>| {
+            objects.push(obj);
+            Ok::<(), QueryError>(())
+        },
+        Some(&Object::new(table)),
+        &mut result,
+        sock,
+    )
+    .await?;
+    Ok(result)
+}
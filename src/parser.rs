@@ -4,7 +4,7 @@ use std::{
 };
 
 use crate::{
-    error::DecodeError,
+    error::{BuilderError, DecodeError},
     nlmsg::{
         get_operation_from_nlmsghdr_type, get_subsystem_from_nlmsghdr_type, pad_netlink_object,
         pad_netlink_object_with_variable_size, AttributeDecoder, NetlinkType, NfNetlinkAttribute,
@@ -46,6 +46,15 @@ pub enum NlMsg<'a> {
 }
 
 pub fn parse_nlmsg<'a>(buf: &'a [u8]) -> Result<(nlmsghdr, NlMsg<'a>), DecodeError> {
+    parse_subsystem_nlmsg(buf, NFNL_SUBSYS_NFTABLES as u8)
+}
+
+/// Parses a netlink message of the nfnetlink subsystem `subsys`, `parse_nlmsg` being restricted
+/// to nftables messages.
+pub fn parse_subsystem_nlmsg<'a>(
+    buf: &'a [u8],
+    subsys: u8,
+) -> Result<(nlmsghdr, NlMsg<'a>), DecodeError> {
     // in theory the message is composed of the following parts:
     // - nlmsghdr (contains the message size and type)
     // - struct nlmsgerr OR nfgenmsg (nftables header that describes the message family)
@@ -77,10 +86,10 @@ pub fn parse_nlmsg<'a>(buf: &'a [u8]) -> Result<(nlmsghdr, NlMsg<'a>), DecodeErr
     // batch messages are not specific to the nftables subsystem
     if hdr.nlmsg_type != NFNL_MSG_BATCH_BEGIN as u16 && hdr.nlmsg_type != NFNL_MSG_BATCH_END as u16
     {
-        // verify that we are decoding nftables messages
-        let subsys = get_subsystem_from_nlmsghdr_type(hdr.nlmsg_type);
-        if subsys != NFNL_SUBSYS_NFTABLES as u8 {
-            return Err(DecodeError::InvalidSubsystem(subsys));
+        // verify that we are decoding messages of the expected subsystem
+        let msg_subsys = get_subsystem_from_nlmsghdr_type(hdr.nlmsg_type);
+        if msg_subsys != subsys {
+            return Err(DecodeError::InvalidSubsystem(msg_subsys));
         }
     }
 
@@ -127,6 +136,15 @@ pub fn write_attribute<'a>(ty: NetlinkType, obj: &impl NfNetlinkAttribute, mut b
     obj.write_payload(buf);
 }
 
+/// Checks that `obj`, and recursively all the attributes nested inside it, can be written as a
+/// netlink attribute, whose length (header included) must fit in 16 bits.
+pub fn check_attribute_size(obj: &impl NfNetlinkAttribute) -> Result<(), BuilderError> {
+    if pad_netlink_object::<nlattr>() + obj.get_size() > u16::MAX as usize {
+        return Err(BuilderError::AttributeTooLarge);
+    }
+    obj.check_size()
+}
+
 pub(crate) fn read_attributes<T: AttributeDecoder + Default>(buf: &[u8]) -> Result<T, DecodeError> {
     debug!(
         "Calling <{} as NfNetlinkDeserialize>::deserialize()",
@@ -175,12 +193,22 @@ pub(crate) fn parse_object<T: AttributeDecoder + Default + Sized>(
     add_obj: u32,
     del_obj: u32,
 ) -> Result<(T, nfgenmsg, &[u8]), DecodeError> {
+    parse_subsystem_object(buf, NFNL_SUBSYS_NFTABLES as u8, &[add_obj, del_obj])
+}
+
+/// Parses an object sent in a message of the nfnetlink subsystem `subsys`, whose operation must
+/// be one of `ops`.
+pub(crate) fn parse_subsystem_object<'a, T: AttributeDecoder + Default + Sized>(
+    buf: &'a [u8],
+    subsys: u8,
+    ops: &[u32],
+) -> Result<(T, nfgenmsg, &'a [u8]), DecodeError> {
     debug!("parse_object() started");
-    let (hdr, msg) = parse_nlmsg(buf)?;
+    let (hdr, msg) = parse_subsystem_nlmsg(buf, subsys)?;
 
     let op = get_operation_from_nlmsghdr_type(hdr.nlmsg_type) as u32;
 
-    if op != add_obj && op != del_obj {
+    if !ops.contains(&op) {
         return Err(DecodeError::UnexpectedType(hdr.nlmsg_type));
     }
 
@@ -6,13 +6,13 @@ use std::{
 use rustables_macros::nfnetlink_struct;
 
 use crate::{
-    error::DecodeError,
+    error::{BuilderError, DecodeError},
     expr::Verdict,
     nlmsg::{
         pad_netlink_object, pad_netlink_object_with_variable_size, AttributeDecoder,
         NfNetlinkAttribute, NfNetlinkDeserializable, NfNetlinkObject,
     },
-    parser::{parse_object, write_attribute},
+    parser::{check_attribute_size, parse_object, write_attribute},
     sys::{nlattr, NFTA_DATA_VALUE, NFTA_DATA_VERDICT, NFTA_LIST_ELEM, NLA_TYPE_MASK},
     ProtocolFamily,
 };
@@ -156,6 +156,14 @@ where
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T> {
         self.objs.iter()
     }
+
+    pub fn len(&self) -> usize {
+        self.objs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objs.is_empty()
+    }
 }
 
 impl<T> NfNetlinkAttribute for NfNetlinkList<T>
@@ -173,6 +181,13 @@ where
         })
     }
 
+    fn check_size(&self) -> Result<(), BuilderError> {
+        for item in &self.objs {
+            check_attribute_size(item)?;
+        }
+        Ok(())
+    }
+
     fn write_payload(&self, mut addr: &mut [u8]) {
         for item in &self.objs {
             write_attribute(NFTA_LIST_ELEM, item, addr);
@@ -8,9 +8,9 @@ use crate::{
     error::QueryError,
     nlmsg::{
         nft_nlmsg_maxsize, pad_netlink_object_with_variable_size, NfNetlinkAttribute,
-        NfNetlinkObject, NfNetlinkWriter,
+        NfNetlinkDeserializable, NfNetlinkObject, NfNetlinkWriter,
     },
-    parser::{parse_nlmsg, NlMsg},
+    parser::{parse_nlmsg, parse_subsystem_nlmsg, NlMsg},
     sys::{NLM_F_DUMP, NLM_F_MULTI},
     ProtocolFamily,
 };
@@ -169,6 +169,68 @@ pub(crate) async fn recv_and_process_async<'a, T, S: AsyncSocket>(
     }
 }
 
+/// Decodes the objects held in the messages of the nfnetlink subsystem `subsys` stored in `buf`,
+/// and calls `cb` on each of them.
+/// Unlike the replies to our requests, those messages (e.g. the packets of a NFLOG group) are
+/// sent on the kernel's own initiative, and no `NlMsg::Done` marker ends them.
+pub(crate) fn process_subsystem_objects<T: NfNetlinkDeserializable>(
+    mut buf: &[u8],
+    subsys: u8,
+    cb: &mut dyn FnMut(T) -> Result<(), QueryError>,
+) -> Result<(), QueryError> {
+    while !buf.is_empty() {
+        let (nlmsghdr, msg) = parse_subsystem_nlmsg(buf, subsys)?;
+        debug!("Got a valid netlink message: {:?} {:?}", nlmsghdr, msg);
+
+        match msg {
+            NlMsg::Error(e) => {
+                if e.error != 0 {
+                    return Err(QueryError::NetlinkError(e));
+                }
+            }
+            NlMsg::NfGenMsg(_genmsg, _data) => {
+                cb(T::deserialize(&buf[0..nlmsghdr.nlmsg_len as usize])?.0)?;
+            }
+            NlMsg::Done | NlMsg::Noop => {}
+        }
+
+        // netlink messages are 4bytes aligned
+        let aligned_length = pad_netlink_object_with_variable_size(nlmsghdr.nlmsg_len as usize);
+        buf = &buf[aligned_length.min(buf.len())..];
+    }
+    Ok(())
+}
+
+/// Waits for a datagram from the kernel, and calls `cb` on each of the objects of the
+/// nfnetlink subsystem `subsys` it holds.
+pub(crate) fn recv_subsystem_objects<T: NfNetlinkDeserializable>(
+    sock: &mut Socket,
+    subsys: u8,
+    cb: &mut dyn FnMut(T) -> Result<(), QueryError>,
+) -> anyhow::Result<()> {
+    let mut msg_buffer = vec![0; nft_nlmsg_maxsize() as usize];
+    let nb_recv = sock.recv(&mut msg_buffer[..], MsgFlags::empty().bits())?;
+    Ok(process_subsystem_objects(
+        &msg_buffer[..nb_recv],
+        subsys,
+        cb,
+    )?)
+}
+
+pub(crate) async fn recv_subsystem_objects_async<T: NfNetlinkDeserializable, S: AsyncSocket>(
+    sock: &mut S,
+    subsys: u8,
+    cb: &mut (dyn FnMut(T) -> Result<(), QueryError> + Send),
+) -> anyhow::Result<()> {
+    let mut msg_buffer = vec![0; nft_nlmsg_maxsize() as usize];
+    let nb_recv = sock.recv(&mut msg_buffer[..]).await?;
+    Ok(process_subsystem_objects(
+        &msg_buffer[..nb_recv],
+        subsys,
+        cb,
+    )?)
+}
+
 pub(crate) fn socket_close_wrapper<E>(
     sock: RawFd,
     cb: impl FnOnce(RawFd) -> Result<(), E>,
@@ -73,9 +73,9 @@ impl Rule {
     }
 
     /// Appends this rule to `batch`
-    pub fn add_to_batch(self, batch: &mut Batch) -> Self {
-        batch.add(&self, crate::MsgType::Add);
-        self
+    pub fn add_to_batch(self, batch: &mut Batch) -> Result<Self, BuilderError> {
+        batch.add(&self, crate::MsgType::Add)?;
+        Ok(self)
     }
 }
 
@@ -0,0 +1,203 @@
+use crate::chain::Chain;
+use crate::error::BuilderError;
+use crate::expr::ct::{Conntrack, ConntrackKey};
+use crate::expr::{
+    Bitwise, Cmp, CmpOp, Encapsulation, ExthdrField, HighLevelPayload, Immediate, Inner, Lookup,
+    Meta, MetaType, RawExpression, Register, RegisterAllocator, RegisterValue,
+};
+use crate::sys::NFT_REG32_SIZE;
+use crate::{Rule, Set};
+
+/// A piece of information about the packet that can be loaded in registers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LoadSource {
+    Meta(MetaType),
+    Payload(HighLevelPayload),
+    Conntrack(ConntrackKey),
+    Exthdr(ExthdrField),
+    /// A header field of the packet encapsulated in a tunnel, see [`Inner`].
+    Inner(Encapsulation, HighLevelPayload),
+}
+
+impl LoadSource {
+    /// Length in bytes of the value loaded in registers.
+    pub fn value_len(&self) -> u32 {
+        match self {
+            LoadSource::Meta(key) => key.value_len(),
+            LoadSource::Payload(field) | LoadSource::Inner(_, field) => {
+                *field.build().get_len().unwrap()
+            }
+            LoadSource::Conntrack(key) => key.value_len(),
+            LoadSource::Exthdr(field) => *field.build().get_len().unwrap(),
+        }
+    }
+
+    fn build(&self, reg: Register) -> Vec<RawExpression> {
+        match self {
+            LoadSource::Meta(key) => vec![Meta::new(*key).with_dreg(reg).into()],
+            LoadSource::Payload(field) => {
+                let mut exprs = vec![field.build().with_dreg(reg).into()];
+                // the fields that do not span whole bytes are masked right away
+                if let Some(mask) = field.build_mask() {
+                    exprs.push(mask.with_sreg(reg).with_dreg(reg).into());
+                }
+                exprs
+            }
+            LoadSource::Conntrack(key) => vec![Conntrack::new(*key).with_dreg(reg).into()],
+            LoadSource::Exthdr(field) => vec![field.build().with_dreg(reg).into()],
+            LoadSource::Inner(encap, field) => {
+                let mut exprs =
+                    vec![Inner::new_payload(*encap, field.build().with_dreg(reg)).into()];
+                if let Some(mask) = field.build_mask() {
+                    exprs.push(mask.with_sreg(reg).with_dreg(reg).into());
+                }
+                exprs
+            }
+        }
+    }
+}
+
+/// Builds a [`Rule`] out of expressions loading values in registers and expressions using them.
+///
+/// Unlike the expression constructors, which always use [`Register::Reg1`], the builder allocates
+/// a register for each value it loads, so that several values can be live at the same time. It
+/// also keeps track of the length of each value, and refuses to compare it with data of a
+/// different length.
+///
+/// [`Register::Reg1`]: crate::expr::Register::Reg1
+#[derive(Debug, Clone)]
+pub struct RuleBuilder {
+    rule: Rule,
+    regs: RegisterAllocator,
+}
+
+impl RuleBuilder {
+    /// Creates a builder for a new rule in the given [`Chain`].
+    pub fn new(chain: &Chain) -> Result<Self, BuilderError> {
+        Ok(Self::from_rule(Rule::new(chain)?))
+    }
+
+    /// Creates a builder appending expressions to `rule`. The registers are assumed to be all
+    /// free at the end of the existing expressions of the rule.
+    pub fn from_rule(rule: Rule) -> Self {
+        RuleBuilder {
+            rule,
+            regs: RegisterAllocator::new(),
+        }
+    }
+
+    /// Appends an expression that does not use the registers of the builder, such as a
+    /// counter or a verdict.
+    pub fn add_expr(&mut self, e: impl Into<RawExpression>) {
+        self.rule.add_expr(e);
+    }
+
+    /// Loads `source` in a new register.
+    pub fn load(&mut self, source: LoadSource) -> Result<RegisterValue, BuilderError> {
+        let value = self.regs.allocate(source.value_len())?;
+        for expr in source.build(value.get_reg()) {
+            self.rule.add_expr(expr);
+        }
+        Ok(value)
+    }
+
+    /// Loads the meta information `key` of the packet in a new register.
+    pub fn load_meta(&mut self, key: MetaType) -> Result<RegisterValue, BuilderError> {
+        self.load(LoadSource::Meta(key))
+    }
+
+    /// Loads the header field `field` of the packet in a new register.
+    pub fn load_payload(&mut self, field: HighLevelPayload) -> Result<RegisterValue, BuilderError> {
+        self.load(LoadSource::Payload(field))
+    }
+
+    /// Loads the conntrack information `key` of the packet in a new register.
+    pub fn load_ct(&mut self, key: ConntrackKey) -> Result<RegisterValue, BuilderError> {
+        self.load(LoadSource::Conntrack(key))
+    }
+
+    /// Loads the concatenation of `sources` in contiguous registers, as expected by the lookups
+    /// in sets with concatenated keys. Like in nftables, each part of the concatenation starts
+    /// on a 32 bits register, so the length of the value includes the padding of the parts.
+    pub fn load_concat(&mut self, sources: &[LoadSource]) -> Result<RegisterValue, BuilderError> {
+        let padded_len = |source: &LoadSource| source.value_len().next_multiple_of(NFT_REG32_SIZE);
+        let value = self.regs.allocate(sources.iter().map(padded_len).sum())?;
+
+        let mut offset = value.get_reg().data_offset().unwrap();
+        for source in sources {
+            let reg = Register::from_data_offset(offset).unwrap();
+            for expr in source.build(reg) {
+                self.rule.add_expr(expr);
+            }
+            offset += padded_len(source);
+        }
+        Ok(value)
+    }
+
+    /// Loads the constant `data` in a new register.
+    pub fn load_immediate(
+        &mut self,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<RegisterValue, BuilderError> {
+        let data = data.into();
+        let value = self.regs.allocate(data.len() as u32)?;
+        self.add_expr(Immediate::new_data(data, value.get_reg()));
+        Ok(value)
+    }
+
+    /// Masks `value` with `mask` and then performs a xor with `xor`, in place. Both must be as
+    /// long as `value`.
+    pub fn bitwise(
+        &mut self,
+        value: RegisterValue,
+        mask: impl Into<Vec<u8>>,
+        xor: impl Into<Vec<u8>>,
+    ) -> Result<RegisterValue, BuilderError> {
+        let mask = mask.into();
+        if mask.len() != value.get_len() as usize {
+            return Err(BuilderError::IncompatibleLength);
+        }
+        self.add_expr(
+            Bitwise::new(mask, xor)?
+                .with_sreg(value.get_reg())
+                .with_dreg(value.get_reg()),
+        );
+        Ok(value)
+    }
+
+    /// Compares `value` with `data` using the comparison operator `op`. May return
+    /// BuilderError::IncompatibleLength if `data` is not as long as `value`.
+    pub fn cmp(
+        &mut self,
+        value: RegisterValue,
+        op: CmpOp,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<(), BuilderError> {
+        let data = data.into();
+        if data.len() != value.get_len() as usize {
+            return Err(BuilderError::IncompatibleLength);
+        }
+        self.add_expr(Cmp::new(op, data).with_sreg(value.get_reg()));
+        Ok(())
+    }
+
+    /// Checks that `value` is an element of `set`. May return BuilderError::IncompatibleLength
+    /// if the keys of the set are not as long as `value`.
+    pub fn lookup(&mut self, value: RegisterValue, set: &Set) -> Result<(), BuilderError> {
+        if set.get_key_len() != Some(&value.get_len()) {
+            return Err(BuilderError::IncompatibleLength);
+        }
+        self.add_expr(Lookup::new(set)?.with_sreg(value.get_reg()));
+        Ok(())
+    }
+
+    /// Releases the registers holding `value`, which must not be used afterwards.
+    pub fn free(&mut self, value: RegisterValue) {
+        self.regs.free(value);
+    }
+
+    /// Returns the rule built so far.
+    pub fn finish(self) -> Rule {
+        self.rule
+    }
+}
@@ -0,0 +1,477 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use ipnetwork::{Ipv4Network, Ipv6Network};
+
+use crate::expr::ct::{ConnTrackState, Conntrack, ConntrackKey};
+use crate::expr::{
+    Bitwise, Cmp, CmpOp, ExpressionVariant, HeaderField, HighLevelPayload, ICMPHeaderField,
+    ICMPv6HeaderField, IPv4HeaderField, IPv6HeaderField, Immediate, LLHeaderField, Meta, MetaType,
+    NetworkHeaderField, Payload, RawExpression, Register, TCPHeaderField, TransportHeaderField,
+    UDPHeaderField, Verdict, VerdictKind, VerdictType,
+};
+use crate::nlmsg::NfNetlinkObject;
+use crate::sys::{NFT_PAYLOAD_LL_HEADER, NFT_PAYLOAD_NETWORK_HEADER, NFT_PAYLOAD_TRANSPORT_HEADER};
+use crate::{ProtocolFamily, Rule};
+
+/// A high-level description of a condition checked by a rule, reconstructed from the
+/// expressions of that rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Match {
+    /// Netfilter protocol of the packet (`meta nfproto`), one of the `NFPROTO_*` constants.
+    NfProto(u8),
+    /// Layer 4 protocol of the packet (`meta l4proto`), one of the `IPPROTO_*` constants.
+    L4Proto(u8),
+    Ipv4Saddr(Ipv4Network),
+    Ipv4Daddr(Ipv4Network),
+    Ipv6Saddr(Ipv6Network),
+    Ipv6Daddr(Ipv6Network),
+    TcpSport(u16),
+    TcpDport(u16),
+    UdpSport(u16),
+    UdpDport(u16),
+    /// The conntrack state of the packet is one of the given states.
+    CtState(ConnTrackState),
+    /// Index of the input interface.
+    Iif(u32),
+    /// Name of the input interface. A name ending with `*` matches every interface whose name
+    /// starts with the part before the `*`.
+    IifName(String),
+    /// Index of the output interface.
+    Oif(u32),
+    /// Name of the output interface, with the same wildcard convention as [`Match::IifName`].
+    OifName(String),
+    /// Mark of the packet (`meta mark`).
+    Mark(u32),
+    /// A header field without a dedicated variant is equal to the given bytes. The bits of the
+    /// fields that do not span whole bytes are given at their place in the loaded bytes, as
+    /// selected by [`HeaderField::mask`].
+    ///
+    /// [`HeaderField::mask`]: crate::expr::HeaderField::mask
+    Payload(HighLevelPayload, Vec<u8>),
+    /// An expression that is not part of a condition we know how to describe.
+    Other(RawExpression),
+}
+
+/// The conditions and the verdict of a rule, as returned by [`Rule::decode`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecodedRule {
+    /// The conditions of the rule, in the order they are evaluated.
+    pub matches: Vec<Match>,
+    /// The verdict applied to the packets matching all the conditions, if any.
+    pub verdict: Option<VerdictKind>,
+}
+
+/// The origin of the value stored in a register.
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Meta(MetaType),
+    Payload { base: u32, offset: u32, len: u32 },
+    Conntrack(ConntrackKey),
+}
+
+/// A value loaded in a register, waiting for a comparison.
+#[derive(Debug)]
+struct Load {
+    reg: Register,
+    source: Source,
+    mask: Option<Vec<u8>>,
+    // the expressions that produced this value, to be reported as is if the value is never
+    // matched against something we understand
+    exprs: Vec<RawExpression>,
+}
+
+#[derive(Default)]
+struct Decoder {
+    res: DecodedRule,
+    loads: Vec<Load>,
+    nfproto: Option<u8>,
+    l4proto: Option<u8>,
+}
+
+impl Decoder {
+    fn new(family: ProtocolFamily) -> Self {
+        let mut decoder = Decoder::default();
+        if let ProtocolFamily::Ipv4 | ProtocolFamily::Ipv6 = family {
+            decoder.nfproto = Some(family as u8);
+        }
+        decoder
+    }
+
+    fn take_load(&mut self, reg: Register) -> Option<Load> {
+        let pos = self
+            .loads
+            .iter()
+            .position(|load| load.reg.same_location(&reg))?;
+        Some(self.loads.remove(pos))
+    }
+
+    fn push_load(&mut self, load: Load) {
+        // the previous value of the register is lost
+        if let Some(prev) = self.take_load(load.reg) {
+            self.push_others(prev.exprs);
+        }
+        self.loads.push(load);
+    }
+
+    fn push_others(&mut self, exprs: Vec<RawExpression>) {
+        self.res.matches.extend(exprs.into_iter().map(Match::Other));
+    }
+
+    fn flush_loads(&mut self) {
+        for load in std::mem::take(&mut self.loads) {
+            self.push_others(load.exprs);
+        }
+    }
+
+    fn process(&mut self, raw: &RawExpression) {
+        match raw.get_data() {
+            Some(ExpressionVariant::Meta(meta)) => self.process_meta(meta, raw),
+            Some(ExpressionVariant::Payload(payload)) => self.process_payload(payload, raw),
+            Some(ExpressionVariant::Conntrack(ct)) => self.process_ct(ct, raw),
+            Some(ExpressionVariant::Bitwise(bitwise)) => self.process_bitwise(bitwise, raw),
+            Some(ExpressionVariant::Cmp(cmp)) => self.process_cmp(cmp, raw),
+            Some(ExpressionVariant::Immediate(immediate)) => self.process_immediate(immediate, raw),
+            _ => self.process_other(raw),
+        }
+    }
+
+    fn process_other(&mut self, raw: &RawExpression) {
+        self.flush_loads();
+        self.res.matches.push(Match::Other(raw.clone()));
+    }
+
+    fn process_meta(&mut self, meta: &Meta, raw: &RawExpression) {
+        match (meta.get_dreg(), meta.get_key()) {
+            (Some(reg), Some(key)) => self.push_load(Load {
+                reg: *reg,
+                source: Source::Meta(*key),
+                mask: None,
+                exprs: vec![raw.clone()],
+            }),
+            // setting the meta value of the packet is not a condition
+            _ => self.process_other(raw),
+        }
+    }
+
+    fn process_payload(&mut self, payload: &Payload, raw: &RawExpression) {
+        match (
+            payload.get_dreg(),
+            payload.get_base(),
+            payload.get_offset(),
+            payload.get_len(),
+        ) {
+            (Some(reg), Some(base), Some(offset), Some(len)) => self.push_load(Load {
+                reg: *reg,
+                source: Source::Payload {
+                    base: *base,
+                    offset: *offset,
+                    len: *len,
+                },
+                mask: None,
+                exprs: vec![raw.clone()],
+            }),
+            _ => self.process_other(raw),
+        }
+    }
+
+    fn process_ct(&mut self, ct: &Conntrack, raw: &RawExpression) {
+        match (ct.get_dreg(), ct.get_key(), ct.get_direction()) {
+            (Some(reg), Some(key), None) => self.push_load(Load {
+                reg: *reg,
+                source: Source::Conntrack(*key),
+                mask: None,
+                exprs: vec![raw.clone()],
+            }),
+            _ => self.process_other(raw),
+        }
+    }
+
+    fn process_bitwise(&mut self, bitwise: &Bitwise, raw: &RawExpression) {
+        let (sreg, dreg) = match (bitwise.get_sreg(), bitwise.get_dreg()) {
+            (Some(sreg), Some(dreg)) => (*sreg, *dreg),
+            _ => return self.process_other(raw),
+        };
+        let mut load = match self.take_load(sreg) {
+            Some(load) => load,
+            None => return self.process_other(raw),
+        };
+        load.exprs.push(raw.clone());
+
+        let mask = bitwise.get_mask().and_then(|mask| mask.get_value());
+        let xor = bitwise.get_xor().and_then(|xor| xor.get_value());
+        match (mask, xor) {
+            // a plain mask, which is what the kernel sees for prefixes and flag tests
+            (Some(mask), Some(xor)) if load.mask.is_none() && xor.iter().all(|x| *x == 0) => {
+                load.reg = dreg;
+                load.mask = Some(mask.clone());
+                self.push_load(load);
+            }
+            _ => {
+                self.push_others(load.exprs);
+            }
+        }
+    }
+
+    fn process_cmp(&mut self, cmp: &Cmp, raw: &RawExpression) {
+        let load = match cmp.get_sreg().and_then(|reg| self.take_load(*reg)) {
+            Some(load) => load,
+            None => return self.process_other(raw),
+        };
+        let data = cmp.get_data().and_then(|data| data.get_value());
+        let res = match (cmp.get_op(), data) {
+            (Some(op), Some(data)) => self.describe(&load, *op, data),
+            _ => None,
+        };
+        match res {
+            Some(m) => {
+                match m {
+                    Match::NfProto(proto) => self.nfproto = Some(proto),
+                    Match::L4Proto(proto) => self.l4proto = Some(proto),
+                    _ => {}
+                }
+                self.res.matches.push(m);
+            }
+            None => {
+                self.push_others(load.exprs);
+                self.res.matches.push(Match::Other(raw.clone()));
+            }
+        }
+    }
+
+    fn process_immediate(&mut self, immediate: &Immediate, raw: &RawExpression) {
+        let verdict = match immediate.get_dreg() {
+            Some(Register::Verdict) => immediate
+                .get_data()
+                .and_then(|data| data.get_verdict())
+                .and_then(verdict_kind),
+            _ => None,
+        };
+        match verdict {
+            Some(verdict) if self.res.verdict.is_none() => {
+                self.flush_loads();
+                self.res.verdict = Some(verdict);
+            }
+            _ => self.process_other(raw),
+        }
+    }
+
+    /// Describes the comparison of the value of `load` against `data` with `op`.
+    fn describe(&self, load: &Load, op: CmpOp, data: &[u8]) -> Option<Match> {
+        match load.source {
+            Source::Conntrack(ConntrackKey::State) => {
+                let value = u32::from_ne_bytes(data.try_into().ok()?);
+                match (&load.mask, op) {
+                    // ct state { established, related }
+                    (Some(mask), CmpOp::Neq) if value == 0 => {
+                        Some(Match::CtState(ConnTrackState::from_bits_truncate(
+                            u32::from_ne_bytes(mask.as_slice().try_into().ok()?),
+                        )))
+                    }
+                    (None, CmpOp::Eq) => {
+                        Some(Match::CtState(ConnTrackState::from_bits_truncate(value)))
+                    }
+                    _ => None,
+                }
+            }
+            _ if op != CmpOp::Eq => None,
+            Source::Payload { base, offset, len } => {
+                self.describe_payload(base, offset, len, load.mask.as_deref(), data)
+            }
+            _ if load.mask.is_some() => None,
+            Source::Meta(key) => describe_meta(key, data),
+            Source::Conntrack(_) => None,
+        }
+    }
+
+    fn describe_payload(
+        &self,
+        base: u32,
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+        data: &[u8],
+    ) -> Option<Match> {
+        if data.len() != len as usize || mask.is_some_and(|mask| mask.len() != data.len()) {
+            return None;
+        }
+        let field = self.payload_field(base, offset, len, mask)?;
+        let prefix = || match mask {
+            Some(mask) => prefix_len(mask),
+            None => Some((len * 8) as u8),
+        };
+
+        // addresses may be masked by a prefix
+        match field {
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(
+                f @ (IPv4HeaderField::Saddr | IPv4HeaderField::Daddr),
+            )) => {
+                let addr = Ipv4Addr::from(<[u8; 4]>::try_from(data).ok()?);
+                let net = Ipv4Network::new(addr, prefix()?).ok()?;
+                return Some(match f {
+                    IPv4HeaderField::Saddr => Match::Ipv4Saddr(net),
+                    _ => Match::Ipv4Daddr(net),
+                });
+            }
+            HighLevelPayload::Network(NetworkHeaderField::IPv6(
+                f @ (IPv6HeaderField::Saddr | IPv6HeaderField::Daddr),
+            )) => {
+                let addr = Ipv6Addr::from(<[u8; 16]>::try_from(data).ok()?);
+                let net = Ipv6Network::new(addr, prefix()?).ok()?;
+                return Some(match f {
+                    IPv6HeaderField::Saddr => Match::Ipv6Saddr(net),
+                    _ => Match::Ipv6Daddr(net),
+                });
+            }
+            _ => {}
+        }
+
+        // the other fields are only masked to extract their bits from the loaded bytes
+        if mask != field.field().mask() {
+            return None;
+        }
+        let port = || Some(u16::from_be_bytes(data.try_into().ok()?));
+        Some(match field {
+            // `ip protocol` and `ip6 nexthdr`, which nft prefers to `meta l4proto` in the ip and
+            // ip6 families
+            HighLevelPayload::Network(
+                NetworkHeaderField::IPv4(IPv4HeaderField::Protocol)
+                | NetworkHeaderField::IPv6(IPv6HeaderField::NextHeader),
+            ) => Match::L4Proto(data[0]),
+            HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Sport)) => {
+                Match::TcpSport(port()?)
+            }
+            HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Dport)) => {
+                Match::TcpDport(port()?)
+            }
+            HighLevelPayload::Transport(TransportHeaderField::Udp(UDPHeaderField::Sport)) => {
+                Match::UdpSport(port()?)
+            }
+            HighLevelPayload::Transport(TransportHeaderField::Udp(UDPHeaderField::Dport)) => {
+                Match::UdpDport(port()?)
+            }
+            _ => Match::Payload(field, data.to_vec()),
+        })
+    }
+
+    /// Identifies the header field loaded from `offset` in the header `base`, telling apart the
+    /// fields sharing the same bytes with `mask`. The network and transport header fields depend
+    /// on the protocols matched before.
+    fn payload_field(
+        &self,
+        base: u32,
+        offset: u32,
+        len: u32,
+        mask: Option<&[u8]>,
+    ) -> Option<HighLevelPayload> {
+        Some(match base {
+            NFT_PAYLOAD_LL_HEADER => HighLevelPayload::LinkLayer(
+                LLHeaderField::from_raw_data_with_mask(offset, len, mask).ok()?,
+            ),
+            NFT_PAYLOAD_NETWORK_HEADER => {
+                HighLevelPayload::Network(match self.nfproto.map(i32::from)? {
+                    libc::NFPROTO_IPV4 => NetworkHeaderField::IPv4(
+                        IPv4HeaderField::from_raw_data_with_mask(offset, len, mask).ok()?,
+                    ),
+                    libc::NFPROTO_IPV6 => NetworkHeaderField::IPv6(
+                        IPv6HeaderField::from_raw_data_with_mask(offset, len, mask).ok()?,
+                    ),
+                    _ => return None,
+                })
+            }
+            NFT_PAYLOAD_TRANSPORT_HEADER => {
+                HighLevelPayload::Transport(match self.l4proto.map(i32::from)? {
+                    libc::IPPROTO_TCP => TransportHeaderField::Tcp(
+                        TCPHeaderField::from_raw_data_with_mask(offset, len, mask).ok()?,
+                    ),
+                    libc::IPPROTO_UDP => {
+                        TransportHeaderField::Udp(UDPHeaderField::from_raw_data(offset, len).ok()?)
+                    }
+                    libc::IPPROTO_ICMP => TransportHeaderField::ICMP(
+                        ICMPHeaderField::from_raw_data(offset, len).ok()?,
+                    ),
+                    libc::IPPROTO_ICMPV6 => TransportHeaderField::ICMPv6(
+                        ICMPv6HeaderField::from_raw_data(offset, len).ok()?,
+                    ),
+                    _ => return None,
+                })
+            }
+            _ => return None,
+        })
+    }
+
+    fn finish(mut self) -> DecodedRule {
+        self.flush_loads();
+        self.res
+    }
+}
+
+fn describe_meta(key: MetaType, data: &[u8]) -> Option<Match> {
+    match key {
+        MetaType::NfProto if data.len() == 1 => Some(Match::NfProto(data[0])),
+        MetaType::L4Proto if data.len() == 1 => Some(Match::L4Proto(data[0])),
+        MetaType::Iif => Some(Match::Iif(u32::from_ne_bytes(data.try_into().ok()?))),
+        MetaType::Oif => Some(Match::Oif(u32::from_ne_bytes(data.try_into().ok()?))),
+        MetaType::Mark => Some(Match::Mark(u32::from_ne_bytes(data.try_into().ok()?))),
+        MetaType::IifName => Some(Match::IifName(iface_name(data)?)),
+        MetaType::OifName => Some(Match::OifName(iface_name(data)?)),
+        _ => None,
+    }
+}
+
+/// Converts the data compared against an interface name, which is null-terminated for exact
+/// matches and not for prefix matches.
+fn iface_name(data: &[u8]) -> Option<String> {
+    match data.split_last() {
+        Some((0, name)) => String::from_utf8(name.to_vec()).ok(),
+        _ => String::from_utf8(data.to_vec()).ok().map(|name| name + "*"),
+    }
+}
+
+/// Returns the length of the prefix described by `mask`, if its bits set are contiguous.
+fn prefix_len(mask: &[u8]) -> Option<u8> {
+    let ones = mask.iter().map(|b| b.count_ones()).sum::<u32>() as usize;
+    let contiguous =
+        (0..mask.len() * 8).all(|i| (mask[i / 8] & (0x80 >> (i % 8)) != 0) == (i < ones));
+    if contiguous {
+        Some(ones as u8)
+    } else {
+        None
+    }
+}
+
+fn verdict_kind(verdict: &Verdict) -> Option<VerdictKind> {
+    Some(match verdict.get_code()? {
+        VerdictType::Drop => VerdictKind::Drop,
+        VerdictType::Accept => VerdictKind::Accept,
+        VerdictType::Queue => VerdictKind::Queue,
+        VerdictType::Continue => VerdictKind::Continue,
+        VerdictType::Break => VerdictKind::Break,
+        VerdictType::Jump => VerdictKind::Jump {
+            chain: verdict.get_chain()?.clone(),
+        },
+        VerdictType::Goto => VerdictKind::Goto {
+            chain: verdict.get_chain()?.clone(),
+        },
+        VerdictType::Return => VerdictKind::Return,
+    })
+}
+
+impl Rule {
+    /// Reconstructs the conditions and the verdict of this rule from its expressions, for
+    /// instance to audit the rules retrieved with [`list_rules_for_chain`].
+    ///
+    /// The sequences of expressions that do not translate to a known [`Match`] are returned as
+    /// [`Match::Other`], so no expression is silently dropped.
+    ///
+    /// [`list_rules_for_chain`]: crate::list_rules_for_chain
+    pub fn decode(&self) -> DecodedRule {
+        let mut decoder = Decoder::new(self.get_family());
+        if let Some(exprs) = self.get_expressions() {
+            for raw in exprs.iter() {
+                decoder.process(raw);
+            }
+        }
+        decoder.finish()
+    }
+}
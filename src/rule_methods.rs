@@ -1,17 +1,30 @@
 use std::ffi::CString;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr};
+use std::ops::RangeInclusive;
 
 use ipnetwork::IpNetwork;
 
 use crate::data_type::ip_to_vec;
 use crate::error::BuilderError;
-use crate::expr::ct::{ConnTrackState, Conntrack, ConntrackKey};
+use crate::expr::ct::{ConnTrackEvents, ConnTrackState, Conntrack, ConntrackKey};
 use crate::expr::{
-    Bitwise, Cmp, CmpOp, HighLevelPayload, IPv4HeaderField, IPv6HeaderField, Immediate, Meta,
-    MetaType, NetworkHeaderField, TCPHeaderField, TransportHeaderField, UDPHeaderField,
-    VerdictKind,
+    Bitwise, Byteorder, ByteorderOp, Cmp, CmpOp, Dup, Encapsulation, ExthdrField, Fib, FibFlags,
+    FibResult, Fwd, Hash, HeaderField, HighLevelPayload, IPv4HeaderField, IPv6HeaderField,
+    Icmpv4Code, Icmpv6Code, Immediate, Inner, Limit, LimitUnit, Lookup, Meta, MetaType, Nat,
+    NatFlags, NatType, NetworkHeaderField, Notrack, Objref, Payload, PayloadCsumType, Queue,
+    QueueFlags, Range, RangeOp, Redir, Register, Reject, RejectKind, RejectType, Rt, RtKey, Socket,
+    SocketKey, Synproxy, TCPHeaderField, TcpOptionField, Tproxy, TransportHeaderField,
+    UDPHeaderField, VerdictKind,
 };
-use crate::Rule;
+use crate::nlmsg::NfNetlinkObject;
+use crate::parser_impls::NfNetlinkData;
+use crate::set::{MapBuilder, SetElementList};
+use crate::sys::NFT_PAYLOAD_NETWORK_HEADER;
+use crate::{Match, ObjectType, ProtocolFamily, Rule, Set, Table};
+
+const TCP_FLAG_SYN: u8 = 0x02;
+const VXLAN_PORT: u16 = 4789;
+const GENEVE_PORT: u16 = 6081;
 
 /// Simple protocol description. Note that it does not implement other layer 4 protocols as
 /// IGMP et al. See [`Rule::igmp`] for a workaround.
@@ -21,8 +34,54 @@ pub enum Protocol {
     UDP,
 }
 
+/// The addresses a NAT statement maps the packets to, see [`Rule::snat_to`] and
+/// [`Rule::dnat_to`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NatAddress {
+    /// All the addresses of a network, from its network address to its broadcast address.
+    Network(IpNetwork),
+    /// All the addresses from the start of the range to its end, inclusive.
+    Range(RangeInclusive<IpAddr>),
+}
+
+impl NatAddress {
+    /// Returns the first and the last address, which must belong to the same family and be in
+    /// increasing order.
+    fn bounds(&self) -> Result<(IpAddr, IpAddr), BuilderError> {
+        let (min, max) = match self {
+            NatAddress::Network(net) => (net.network(), net.broadcast()),
+            NatAddress::Range(range) => (*range.start(), *range.end()),
+        };
+        if min.is_ipv4() != max.is_ipv4() {
+            return Err(BuilderError::IncompatibleAddressFamilies);
+        }
+        if min > max {
+            return Err(BuilderError::InvalidRange);
+        }
+        Ok((min, max))
+    }
+}
+
+impl From<IpAddr> for NatAddress {
+    fn from(addr: IpAddr) -> Self {
+        NatAddress::Range(addr..=addr)
+    }
+}
+
+impl From<IpNetwork> for NatAddress {
+    fn from(net: IpNetwork) -> Self {
+        NatAddress::Network(net)
+    }
+}
+
+impl From<RangeInclusive<IpAddr>> for NatAddress {
+    fn from(range: RangeInclusive<IpAddr>) -> Self {
+        NatAddress::Range(range)
+    }
+}
+
 impl Rule {
-    fn match_port(mut self, port: u16, protocol: Protocol, source: bool) -> Self {
+    fn load_port(mut self, protocol: Protocol, source: bool) -> Self {
         self = self.protocol(protocol);
         self.add_expr(
             HighLevelPayload::Transport(match protocol {
@@ -39,10 +98,33 @@ impl Rule {
             })
             .build(),
         );
+        self
+    }
+
+    fn match_port(mut self, port: u16, protocol: Protocol, source: bool) -> Self {
+        self = self.load_port(protocol, source);
         self.add_expr(Cmp::new(CmpOp::Eq, port.to_be_bytes()));
         self
     }
 
+    fn match_port_range(
+        mut self,
+        ports: RangeInclusive<u16>,
+        protocol: Protocol,
+        source: bool,
+    ) -> Result<Self, BuilderError> {
+        if ports.start() > ports.end() {
+            return Err(BuilderError::InvalidRange);
+        }
+        self = self.load_port(protocol, source);
+        self.add_expr(Range::new(
+            RangeOp::Eq,
+            ports.start().to_be_bytes(),
+            ports.end().to_be_bytes(),
+        )?);
+        Ok(self)
+    }
+
     pub fn match_ip(mut self, ip: IpAddr, source: bool) -> Self {
         self.add_expr(Meta::new(MetaType::NfProto));
         match ip {
@@ -108,6 +190,27 @@ impl Rule {
 }
 
 impl Rule {
+    fn meta_set(mut self, key: MetaType, data: Vec<u8>) -> Self {
+        self.add_expr(Immediate::new_data(data, Register::Reg1));
+        self.add_expr(Meta::set(key, Register::Reg1));
+        self
+    }
+
+    fn tcp_syn(mut self) -> Result<Self, BuilderError> {
+        self.add_expr(
+            HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Flags)).build(),
+        );
+        self.add_expr(Bitwise::new([TCP_FLAG_SYN], [0u8])?);
+        self.add_expr(Cmp::new(CmpOp::Neq, [0u8]));
+        Ok(self)
+    }
+
+    fn ct_set(mut self, key: ConntrackKey, data: Vec<u8>) -> Self {
+        self.add_expr(Immediate::new_data(data, Register::Reg1));
+        self.add_expr(Conntrack::new_set(key, Register::Reg1));
+        self
+    }
+
     /// Matches ICMP packets.
     pub fn icmp(mut self) -> Self {
         // quid of icmpv6?
@@ -123,12 +226,31 @@ impl Rule {
     }
     /// Matches packets from source `port` and `protocol`.
     pub fn sport(self, port: u16, protocol: Protocol) -> Self {
-        self.match_port(port, protocol, false)
+        self.match_port(port, protocol, true)
     }
     /// Matches packets to destination `port` and `protocol`.
     pub fn dport(self, port: u16, protocol: Protocol) -> Self {
         self.match_port(port, protocol, false)
     }
+    /// Matches packets from a source port in `ports` and `protocol` (`tcp sport 1000-2000`).
+    /// Fails with [`BuilderError::InvalidRange`] if the range is reversed.
+    pub fn sport_range(
+        self,
+        ports: RangeInclusive<u16>,
+        protocol: Protocol,
+    ) -> Result<Self, BuilderError> {
+        self.match_port_range(ports, protocol, true)
+    }
+    /// Matches packets to a destination port in `ports` and `protocol`
+    /// (`tcp dport 1000-2000`). Fails with [`BuilderError::InvalidRange`] if the range is
+    /// reversed.
+    pub fn dport_range(
+        self,
+        ports: RangeInclusive<u16>,
+        protocol: Protocol,
+    ) -> Result<Self, BuilderError> {
+        self.match_port_range(ports, protocol, false)
+    }
     /// Matches packets on `protocol`.
     pub fn protocol(mut self, protocol: Protocol) -> Self {
         self.add_expr(Meta::new(MetaType::L4Proto));
@@ -142,21 +264,78 @@ impl Rule {
         self
     }
     /// Matches packets in an already established connection.
-    pub fn established(mut self) -> Result<Self, BuilderError> {
-        let allowed_states = ConnTrackState::ESTABLISHED.bits();
+    pub fn established(self) -> Result<Self, BuilderError> {
+        self.ct_state(ConnTrackState::ESTABLISHED)
+    }
+    /// Matches packets whose connection is in any of `states`, as in
+    /// `ct state { established, related }`.
+    pub fn ct_state(mut self, states: ConnTrackState) -> Result<Self, BuilderError> {
         self.add_expr(Conntrack::new(ConntrackKey::State));
         self.add_expr(Bitwise::new(
-            allowed_states.to_le_bytes(),
+            states.bits().to_ne_bytes(),
             0u32.to_be_bytes(),
         )?);
         self.add_expr(Cmp::new(CmpOp::Neq, 0u32.to_be_bytes()));
         Ok(self)
     }
+    /// Sets the mark of the connection of the packets to `mark` (`ct mark set mark`).
+    pub fn ct_set_mark(self, mark: u32) -> Self {
+        self.ct_set(ConntrackKey::Mark, mark.to_ne_bytes().to_vec())
+    }
+    /// Sets the conntrack zone of the packets to `zone`, to keep apart the connections of
+    /// overlapping networks (`ct zone set zone`). Only effective before the packets are tracked,
+    /// in `raw` priority chains.
+    pub fn ct_set_zone(self, zone: u16) -> Self {
+        self.ct_set(ConntrackKey::Zone, zone.to_ne_bytes().to_vec())
+    }
+    /// Adds the label with the given bit number (0 to 127) to the connection of the packets
+    /// (`ct label set label`). Fails with [`BuilderError::InvalidCtLabel`] for higher bit
+    /// numbers.
+    pub fn ct_set_label(self, label: u8) -> Result<Self, BuilderError> {
+        if label >= 128 {
+            return Err(BuilderError::InvalidCtLabel(label));
+        }
+        let mut words = [0u32; 4];
+        words[label as usize / 32] = 1 << (label % 32);
+        Ok(self.ct_set(
+            ConntrackKey::Labels,
+            words.iter().flat_map(|word| word.to_ne_bytes()).collect(),
+        ))
+    }
+    /// Restricts the conntrack events reported to userspace for the connection of the packets to
+    /// `events` (`ct event set events`).
+    pub fn ct_set_events(self, events: ConnTrackEvents) -> Self {
+        self.ct_set(
+            ConntrackKey::EventMask,
+            events.bits().to_ne_bytes().to_vec(),
+        )
+    }
+    /// Labels the packets with the secmark object `name` of the table, for SELinux policies
+    /// (`meta secmark set "name"`). See [`Secmark`](crate::Secmark) for the object itself.
+    pub fn set_secmark(mut self, name: &str) -> Self {
+        self.add_expr(Objref::new(ObjectType::Secmark, name));
+        self
+    }
+    /// Saves the security mark of the packets to their connection
+    /// (`ct secmark set meta secmark`), so that it can be restored on the following packets
+    /// with [`Rule::ct_restore_secmark`].
+    pub fn ct_save_secmark(mut self) -> Self {
+        self.add_expr(Meta::new(MetaType::Secmark));
+        self.add_expr(Conntrack::new_set(ConntrackKey::Secmark, Register::Reg1));
+        self
+    }
+    /// Sets the security mark of the packets to the one saved in their connection
+    /// (`meta secmark set ct secmark`).
+    pub fn ct_restore_secmark(mut self) -> Self {
+        self.add_expr(Conntrack::new(ConntrackKey::Secmark));
+        self.add_expr(Meta::set(MetaType::Secmark, Register::Reg1));
+        self
+    }
     /// Matches packets going through `iface_index`. Interface indexes can be queried with
     /// `iface_index()`.
     pub fn iface_id(mut self, iface_index: libc::c_uint) -> Self {
         self.add_expr(Meta::new(MetaType::Iif));
-        self.add_expr(Cmp::new(CmpOp::Eq, iface_index.to_be_bytes()));
+        self.add_expr(Cmp::new(CmpOp::Eq, iface_index.to_ne_bytes()));
         self
     }
     /// Matches packets going through `iface_name`, an interface name, as in "wlan0" or "lo"
@@ -188,6 +367,421 @@ impl Rule {
     pub fn dnetwork(self, net: IpNetwork) -> Result<Self, BuilderError> {
         self.match_network(net, false)
     }
+    /// Matches at most `rate` packets per `unit`, for instance to protect a service from floods.
+    pub fn limit(mut self, rate: u64, unit: LimitUnit) -> Self {
+        self.add_expr(Limit::new(rate, unit));
+        self
+    }
+    /// Drops the packets that would not be routed back through the interface they came from,
+    /// like the strict mode of the `rp_filter` sysctl (`fib saddr . iif oif missing drop`).
+    pub fn rpfilter(mut self) -> Self {
+        self.add_expr(Fib::new(
+            FibFlags::SADDR | FibFlags::IIF | FibFlags::PRESENT,
+            FibResult::Oif,
+        ));
+        self.add_expr(Cmp::new(CmpOp::Eq, [0u8]));
+        self.drop()
+    }
+    /// Lowers the maximum segment size announced by TCP SYN packets to the one of the route they
+    /// take, so that the connections through tunnels with a smaller MTU are not stalled by
+    /// fragmentation issues (`tcp flags syn tcp option maxseg size set rt mtu`).
+    pub fn clamp_mss_to_pmtu(mut self) -> Result<Self, BuilderError> {
+        self = self.protocol(Protocol::TCP).tcp_syn()?;
+        self.add_expr(Rt::new(RtKey::TcpMss));
+        // the MSS of the route is loaded in host byte order
+        self.add_expr(Byteorder::new(ByteorderOp::Hton, Register::Reg1, 2, 2));
+        self.add_expr(ExthdrField::Tcp(TcpOptionField::MaxSegSize).build_write(Register::Reg1));
+        Ok(self)
+    }
+    /// Disables connection tracking for the packets (`notrack`). Only effective before the
+    /// packets are tracked, in `raw` priority chains.
+    pub fn notrack(mut self) -> Self {
+        self.add_expr(Notrack::default());
+        self
+    }
+    /// Disables connection tracking for the TCP SYN packets to `port`, the first step of the
+    /// synproxy recipe (`tcp dport port tcp flags syn notrack`), to add to a `raw` priority
+    /// chain hooked on prerouting. See [`Rule::synproxy`] for the second step.
+    pub fn notrack_syn(self, port: u16) -> Result<Self, BuilderError> {
+        Ok(self.dport(port, Protocol::TCP).tcp_syn()?.notrack())
+    }
+    /// Hands the TCP packets to `port` that are not part of a known connection to `synproxy`,
+    /// which answers the SYN packets with SYN cookies and only lets the connection reach the
+    /// server once the client completed the handshake
+    /// (`tcp dport port ct state invalid,untracked synproxy mss 1460 wscale 7 timestamp
+    /// sack-perm`).
+    ///
+    /// The SYN packets must have been left untracked by a [`Rule::notrack_syn`] rule, and this
+    /// rule should be followed in the filter chain by one dropping the remaining invalid
+    /// packets, such as the ACK packets with a wrong cookie
+    /// (`Rule::new(&chain)?.ct_state(ConnTrackState::INVALID)?.drop()`). The
+    /// `net.ipv4.tcp_syncookies` and `net.netfilter.nf_conntrack_tcp_loose` sysctls should be
+    /// set to 1 and 0 respectively.
+    pub fn synproxy(self, port: u16, synproxy: Synproxy) -> Result<Self, BuilderError> {
+        let mut rule = self
+            .dport(port, Protocol::TCP)
+            .ct_state(ConnTrackState::INVALID | ConnTrackState::UNTRACKED)?;
+        rule.add_expr(synproxy);
+        Ok(rule)
+    }
+    /// Matches the packets encapsulated with `encap` whose inner `field` is `value`, for instance
+    /// to apply a policy to the traffic of an overlay network (`vxlan ip saddr 10.0.0.1`). The
+    /// VXLAN and Geneve packets are expected on their standard UDP port, and the protocol of the
+    /// inner packet is checked before its network or transport header is loaded. May return
+    /// BuilderError::IncompatibleLength if `value` is not as long as `field`.
+    pub fn match_inner(
+        mut self,
+        encap: Encapsulation,
+        field: HighLevelPayload,
+        value: impl Into<Vec<u8>>,
+    ) -> Result<Self, BuilderError> {
+        let value = value.into();
+        if value.len() != field.field().len() as usize {
+            return Err(BuilderError::IncompatibleLength);
+        }
+        self = match encap {
+            Encapsulation::Vxlan => self.dport(VXLAN_PORT, Protocol::UDP),
+            Encapsulation::Geneve => self.dport(GENEVE_PORT, Protocol::UDP),
+            Encapsulation::Gre | Encapsulation::Gretap => {
+                self.add_expr(Meta::new(MetaType::L4Proto));
+                self.add_expr(Cmp::new(CmpOp::Eq, [libc::IPPROTO_GRE as u8]));
+                self
+            }
+        };
+        match field {
+            HighLevelPayload::LinkLayer(_) => {}
+            HighLevelPayload::Network(field) => {
+                self.add_expr(Inner::new_meta(encap, Meta::new(MetaType::Protocol)));
+                self.add_expr(Cmp::new(CmpOp::Eq, ethertype(&field).to_be_bytes()));
+            }
+            HighLevelPayload::Transport(field) => {
+                self.add_expr(Inner::new_meta(encap, Meta::new(MetaType::L4Proto)));
+                self.add_expr(Cmp::new(CmpOp::Eq, [l4proto(&field)]));
+            }
+        }
+        self.add_expr(Inner::new(encap, field));
+        if let Some(mask) = field.build_mask() {
+            self.add_expr(mask);
+        }
+        self.add_expr(Cmp::new(CmpOp::Eq, value));
+        Ok(self)
+    }
+
+    /// Destination NATs the IPv4 packets to one of `backends`, picked by hashing their source
+    /// address so that a client always reaches the same backend
+    /// (`dnat to jhash ip saddr mod N map { 0 : backends[0], ... }`).
+    ///
+    /// Returns the map of the backends, named `map_name`, along with its elements. Both must be
+    /// added to the batch before the rule. Fails with [`BuilderError::InvalidBackendCount`] if
+    /// `backends` is empty or has more elements than the hash can spread the packets over.
+    pub fn dnat_jhash(
+        mut self,
+        map_name: &str,
+        backends: &[Ipv4Addr],
+    ) -> Result<(Self, Set, SetElementList), BuilderError> {
+        let nb_backends = match u32::try_from(backends.len()) {
+            Ok(0) | Err(_) => return Err(BuilderError::InvalidBackendCount),
+            Ok(nb_backends) => nb_backends,
+        };
+        let table = Table::new(self.get_family())
+            .with_name(self.get_table().ok_or(BuilderError::MissingTableName)?);
+        let mut map = MapBuilder::<u32, Ipv4Addr>::new(map_name, &table)?;
+        for (idx, backend) in backends.iter().enumerate() {
+            map.add(&(idx as u32), backend);
+        }
+        let (map, elements) = map.finish();
+
+        self.add_expr(Meta::new(MetaType::NfProto));
+        self.add_expr(Cmp::new(CmpOp::Eq, [libc::NFPROTO_IPV4 as u8]));
+        self.add_expr(
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Saddr)).build(),
+        );
+        self.add_expr(Hash::new_jhash(Register::Reg1, 4, nb_backends));
+        self.add_expr(Lookup::new(&map)?.with_dreg(Register::Reg1));
+        self.add_expr(
+            Nat::default()
+                .with_nat_type(NatType::DNat)
+                .with_family(ProtocolFamily::Ipv4)
+                .with_ip_register(Register::Reg1),
+        );
+        Ok((self, map, elements))
+    }
+    /// Source NATs the packets to `addr`, and to a port in `ports` if given, with the options in
+    /// `flags` (`snat to 10.0.0.1-10.0.0.5:1024-2048 random persistent`).
+    pub fn snat_to(
+        self,
+        addr: impl Into<NatAddress>,
+        ports: Option<RangeInclusive<u16>>,
+        flags: NatFlags,
+    ) -> Result<Self, BuilderError> {
+        self.nat_to(NatType::SNat, addr.into(), ports, flags)
+    }
+    /// Destination NATs the packets to `addr`, and to a port in `ports` if given, with the
+    /// options in `flags`, for instance to forward a port to a host behind the firewall
+    /// (`dnat to 192.168.1.10:8080`).
+    pub fn dnat_to(
+        self,
+        addr: impl Into<NatAddress>,
+        ports: Option<RangeInclusive<u16>>,
+        flags: NatFlags,
+    ) -> Result<Self, BuilderError> {
+        self.nat_to(NatType::DNat, addr.into(), ports, flags)
+    }
+    fn nat_to(
+        mut self,
+        nat_type: NatType,
+        addr: NatAddress,
+        ports: Option<RangeInclusive<u16>>,
+        flags: NatFlags,
+    ) -> Result<Self, BuilderError> {
+        let (min, max) = addr.bounds()?;
+        let mut nat = Nat::default()
+            .with_nat_type(nat_type)
+            .with_family(match min {
+                IpAddr::V4(_) => ProtocolFamily::Ipv4,
+                IpAddr::V6(_) => ProtocolFamily::Ipv6,
+            })
+            .with_ip_register(Register::Reg1);
+        self.add_expr(Immediate::new_data(ip_to_vec(min), Register::Reg1));
+        if min != max {
+            self.add_expr(Immediate::new_data(ip_to_vec(max), Register::Reg2));
+            nat.set_ip_register_max(Register::Reg2);
+        }
+        if !flags.is_empty() {
+            nat.set_flags(flags.bits());
+        }
+        if let Some(ports) = ports {
+            if ports.start() > ports.end() {
+                return Err(BuilderError::InvalidRange);
+            }
+            self.add_expr(Immediate::new_data(
+                ports.start().to_be_bytes().to_vec(),
+                Register::Reg3,
+            ));
+            nat.set_port_register(Register::Reg3);
+            if ports.start() != ports.end() {
+                self.add_expr(Immediate::new_data(
+                    ports.end().to_be_bytes().to_vec(),
+                    Register::Reg4,
+                ));
+                nat.set_port_register_max(Register::Reg4);
+            }
+        }
+        self.add_expr(nat);
+        Ok(self)
+    }
+    /// Redirects the packets to the local machine on `port`, for instance to a local proxy
+    /// (`redirect to :port`). Should follow a match on the layer 4 protocol.
+    pub fn redirect_to(mut self, port: u16) -> Self {
+        self.add_expr(Immediate::new_data(
+            port.to_be_bytes().to_vec(),
+            Register::Reg1,
+        ));
+        self.add_expr(Redir::default().with_reg_proto_min(Register::Reg1));
+        self
+    }
+    /// Accepts the packets belonging to a local socket with the `IP_TRANSPARENT` option, such as
+    /// the ones of the connections already intercepted by a transparent proxy, and marks them with
+    /// `mark` so that they are routed locally (`socket transparent 1 meta mark set mark accept`).
+    pub fn transparent_socket(mut self, mark: u32) -> Self {
+        self.add_expr(Socket::new(SocketKey::Transparent));
+        self.add_expr(Cmp::new(CmpOp::Eq, [1u8]));
+        self.set_mark(mark).accept()
+    }
+    /// Diverts the packets on `protocol` to the local transparent proxy listening on `port`, and
+    /// marks them with `mark` so that they are routed locally
+    /// (`meta l4proto tcp tproxy to :port meta mark set mark accept`).
+    ///
+    /// The packets of the connections already established with the proxy should be accepted
+    /// beforehand with [`Rule::transparent_socket`].
+    pub fn tproxy_to(mut self, port: u16, protocol: Protocol, mark: u32) -> Self {
+        self = self.protocol(protocol);
+        self.add_expr(Immediate::new_data(
+            port.to_be_bytes().to_vec(),
+            Register::Reg1,
+        ));
+        self.add_expr(Tproxy::new(ProtocolFamily::Unspec).with_reg_port(Register::Reg1));
+        self.set_mark(mark).accept()
+    }
+    /// Sends a copy of the packets to `addr`, through the interface `iface` if given, for
+    /// instance to mirror the traffic to an IDS (`dup to addr device iface`).
+    pub fn dup_to(mut self, addr: IpAddr, iface: Option<&str>) -> Result<Self, BuilderError> {
+        self.add_expr(Immediate::new_data(ip_to_vec(addr), Register::Reg1));
+        let mut dup = Dup::default().with_sreg_addr(Register::Reg1);
+        if let Some(iface) = iface {
+            self.add_expr(Immediate::new_data(iface_data(iface)?, Register::Reg2));
+            dup.set_sreg_dev(Register::Reg2);
+        }
+        self.add_expr(dup);
+        Ok(self)
+    }
+    /// Sends a copy of the packets as is through the interface `iface`. Only available in
+    /// `netdev` tables (`dup to iface`).
+    pub fn dup_to_device(mut self, iface: &str) -> Result<Self, BuilderError> {
+        self.add_expr(Immediate::new_data(iface_data(iface)?, Register::Reg1));
+        self.add_expr(Dup::default().with_sreg_dev(Register::Reg1));
+        Ok(self)
+    }
+    /// Sends the packets as is through the interface `iface`, bypassing the rest of the network
+    /// stack. Only available in the ingress hook of `netdev` tables (`fwd to iface`).
+    pub fn fwd_to(mut self, iface: &str) -> Result<Self, BuilderError> {
+        self.add_expr(Immediate::new_data(iface_data(iface)?, Register::Reg1));
+        self.add_expr(Fwd::default().with_sreg_dev(Register::Reg1));
+        Ok(self)
+    }
+    /// Routes the packets to the neighbour `addr` through the interface `iface`, bypassing the
+    /// rest of the network stack. Only available in the ingress hook of `netdev` tables
+    /// (`fwd ip to addr device iface`).
+    pub fn fwd_to_neigh(mut self, addr: IpAddr, iface: &str) -> Result<Self, BuilderError> {
+        self.add_expr(Immediate::new_data(iface_data(iface)?, Register::Reg1));
+        self.add_expr(Immediate::new_data(ip_to_vec(addr), Register::Reg2));
+        self.add_expr(
+            Fwd::default()
+                .with_sreg_dev(Register::Reg1)
+                .with_sreg_addr(Register::Reg2)
+                .with_nfproto(match addr {
+                    IpAddr::V4(_) => ProtocolFamily::Ipv4,
+                    IpAddr::V6(_) => ProtocolFamily::Ipv6,
+                }),
+        );
+        Ok(self)
+    }
+    /// Sends the packets to the userspace programs listening on the NFQUEUE queues `first` to
+    /// `last`, which issue the verdict (`queue num first-last`). See [`Queue::new_range`] for
+    /// the ranges that are refused.
+    pub fn queue(mut self, first: u16, last: u16, flags: QueueFlags) -> Result<Self, BuilderError> {
+        self.add_expr(Queue::new_range(first, last, flags)?);
+        Ok(self)
+    }
+    /// Sets the mark of the packets to `mark`, for instance to select a routing table with an
+    /// `ip rule` (`meta mark set mark`).
+    pub fn set_mark(self, mark: u32) -> Self {
+        self.meta_set(MetaType::Mark, mark.to_ne_bytes().to_vec())
+    }
+    /// Sets the priority of the packets to the tc class `major:minor`
+    /// (`meta priority set major:minor`).
+    pub fn set_priority(self, major: u16, minor: u16) -> Self {
+        let handle = (major as u32) << 16 | minor as u32;
+        self.meta_set(MetaType::Priority, handle.to_ne_bytes().to_vec())
+    }
+    /// Enables the tracing of the packets, which can then be followed with `nft monitor trace`
+    /// (`meta nftrace set 1`).
+    pub fn nftrace(self) -> Self {
+        self.meta_set(MetaType::NfTrace, vec![1u8])
+    }
+    /// Sets the TTL of the IPv4 packets to `ttl`, updating their checksum (`ip ttl set ttl`).
+    pub fn set_ttl(mut self, ttl: u8) -> Self {
+        self.add_expr(Meta::new(MetaType::NfProto));
+        self.add_expr(Cmp::new(CmpOp::Eq, [libc::NFPROTO_IPV4 as u8]));
+        self.add_expr(Immediate::new_data(vec![ttl], Register::Reg1));
+        // the TTL starts on a 16 bits word
+        self.add_expr(
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Ttl))
+                .set(Register::Reg1)
+                .unwrap(),
+        );
+        self
+    }
+    /// Sets the DSCP of the IPv4 packets to `dscp`, updating their checksum
+    /// (`ip dscp set dscp`).
+    ///
+    /// The DSCP only spans the 6 upper bits of the second byte of the header, so the first two
+    /// bytes are loaded, changed and written back as a whole. Fails with
+    /// [`BuilderError::InvalidDscp`] if `dscp` does not fit in 6 bits.
+    pub fn set_dscp(mut self, dscp: u8) -> Result<Self, BuilderError> {
+        if dscp > 0x3f {
+            return Err(BuilderError::InvalidDscp(dscp));
+        }
+        self.add_expr(Meta::new(MetaType::NfProto));
+        self.add_expr(Cmp::new(CmpOp::Eq, [libc::NFPROTO_IPV4 as u8]));
+        let version_tos = Payload::default()
+            .with_base(NFT_PAYLOAD_NETWORK_HEADER)
+            .with_offset(0u32)
+            .with_len(2u32);
+        self.add_expr(version_tos.with_dreg(Register::Reg1));
+        self.add_expr(Bitwise::new([0xffu8, 0x03], [0u8, dscp << 2])?);
+        self.add_expr(
+            version_tos
+                .with_sreg(Register::Reg1)
+                .with_csum_type(PayloadCsumType::Inet)
+                .with_csum_offset(IPv4HeaderField::Checksum.offset()),
+        );
+        Ok(self)
+    }
+    /// Rejects the packets with `kind`, converted to the reject type supported by the family of
+    /// the table (`reject with icmpx port-unreachable`).
+    ///
+    /// In `inet` tables, ICMP and ICMPv6 errors only apply to the packets of their family, which
+    /// are matched first unless the rule already does. TCP resets require the rule to match TCP
+    /// packets beforehand, for instance with [`Rule::protocol`]. The other combinations are
+    /// refused with [`BuilderError::UnsupportedReject`].
+    pub fn reject(mut self, kind: RejectKind) -> Result<Self, BuilderError> {
+        let matches = self.decode().matches;
+        let nfproto = matches.iter().rev().find_map(|m| match m {
+            Match::NfProto(proto) => Some(*proto as i32),
+            _ => None,
+        });
+        let unsupported = Err(BuilderError::UnsupportedReject(kind));
+        let icmp_unreach = |code: u8| {
+            Reject::default()
+                .with_type(RejectType::IcmpUnreach)
+                .with_icmp_code(code)
+        };
+        let reject = match (self.get_family(), kind) {
+            (ProtocolFamily::Arp | ProtocolFamily::Unspec | ProtocolFamily::DecNet, _) => {
+                return unsupported
+            }
+            (_, RejectKind::TcpReset) => {
+                let tcp = matches.iter().any(|m| match m {
+                    Match::L4Proto(proto) => *proto as i32 == libc::IPPROTO_TCP,
+                    Match::TcpSport(_) | Match::TcpDport(_) => true,
+                    _ => false,
+                });
+                if !tcp {
+                    return unsupported;
+                }
+                Reject::default().with_type(RejectType::TcpRst)
+            }
+            (ProtocolFamily::Ipv4, RejectKind::Icmpx(code)) => {
+                icmp_unreach(Icmpv4Code::from(code).into())
+            }
+            (ProtocolFamily::Ipv6, RejectKind::Icmpx(code)) => {
+                icmp_unreach(Icmpv6Code::from(code).into())
+            }
+            (_, RejectKind::Icmpx(code)) => Reject::default()
+                .with_type(RejectType::IcmpxUnreach)
+                .with_icmp_code(code),
+            (ProtocolFamily::Ipv4, RejectKind::Icmp(code)) => icmp_unreach(code.into()),
+            (ProtocolFamily::Ipv6, RejectKind::Icmpv6(code)) => icmp_unreach(code.into()),
+            (ProtocolFamily::Inet, RejectKind::Icmp(code)) => {
+                if !self.ensure_nfproto(nfproto, libc::NFPROTO_IPV4) {
+                    return unsupported;
+                }
+                icmp_unreach(code.into())
+            }
+            (ProtocolFamily::Inet, RejectKind::Icmpv6(code)) => {
+                if !self.ensure_nfproto(nfproto, libc::NFPROTO_IPV6) {
+                    return unsupported;
+                }
+                icmp_unreach(code.into())
+            }
+            _ => return unsupported,
+        };
+        self.add_expr(reject);
+        Ok(self)
+    }
+    /// Matches the packets of the netfilter protocol `proto`, unless the rule already matches the
+    /// protocol `matched`. Returns whether both protocols agree.
+    fn ensure_nfproto(&mut self, matched: Option<i32>, proto: i32) -> bool {
+        match matched {
+            Some(matched) => matched == proto,
+            None => {
+                self.add_expr(Meta::new(MetaType::NfProto));
+                self.add_expr(Cmp::new(CmpOp::Eq, [proto as u8]));
+                true
+            }
+        }
+    }
     /// Adds the `Accept` verdict to the rule. The packet will be sent to destination.
     pub fn accept(mut self) -> Self {
         self.add_expr(Immediate::new_verdict(VerdictKind::Accept));
@@ -200,6 +794,38 @@ impl Rule {
     }
 }
 
+/// Ethertype of the packets holding the network header `field`.
+fn ethertype(field: &NetworkHeaderField) -> u16 {
+    let ethertype = match field {
+        NetworkHeaderField::IPv4(_) => libc::ETH_P_IP,
+        NetworkHeaderField::IPv6(_) => libc::ETH_P_IPV6,
+        NetworkHeaderField::Arp(_) => libc::ETH_P_ARP,
+    };
+    ethertype as u16
+}
+
+/// Layer 4 protocol of the packets holding the transport header `field`.
+fn l4proto(field: &TransportHeaderField) -> u8 {
+    let proto = match field {
+        TransportHeaderField::Tcp(_) => libc::IPPROTO_TCP,
+        TransportHeaderField::Udp(_) => libc::IPPROTO_UDP,
+        TransportHeaderField::ICMP(_) => libc::IPPROTO_ICMP,
+        TransportHeaderField::ICMPv6(_) => libc::IPPROTO_ICMPV6,
+        TransportHeaderField::Sctp(_) => libc::IPPROTO_SCTP,
+        TransportHeaderField::Dccp(_) => libc::IPPROTO_DCCP,
+        TransportHeaderField::Ah(_) => libc::IPPROTO_AH,
+        TransportHeaderField::Esp(_) => libc::IPPROTO_ESP,
+        TransportHeaderField::Gre(_) => libc::IPPROTO_GRE,
+    };
+    proto as u8
+}
+
+/// Looks up the index of `iface`, as stored in registers.
+fn iface_data(iface: &str) -> Result<Vec<u8>, BuilderError> {
+    let index = iface_index(iface).map_err(BuilderError::InterfaceNotFound)?;
+    Ok(index.to_ne_bytes().to_vec())
+}
+
 /// Looks up the interface index for a given interface name.
 pub fn iface_index(name: &str) -> Result<libc::c_uint, std::io::Error> {
     let c_name = CString::new(name)?;
@@ -2,13 +2,14 @@ use rustables_macros::nfnetlink_struct;
 
 use crate::data_type::DataType;
 use crate::error::BuilderError;
-use crate::nlmsg::NfNetlinkObject;
+use crate::nlmsg::{pad_netlink_object, NfNetlinkAttribute, NfNetlinkObject};
 use crate::parser_impls::{NfNetlinkData, NfNetlinkList};
 use crate::sys::{
-    NFTA_SET_ELEM_KEY, NFTA_SET_ELEM_LIST_ELEMENTS, NFTA_SET_ELEM_LIST_SET,
-    NFTA_SET_ELEM_LIST_TABLE, NFTA_SET_FLAGS, NFTA_SET_ID, NFTA_SET_KEY_LEN, NFTA_SET_KEY_TYPE,
-    NFTA_SET_NAME, NFTA_SET_TABLE, NFTA_SET_USERDATA, NFT_MSG_DELSET, NFT_MSG_DELSETELEM,
-    NFT_MSG_NEWSET, NFT_MSG_NEWSETELEM,
+    nlattr, NFTA_SET_DATA_LEN, NFTA_SET_DATA_TYPE, NFTA_SET_ELEM_DATA, NFTA_SET_ELEM_KEY,
+    NFTA_SET_ELEM_LIST_ELEMENTS, NFTA_SET_ELEM_LIST_SET, NFTA_SET_ELEM_LIST_TABLE, NFTA_SET_FLAGS,
+    NFTA_SET_ID, NFTA_SET_KEY_LEN, NFTA_SET_KEY_TYPE, NFTA_SET_NAME, NFTA_SET_TABLE,
+    NFTA_SET_USERDATA, NFT_MSG_DELSET, NFT_MSG_DELSETELEM, NFT_MSG_NEWSET, NFT_MSG_NEWSETELEM,
+    NFT_SET_MAP,
 };
 use crate::table::Table;
 use crate::ProtocolFamily;
@@ -29,6 +30,10 @@ pub struct Set {
     pub key_type: u32,
     #[field(NFTA_SET_KEY_LEN)]
     pub key_len: u32,
+    #[field(NFTA_SET_DATA_TYPE)]
+    pub data_type: u32,
+    #[field(NFTA_SET_DATA_LEN)]
+    pub data_len: u32,
     #[field(NFTA_SET_ID)]
     pub id: u32,
     #[field(NFTA_SET_USERDATA)]
@@ -78,9 +83,57 @@ impl<K: DataType> SetBuilder<K> {
     pub fn add(&mut self, key: &K) {
         self.list.elements.as_mut().unwrap().add_value(SetElement {
             key: Some(NfNetlinkData::default().with_value(key.data())),
+            data: None,
         });
     }
 
+    /// Returns the set and the list of its elements. The elements should be added to a batch
+    /// with [`Batch::add_set_elements`](crate::Batch::add_set_elements), which splits large
+    /// lists over several messages.
+    pub fn finish(self) -> (Set, SetElementList) {
+        (self.inner, self.list)
+    }
+}
+
+/// Builds a map, a set whose elements associate a key of type `K` to a value of type `V`.
+pub struct MapBuilder<K: DataType, V: DataType> {
+    inner: Set,
+    list: SetElementList,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<K: DataType, V: DataType> MapBuilder<K, V> {
+    pub fn new(name: impl Into<String>, table: &Table) -> Result<Self, BuilderError> {
+        let table_name = table.get_name().ok_or(BuilderError::MissingTableName)?;
+        let map_name = name.into();
+        let map = Set::default()
+            .with_flags(NFT_SET_MAP)
+            .with_key_type(K::TYPE)
+            .with_key_len(K::LEN)
+            .with_data_type(V::TYPE)
+            .with_data_len(V::LEN)
+            .with_table(table_name)
+            .with_name(&map_name);
+
+        Ok(MapBuilder {
+            inner: map,
+            list: SetElementList {
+                table: Some(table_name.clone()),
+                set: Some(map_name),
+                elements: Some(SetElementListElements::default()),
+            },
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn add(&mut self, key: &K, value: &V) {
+        self.list.elements.as_mut().unwrap().add_value(SetElement {
+            key: Some(NfNetlinkData::default().with_value(key.data())),
+            data: Some(NfNetlinkData::default().with_value(value.data())),
+        });
+    }
+
+    /// Returns the map and the list of its elements, see [`SetBuilder::finish`].
     pub fn finish(self) -> (Set, SetElementList) {
         (self.inner, self.list)
     }
@@ -97,6 +150,47 @@ pub struct SetElementList {
     pub elements: SetElementListElements,
 }
 
+impl SetElementList {
+    /// Splits this list into lists holding as many elements as possible while still fitting in a
+    /// single netlink message, as the length of the NFTA_SET_ELEM_LIST_ELEMENTS attribute is
+    /// stored on 16 bits.
+    ///
+    /// An element too large to fit alone in a message is still put in its own list, to be
+    /// rejected when adding that list to a batch.
+    pub fn split(&self) -> Vec<SetElementList> {
+        let elements = match &self.elements {
+            Some(elements) if !elements.is_empty() => elements,
+            _ => return vec![self.clone()],
+        };
+
+        let chunk_template = SetElementList {
+            table: self.table.clone(),
+            set: self.set.clone(),
+            elements: Some(SetElementListElements::default()),
+        };
+        let max_size = u16::MAX as usize - pad_netlink_object::<nlattr>();
+
+        let mut res = vec![chunk_template.clone()];
+        let mut size = 0;
+        for element in elements.iter() {
+            // one nlattr LIST_ELEM per element
+            let element_size = pad_netlink_object::<nlattr>() + element.get_size();
+            if size > 0 && size + element_size > max_size {
+                res.push(chunk_template.clone());
+                size = 0;
+            }
+            size += element_size;
+            res.last_mut()
+                .unwrap()
+                .elements
+                .as_mut()
+                .unwrap()
+                .add_value(element.clone());
+        }
+        res
+    }
+}
+
 impl NfNetlinkObject for SetElementList {
     const MSG_TYPE_ADD: u32 = NFT_MSG_NEWSETELEM;
     const MSG_TYPE_DEL: u32 = NFT_MSG_DELSETELEM;
@@ -111,6 +205,8 @@ impl NfNetlinkObject for SetElementList {
 pub struct SetElement {
     #[field(NFTA_SET_ELEM_KEY)]
     pub key: NfNetlinkData,
+    #[field(NFTA_SET_ELEM_DATA)]
+    pub data: NfNetlinkData,
 }
 
 type SetElementListElements = NfNetlinkList<SetElement>;
@@ -3,7 +3,7 @@ use std::fmt::Debug;
 use netlink_sys::{AsyncSocket, Socket};
 use rustables_macros::nfnetlink_struct;
 
-use crate::error::QueryError;
+use crate::error::{BuilderError, QueryError};
 use crate::nlmsg::NfNetlinkObject;
 use crate::query::list_objects_with_data_async;
 use crate::sys::{
@@ -38,9 +38,9 @@ impl Table {
     }
 
     /// Appends this rule to `batch`
-    pub fn add_to_batch(self, batch: &mut Batch) -> Self {
-        batch.add(&self, crate::MsgType::Add);
-        self
+    pub fn add_to_batch(self, batch: &mut Batch) -> Result<Self, BuilderError> {
+        batch.add(&self, crate::MsgType::Add)?;
+        Ok(self)
     }
 }
 
@@ -1,6 +1,6 @@
 use std::mem::size_of;
 
-use libc::{AF_UNSPEC, NFNL_MSG_BATCH_BEGIN, NLM_F_REQUEST};
+use libc::{AF_UNSPEC, NFNL_MSG_BATCH_BEGIN, NLM_F_ACK, NLM_F_REQUEST};
 use nix::libc::NFNL_MSG_BATCH_END;
 
 use crate::nlmsg::{pad_netlink_object_with_variable_size, NfNetlinkDeserializable};
@@ -64,14 +64,16 @@ fn batch_with_objects() {
 
     let mut batch = Batch::new();
     for i in 0..10 {
-        batch.add(
-            &original_tables[i],
-            if i % 2 == 0 {
-                MsgType::Add
-            } else {
-                MsgType::Del
-            },
-        );
+        batch
+            .add(
+                &original_tables[i],
+                if i % 2 == 0 {
+                    MsgType::Add
+                } else {
+                    MsgType::Del
+                },
+            )
+            .expect("Couldn't add a table to the batch");
     }
     let buf = batch.finalize();
 
@@ -94,3 +96,66 @@ fn batch_with_objects() {
     assert_eq!(hdr, end_hdr);
     assert_eq!(msg, DEFAULT_BATCH_MSG);
 }
+
+#[test]
+fn batch_with_pages() {
+    let mut batch = Batch::with_page_size(128);
+    let mut original_tables = vec![];
+    for i in 0..10 {
+        let mut table = get_test_table();
+        table.set_userdata(vec![i as u8]);
+        batch
+            .add(&table, MsgType::Add)
+            .expect("Couldn't add a table to the batch");
+        original_tables.push(table);
+    }
+    let pages = batch.finalize_pages();
+    assert!(pages.len() > 1);
+
+    let mut nb_tables = 0;
+    for page in &pages {
+        // messages must never straddle two pages
+        let mut remaining_data = page.as_slice();
+        while !remaining_data.is_empty() {
+            let (hdr, _msg) = parse_nlmsg(&remaining_data).expect("Invalid nlmsg message");
+            if hdr.nlmsg_type != NFNL_MSG_BATCH_BEGIN as u16
+                && hdr.nlmsg_type != NFNL_MSG_BATCH_END as u16
+            {
+                let (deserialized_table, _) =
+                    Table::deserialize(&remaining_data).expect("could not deserialize a table");
+                assert_eq!(deserialized_table, original_tables[nb_tables]);
+                nb_tables += 1;
+            }
+            remaining_data =
+                &remaining_data[pad_netlink_object_with_variable_size(hdr.nlmsg_len as usize)..];
+        }
+    }
+    assert_eq!(nb_tables, 10);
+}
+
+#[test]
+fn batch_only_acks_last_message() {
+    let mut batch = Batch::with_page_size(128);
+    for i in 0..10 {
+        let mut table = get_test_table();
+        table.set_userdata(vec![i as u8]);
+        batch
+            .add(&table, MsgType::Add)
+            .expect("Couldn't add a table to the batch");
+    }
+    let pages = batch.finalize_pages_for_sending(10);
+
+    let mut acked_seqs = vec![];
+    for page in &pages {
+        let mut remaining_data = page.as_slice();
+        while !remaining_data.is_empty() {
+            let (hdr, _msg) = parse_nlmsg(&remaining_data).expect("Invalid nlmsg message");
+            if hdr.nlmsg_flags & NLM_F_ACK as u16 != 0 {
+                acked_seqs.push(hdr.nlmsg_seq);
+            }
+            remaining_data =
+                &remaining_data[pad_netlink_object_with_variable_size(hdr.nlmsg_len as usize)..];
+        }
+    }
+    assert_eq!(acked_seqs, vec![10]);
+}
@@ -1,10 +1,10 @@
 use crate::{
-    nlmsg::get_operation_from_nlmsghdr_type,
+    nlmsg::{get_operation_from_nlmsghdr_type, nft_nlmsg_maxsize, NfNetlinkDeserializable},
     sys::{
         NFTA_CHAIN_HOOK, NFTA_CHAIN_NAME, NFTA_CHAIN_TABLE, NFTA_CHAIN_TYPE, NFTA_CHAIN_USERDATA,
         NFTA_HOOK_HOOKNUM, NFTA_HOOK_PRIORITY, NFT_MSG_DELCHAIN, NFT_MSG_NEWCHAIN,
     },
-    ChainType, Hook, HookClass, MsgType,
+    Chain, ChainPolicy, ChainType, Hook, HookClass, MsgType,
 };
 
 use super::{
@@ -118,3 +118,20 @@ fn delete_empty_chain() {
         .to_raw()
     );
 }
+
+#[test]
+fn parse_chain_policy() {
+    for policy in [ChainPolicy::Accept, ChainPolicy::Drop] {
+        let mut chain = get_test_chain()
+            .with_hook(Hook::new(HookClass::In, 0))
+            .with_policy(policy);
+        let mut buf = Vec::with_capacity(nft_nlmsg_maxsize() as usize);
+        let (_nlmsghdr, _nfgenmsg, _raw_expr) = get_test_nlmsg(&mut buf, &mut chain);
+
+        let (deserialized_chain, remaining) =
+            Chain::deserialize(&buf).expect("Couldn't deserialize the object");
+        assert_eq!(deserialized_chain.get_policy(), Some(&policy));
+        assert_eq!(chain, deserialized_chain);
+        assert_eq!(remaining.len(), 0);
+    }
+}
@@ -1,31 +1,68 @@
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
+use ipnetwork::IpNetwork;
 use libc::NF_DROP;
 
 use crate::{
+    error::{BuilderError, DecodeError},
     expr::{
-        Bitwise, Cmp, CmpOp, Conntrack, ConntrackKey, Counter, ExpressionList, HeaderField,
-        HighLevelPayload, IcmpCode, Immediate, Log, Lookup, Masquerade, Meta, MetaType, Nat,
-        NatType, Register, Reject, RejectType, TCPHeaderField, TransportHeaderField, VerdictKind,
+        AhHeaderField, ArpHeaderField, Bitwise, BitwiseOp, Byteorder, ByteorderOp, Cmp, CmpOp,
+        ConnTrackState, Connlimit, Conntrack, ConntrackKey, Counter, CtDirection, DccpHeaderField,
+        Dup, Encapsulation, EspHeaderField, Expression, ExpressionList, ExpressionVariant,
+        ExthdrField, Fib, FibFlags, FibResult, Fwd, GreHeaderField, Hash, HeaderField,
+        HighLevelPayload, ICMPHeaderField, ICMPv6HeaderField, IPv4HeaderField, IPv6HeaderField,
+        IcmpCode, Icmpv4Code, Icmpv6Code, Immediate, Inner, LLHeaderField, Last, Limit, LimitUnit,
+        Log, LogFlags, LogLevel, Lookup, Masquerade, Meta, MetaType, Nat, NatFlags, NatType,
+        NetworkHeaderField, Notrack, Numgen, NumgenType, Objref, Osf, OsfFlags, OsfTtl, Payload,
+        PayloadCsumFlags, PayloadCsumType, PayloadType, Queue, QueueFlags, Quota, Range, RangeOp,
+        Redir, Register, Reject, RejectKind, RejectType, Rt, RtKey, SctpHeaderField, Socket,
+        SocketKey, Synproxy, SynproxyFlags, TCPHeaderField, TcpOptionField, Tproxy,
+        TransportHeaderField, Tunnel, TunnelKey, TunnelMode, VerdictKind, VlanHeaderField, Xfrm,
+        XfrmDirection, XfrmKey,
     },
+    iface_index,
+    nlmsg::{NfNetlinkDeserializable, NfNetlinkObject, NfNetlinkWriter},
     set::SetBuilder,
     sys::{
-        NFTA_BITWISE_DREG, NFTA_BITWISE_LEN, NFTA_BITWISE_MASK, NFTA_BITWISE_SREG,
-        NFTA_BITWISE_XOR, NFTA_CMP_DATA, NFTA_CMP_OP, NFTA_CMP_SREG, NFTA_COUNTER_BYTES,
-        NFTA_COUNTER_PACKETS, NFTA_CT_DREG, NFTA_CT_KEY, NFTA_DATA_VALUE, NFTA_DATA_VERDICT,
-        NFTA_EXPR_DATA, NFTA_EXPR_NAME, NFTA_IMMEDIATE_DATA, NFTA_IMMEDIATE_DREG, NFTA_LIST_ELEM,
-        NFTA_LOG_GROUP, NFTA_LOG_PREFIX, NFTA_LOOKUP_SET, NFTA_LOOKUP_SREG, NFTA_META_DREG,
-        NFTA_META_KEY, NFTA_NAT_FAMILY, NFTA_NAT_REG_ADDR_MIN, NFTA_NAT_TYPE, NFTA_PAYLOAD_BASE,
-        NFTA_PAYLOAD_DREG, NFTA_PAYLOAD_LEN, NFTA_PAYLOAD_OFFSET, NFTA_REJECT_ICMP_CODE,
-        NFTA_REJECT_TYPE, NFTA_RULE_CHAIN, NFTA_RULE_EXPRESSIONS, NFTA_RULE_TABLE,
-        NFTA_VERDICT_CODE, NFT_CMP_EQ, NFT_CT_STATE, NFT_META_PROTOCOL, NFT_NAT_SNAT,
-        NFT_PAYLOAD_TRANSPORT_HEADER, NFT_REG_1, NFT_REG_VERDICT, NFT_REJECT_ICMPX_UNREACH,
+        NFTA_BITWISE_DATA, NFTA_BITWISE_DREG, NFTA_BITWISE_LEN, NFTA_BITWISE_MASK, NFTA_BITWISE_OP,
+        NFTA_BITWISE_SREG, NFTA_BITWISE_XOR, NFTA_BYTEORDER_DREG, NFTA_BYTEORDER_LEN,
+        NFTA_BYTEORDER_OP, NFTA_BYTEORDER_SIZE, NFTA_BYTEORDER_SREG, NFTA_CMP_DATA, NFTA_CMP_OP,
+        NFTA_CMP_SREG, NFTA_CONNLIMIT_COUNT, NFTA_CONNLIMIT_FLAGS, NFTA_COUNTER_BYTES,
+        NFTA_COUNTER_PACKETS, NFTA_CT_DIRECTION, NFTA_CT_DREG, NFTA_CT_KEY, NFTA_DATA_VALUE,
+        NFTA_DATA_VERDICT, NFTA_DUP_SREG_ADDR, NFTA_DUP_SREG_DEV, NFTA_EXPR_DATA, NFTA_EXPR_NAME,
+        NFTA_EXTHDR_LEN, NFTA_EXTHDR_OFFSET, NFTA_EXTHDR_OP, NFTA_EXTHDR_SREG, NFTA_EXTHDR_TYPE,
+        NFTA_FIB_DREG, NFTA_FIB_FLAGS, NFTA_FIB_F_IIF, NFTA_FIB_F_SADDR, NFTA_FIB_RESULT,
+        NFTA_FWD_NFPROTO, NFTA_FWD_SREG_ADDR, NFTA_FWD_SREG_DEV, NFTA_HASH_DREG, NFTA_HASH_LEN,
+        NFTA_HASH_MODULUS, NFTA_HASH_SEED, NFTA_HASH_SREG, NFTA_HASH_TYPE, NFTA_IMMEDIATE_DATA,
+        NFTA_IMMEDIATE_DREG, NFTA_LAST_MSECS, NFTA_LAST_SET, NFTA_LIMIT_BURST, NFTA_LIMIT_FLAGS,
+        NFTA_LIMIT_RATE, NFTA_LIMIT_TYPE, NFTA_LIMIT_UNIT, NFTA_LIST_ELEM, NFTA_LOG_GROUP,
+        NFTA_LOG_PREFIX, NFTA_LOG_QTHRESHOLD, NFTA_LOG_SNAPLEN, NFTA_LOOKUP_SET, NFTA_LOOKUP_SREG,
+        NFTA_META_DREG, NFTA_META_KEY, NFTA_META_SREG, NFTA_NAT_FAMILY, NFTA_NAT_REG_ADDR_MIN,
+        NFTA_NAT_TYPE, NFTA_NG_DREG, NFTA_NG_MODULUS, NFTA_NG_TYPE, NFTA_OBJREF_IMM_NAME,
+        NFTA_OBJREF_IMM_TYPE, NFTA_OSF_DREG, NFTA_OSF_FLAGS, NFTA_OSF_TTL, NFTA_PAYLOAD_BASE,
+        NFTA_PAYLOAD_CSUM_OFFSET, NFTA_PAYLOAD_CSUM_TYPE, NFTA_PAYLOAD_DREG, NFTA_PAYLOAD_LEN,
+        NFTA_PAYLOAD_OFFSET, NFTA_PAYLOAD_SREG, NFTA_QUEUE_FLAGS, NFTA_QUEUE_NUM, NFTA_QUEUE_TOTAL,
+        NFTA_QUOTA_BYTES, NFTA_QUOTA_FLAGS, NFTA_RANGE_FROM_DATA, NFTA_RANGE_OP, NFTA_RANGE_SREG,
+        NFTA_RANGE_TO_DATA, NFTA_REDIR_FLAGS, NFTA_REDIR_REG_PROTO_MAX, NFTA_REDIR_REG_PROTO_MIN,
+        NFTA_REJECT_ICMP_CODE, NFTA_REJECT_TYPE, NFTA_RT_DREG, NFTA_RT_KEY, NFTA_RULE_CHAIN,
+        NFTA_RULE_EXPRESSIONS, NFTA_RULE_TABLE, NFTA_SOCKET_DREG, NFTA_SOCKET_KEY,
+        NFTA_SOCKET_LEVEL, NFTA_SYNPROXY_FLAGS, NFTA_SYNPROXY_MSS, NFTA_SYNPROXY_WSCALE,
+        NFTA_TPROXY_FAMILY, NFTA_TPROXY_REG_ADDR, NFTA_TPROXY_REG_PORT, NFTA_TUNNEL_DREG,
+        NFTA_TUNNEL_KEY, NFTA_TUNNEL_MODE, NFTA_VERDICT_CODE, NFTA_XFRM_DIR, NFTA_XFRM_DREG,
+        NFTA_XFRM_KEY, NFTA_XFRM_SPNUM, NFT_BITWISE_LSHIFT, NFT_BYTEORDER_HTON, NFT_CMP_EQ,
+        NFT_CONNLIMIT_F_INV, NFT_CT_PROTO_DST, NFT_CT_STATE, NFT_EXTHDR_OP_TCPOPT,
+        NFT_FIB_RESULT_OIF, NFT_HASH_JENKINS, NFT_LIMIT_F_INV, NFT_LIMIT_PKTS, NFT_META_PRIORITY,
+        NFT_META_PROTOCOL, NFT_NAT_SNAT, NFT_NG_INCREMENTAL, NFT_OBJECT_SECMARK, NFT_OSF_F_VERSION,
+        NFT_PAYLOAD_CSUM_INET, NFT_PAYLOAD_NETWORK_HEADER, NFT_PAYLOAD_TRANSPORT_HEADER,
+        NFT_QUEUE_FLAG_BYPASS, NFT_QUEUE_FLAG_CPU_FANOUT, NFT_QUOTA_F_INV, NFT_RANGE_EQ, NFT_REG_1,
+        NFT_REG_2, NFT_REG_VERDICT, NFT_REJECT_ICMPX_UNREACH, NFT_RT_TCPMSS, NFT_SOCKET_CGROUPV2,
+        NFT_TUNNEL_ID, NFT_TUNNEL_MODE_RX, NFT_XFRM_KEY_REQID,
     },
     tests::{get_test_table, SET_NAME},
-    ProtocolFamily,
+    Batch, Chain, MsgType, ObjectType, Protocol, ProtocolFamily, Rule, Table,
 };
 
-use super::{get_test_nlmsg, get_test_rule, NetlinkExpr, CHAIN_NAME, TABLE_NAME};
+use super::{get_test_nlmsg, get_test_rule, rule_exprs, NetlinkExpr, CHAIN_NAME, TABLE_NAME};
 
 #[test]
 fn bitwise_expr_is_valid() {
@@ -85,14 +122,21 @@ fn bitwise_expr_is_valid() {
 }
 
 #[test]
-fn cmp_expr_is_valid() {
-    let val = [1u8, 2, 3, 4];
-    let cmp = Cmp::new(CmpOp::Eq, val.clone());
-    let mut rule = get_test_rule().with_expressions(vec![cmp]);
+fn bitwise_shift_expr_is_valid() {
+    assert!(matches!(
+        Bitwise::new_shift(BitwiseOp::Lshift, 4, 32),
+        Err(BuilderError::InvalidShift)
+    ));
+    assert!(matches!(
+        Bitwise::new_shift(BitwiseOp::Rshift, 2, 8),
+        Err(BuilderError::InvalidShift)
+    ));
+    let bitwise_shift = Bitwise::new_shift(BitwiseOp::Lshift, 4, 8).unwrap();
+    let mut rule = get_test_rule().with_expressions(vec![bitwise_shift]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 100);
+    assert_eq!(nlmsghdr.nlmsg_len, 120);
 
     assert_eq!(
         raw_expr,
@@ -104,16 +148,30 @@ fn cmp_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"cmp".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"bitwise".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
-                                NetlinkExpr::Final(NFTA_CMP_SREG, NFT_REG_1.to_be_bytes().to_vec()),
-                                NetlinkExpr::Final(NFTA_CMP_OP, NFT_CMP_EQ.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_BITWISE_SREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_BITWISE_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_BITWISE_LEN, 4u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_BITWISE_OP,
+                                    NFT_BITWISE_LSHIFT.to_be_bytes().to_vec()
+                                ),
                                 NetlinkExpr::Nested(
-                                    NFTA_CMP_DATA,
-                                    vec![NetlinkExpr::Final(NFTA_DATA_VALUE, val.to_vec())]
-                                )
+                                    NFTA_BITWISE_DATA,
+                                    vec![NetlinkExpr::Final(
+                                        NFTA_DATA_VALUE,
+                                        8u32.to_ne_bytes().to_vec()
+                                    )]
+                                ),
                             ]
                         )
                     ]
@@ -125,18 +183,13 @@ fn cmp_expr_is_valid() {
 }
 
 #[test]
-fn counter_expr_is_valid() {
-    let nb_bytes = 123456u64;
-    let nb_packets = 987u64;
-    let counter = Counter::default()
-        .with_nb_bytes(nb_bytes)
-        .with_nb_packets(nb_packets);
-
-    let mut rule = get_test_rule().with_expressions(vec![counter]);
+fn byteorder_expr_is_valid() {
+    let byteorder = Byteorder::new(ByteorderOp::Hton, Register::Reg1, 2, 2);
+    let mut rule = get_test_rule().with_expressions(vec![byteorder]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 100);
+    assert_eq!(nlmsghdr.nlmsg_len, 120);
 
     assert_eq!(
         raw_expr,
@@ -148,17 +201,66 @@ fn counter_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"counter".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"byteorder".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
                                 NetlinkExpr::Final(
-                                    NFTA_COUNTER_BYTES,
-                                    nb_bytes.to_be_bytes().to_vec()
+                                    NFTA_BYTEORDER_SREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Final(
-                                    NFTA_COUNTER_PACKETS,
-                                    nb_packets.to_be_bytes().to_vec()
+                                    NFTA_BYTEORDER_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_BYTEORDER_OP,
+                                    NFT_BYTEORDER_HTON.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_BYTEORDER_LEN, 2u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_BYTEORDER_SIZE,
+                                    2u32.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn cmp_expr_is_valid() {
+    let val = [1u8, 2, 3, 4];
+    let cmp = Cmp::new(CmpOp::Eq, val.clone());
+    let mut rule = get_test_rule().with_expressions(vec![cmp]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"cmp".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_CMP_SREG, NFT_REG_1.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_CMP_OP, NFT_CMP_EQ.to_be_bytes().to_vec()),
+                                NetlinkExpr::Nested(
+                                    NFTA_CMP_DATA,
+                                    vec![NetlinkExpr::Final(NFTA_DATA_VALUE, val.to_vec())]
                                 )
                             ]
                         )
@@ -171,13 +273,13 @@ fn counter_expr_is_valid() {
 }
 
 #[test]
-fn ct_expr_is_valid() {
-    let ct = Conntrack::default().with_retrieve_value(ConntrackKey::State);
-    let mut rule = get_test_rule().with_expressions(vec![ct]);
+fn connlimit_expr_is_valid() {
+    let connlimit = Connlimit::new(5, true);
+    let mut rule = get_test_rule().with_expressions(vec![connlimit]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 88);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
 
     assert_eq!(
         raw_expr,
@@ -189,15 +291,18 @@ fn ct_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"ct".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"connlimit".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
                                 NetlinkExpr::Final(
-                                    NFTA_CT_KEY,
-                                    NFT_CT_STATE.to_be_bytes().to_vec()
+                                    NFTA_CONNLIMIT_COUNT,
+                                    5u32.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_CONNLIMIT_FLAGS,
+                                    NFT_CONNLIMIT_F_INV.to_be_bytes().to_vec()
                                 ),
-                                NetlinkExpr::Final(NFTA_CT_DREG, NFT_REG_1.to_be_bytes().to_vec())
                             ]
                         )
                     ]
@@ -205,14 +310,18 @@ fn ct_expr_is_valid() {
             )
         ])
         .to_raw()
-    )
+    );
 }
 
 #[test]
-fn immediate_expr_is_valid() {
-    let immediate = Immediate::new_data(vec![42u8], Register::Reg1);
-    let mut rule =
-        get_test_rule().with_expressions(ExpressionList::default().with_value(immediate));
+fn counter_expr_is_valid() {
+    let nb_bytes = 123456u64;
+    let nb_packets = 987u64;
+    let counter = Counter::default()
+        .with_nb_bytes(nb_bytes)
+        .with_nb_packets(nb_packets);
+
+    let mut rule = get_test_rule().with_expressions(vec![counter]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
@@ -228,17 +337,17 @@ fn immediate_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"immediate".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"counter".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
                                 NetlinkExpr::Final(
-                                    NFTA_IMMEDIATE_DREG,
-                                    NFT_REG_1.to_be_bytes().to_vec()
+                                    NFTA_COUNTER_BYTES,
+                                    nb_bytes.to_be_bytes().to_vec()
                                 ),
-                                NetlinkExpr::Nested(
-                                    NFTA_IMMEDIATE_DATA,
-                                    vec![NetlinkExpr::Final(1u16, 42u8.to_be_bytes().to_vec())]
+                                NetlinkExpr::Final(
+                                    NFTA_COUNTER_PACKETS,
+                                    nb_packets.to_be_bytes().to_vec()
                                 )
                             ]
                         )
@@ -251,13 +360,13 @@ fn immediate_expr_is_valid() {
 }
 
 #[test]
-fn log_expr_is_valid() {
-    let log = Log::new(Some(1337), Some("mockprefix")).expect("Could not build a log expression");
-    let mut rule = get_test_rule().with_expressions(ExpressionList::default().with_value(log));
+fn ct_expr_is_valid() {
+    let ct = Conntrack::default().with_retrieve_value(ConntrackKey::State);
+    let mut rule = get_test_rule().with_expressions(vec![ct]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 96);
+    assert_eq!(nlmsghdr.nlmsg_len, 88);
 
     assert_eq!(
         raw_expr,
@@ -269,12 +378,15 @@ fn log_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"log".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"ct".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
-                                NetlinkExpr::Final(NFTA_LOG_GROUP, 1337u16.to_be_bytes().to_vec()),
-                                NetlinkExpr::Final(NFTA_LOG_PREFIX, b"mockprefix".to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_CT_KEY,
+                                    NFT_CT_STATE.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_CT_DREG, NFT_REG_1.to_be_bytes().to_vec())
                             ]
                         )
                     ]
@@ -282,19 +394,13 @@ fn log_expr_is_valid() {
             )
         ])
         .to_raw()
-    );
+    )
 }
 
 #[test]
-fn lookup_expr_is_valid() {
-    let table = get_test_table();
-    let mut set_builder = SetBuilder::new(SET_NAME, &table).unwrap();
-    let address: Ipv4Addr = [8, 8, 8, 8].into();
-    set_builder.add(&address);
-    let (set, _set_elements) = set_builder.finish();
-    let lookup = Lookup::new(&set).unwrap();
-
-    let mut rule = get_test_rule().with_expressions(ExpressionList::default().with_value(lookup));
+fn ct_with_direction_is_valid() {
+    let ct_direction = Conntrack::new(ConntrackKey::ProtoDst).with_direction(CtDirection::Reply);
+    let mut rule = get_test_rule().with_expressions(vec![ct_direction]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
@@ -310,15 +416,16 @@ fn lookup_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"lookup".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"ct".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
-                                NetlinkExpr::Final(NFTA_LOOKUP_SET, b"mockset".to_vec()),
+                                NetlinkExpr::Final(NFTA_CT_DREG, NFT_REG_1.to_be_bytes().to_vec()),
                                 NetlinkExpr::Final(
-                                    NFTA_LOOKUP_SREG,
-                                    NFT_REG_1.to_be_bytes().to_vec()
+                                    NFTA_CT_KEY,
+                                    NFT_CT_PROTO_DST.to_be_bytes().to_vec()
                                 ),
+                                NetlinkExpr::Final(NFTA_CT_DIRECTION, [1u8].to_vec()),
                             ]
                         )
                     ]
@@ -330,13 +437,15 @@ fn lookup_expr_is_valid() {
 }
 
 #[test]
-fn masquerade_expr_is_valid() {
-    let masquerade = Masquerade::default();
-    let mut rule = get_test_rule().with_expressions(vec![masquerade]);
+fn dup_expr_is_valid() {
+    let dup = Dup::default()
+        .with_sreg_addr(Register::Reg1)
+        .with_sreg_dev(Register::Reg2);
+    let mut rule = get_test_rule().with_expressions(vec![dup]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 72);
+    assert_eq!(nlmsghdr.nlmsg_len, 88);
 
     assert_eq!(
         raw_expr,
@@ -348,8 +457,20 @@ fn masquerade_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"masq".to_vec()),
-                        NetlinkExpr::Nested(NFTA_EXPR_DATA, vec![]),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"dup".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_DUP_SREG_ADDR,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_DUP_SREG_DEV,
+                                    NFT_REG_2.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
                     ]
                 )]
             )
@@ -359,15 +480,13 @@ fn masquerade_expr_is_valid() {
 }
 
 #[test]
-fn meta_expr_is_valid() {
-    let meta = Meta::default()
-        .with_key(MetaType::Protocol)
-        .with_dreg(Register::Reg1);
-    let mut rule = get_test_rule().with_expressions(vec![meta]);
+fn exthdr_expr_is_valid() {
+    let exthdr = ExthdrField::Tcp(TcpOptionField::MaxSegSize).build_write(Register::Reg1);
+    let mut rule = get_test_rule().with_expressions(vec![exthdr]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 88);
+    assert_eq!(nlmsghdr.nlmsg_len, 116);
 
     assert_eq!(
         raw_expr,
@@ -379,18 +498,21 @@ fn meta_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"meta".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"exthdr".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
+                                NetlinkExpr::Final(NFTA_EXTHDR_TYPE, [2u8].to_vec()),
+                                NetlinkExpr::Final(NFTA_EXTHDR_OFFSET, 2u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_EXTHDR_LEN, 2u32.to_be_bytes().to_vec()),
                                 NetlinkExpr::Final(
-                                    NFTA_META_KEY,
-                                    NFT_META_PROTOCOL.to_be_bytes().to_vec()
+                                    NFTA_EXTHDR_OP,
+                                    NFT_EXTHDR_OP_TCPOPT.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Final(
-                                    NFTA_META_DREG,
+                                    NFTA_EXTHDR_SREG,
                                     NFT_REG_1.to_be_bytes().to_vec()
-                                )
+                                ),
                             ]
                         )
                     ]
@@ -402,12 +524,9 @@ fn meta_expr_is_valid() {
 }
 
 #[test]
-fn nat_expr_is_valid() {
-    let nat = Nat::default()
-        .with_nat_type(NatType::SNat)
-        .with_family(ProtocolFamily::Ipv4)
-        .with_ip_register(Register::Reg1);
-    let mut rule = get_test_rule().with_expressions(vec![nat]);
+fn fib_expr_is_valid() {
+    let fib = Fib::new(FibFlags::SADDR | FibFlags::IIF, FibResult::Oif);
+    let mut rule = get_test_rule().with_expressions(vec![fib]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
@@ -423,22 +542,19 @@ fn nat_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"nat".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"fib".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
+                                NetlinkExpr::Final(NFTA_FIB_DREG, NFT_REG_1.to_be_bytes().to_vec()),
                                 NetlinkExpr::Final(
-                                    NFTA_NAT_TYPE,
-                                    NFT_NAT_SNAT.to_be_bytes().to_vec()
+                                    NFTA_FIB_RESULT,
+                                    NFT_FIB_RESULT_OIF.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Final(
-                                    NFTA_NAT_FAMILY,
-                                    (ProtocolFamily::Ipv4 as u32).to_be_bytes().to_vec(),
+                                    NFTA_FIB_FLAGS,
+                                    (NFTA_FIB_F_SADDR | NFTA_FIB_F_IIF).to_be_bytes().to_vec()
                                 ),
-                                NetlinkExpr::Final(
-                                    NFTA_NAT_REG_ADDR_MIN,
-                                    NFT_REG_1.to_be_bytes().to_vec()
-                                )
                             ]
                         )
                     ]
@@ -450,15 +566,16 @@ fn nat_expr_is_valid() {
 }
 
 #[test]
-fn payload_expr_is_valid() {
-    let tcp_header_field = TCPHeaderField::Sport;
-    let transport_header_field = TransportHeaderField::Tcp(tcp_header_field);
-    let payload = HighLevelPayload::Transport(transport_header_field);
-    let mut rule = get_test_rule().with_expressions(vec![payload.build()]);
+fn fwd_expr_is_valid() {
+    let fwd = Fwd::default()
+        .with_sreg_dev(Register::Reg1)
+        .with_sreg_addr(Register::Reg2)
+        .with_nfproto(ProtocolFamily::Ipv6);
+    let mut rule = get_test_rule().with_expressions(vec![fwd]);
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 108);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
 
     assert_eq!(
         raw_expr,
@@ -470,25 +587,21 @@ fn payload_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"payload".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"fwd".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
                                 NetlinkExpr::Final(
-                                    NFTA_PAYLOAD_DREG,
+                                    NFTA_FWD_SREG_DEV,
                                     NFT_REG_1.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Final(
-                                    NFTA_PAYLOAD_BASE,
-                                    NFT_PAYLOAD_TRANSPORT_HEADER.to_be_bytes().to_vec()
-                                ),
-                                NetlinkExpr::Final(
-                                    NFTA_PAYLOAD_OFFSET,
-                                    tcp_header_field.offset().to_be_bytes().to_vec()
+                                    NFTA_FWD_SREG_ADDR,
+                                    NFT_REG_2.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Final(
-                                    NFTA_PAYLOAD_LEN,
-                                    tcp_header_field.len().to_be_bytes().to_vec()
+                                    NFTA_FWD_NFPROTO,
+                                    (libc::NFPROTO_IPV6 as u32).to_be_bytes().to_vec()
                                 ),
                             ]
                         )
@@ -501,15 +614,13 @@ fn payload_expr_is_valid() {
 }
 
 #[test]
-fn reject_expr_is_valid() {
-    let code = IcmpCode::NoRoute;
-    let reject = Reject::default()
-        .with_type(RejectType::IcmpxUnreach)
-        .with_icmp_code(code);
-    let mut rule = get_test_rule().with_expressions(vec![reject]);
+fn hash_expr_is_valid() {
+    let hash = Hash::new_jhash(Register::Reg2, 4, 4).with_seed(0xdeadbeefu32);
+    let mut rule = get_test_rule().with_expressions(vec![hash]);
+
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 92);
+    assert_eq!(nlmsghdr.nlmsg_len, 120);
 
     assert_eq!(
         raw_expr,
@@ -521,17 +632,27 @@ fn reject_expr_is_valid() {
                 vec![NetlinkExpr::Nested(
                     NFTA_LIST_ELEM,
                     vec![
-                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"reject".to_vec()),
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"hash".to_vec()),
                         NetlinkExpr::Nested(
                             NFTA_EXPR_DATA,
                             vec![
                                 NetlinkExpr::Final(
-                                    NFTA_REJECT_TYPE,
-                                    NFT_REJECT_ICMPX_UNREACH.to_be_bytes().to_vec()
+                                    NFTA_HASH_SREG,
+                                    NFT_REG_2.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Final(
-                                    NFTA_REJECT_ICMP_CODE,
-                                    (code as u8).to_be_bytes().to_vec()
+                                    NFTA_HASH_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_HASH_LEN, 4u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_HASH_MODULUS, 4u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_HASH_SEED,
+                                    0xdeadbeefu32.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_HASH_TYPE,
+                                    NFT_HASH_JENKINS.to_be_bytes().to_vec()
                                 ),
                             ]
                         )
@@ -544,13 +665,14 @@ fn reject_expr_is_valid() {
 }
 
 #[test]
-fn verdict_expr_is_valid() {
-    let verdict = Immediate::new_verdict(VerdictKind::Drop);
-    let mut rule = get_test_rule().with_expressions(ExpressionList::default().with_value(verdict));
+fn immediate_expr_is_valid() {
+    let immediate = Immediate::new_data(vec![42u8], Register::Reg1);
+    let mut rule =
+        get_test_rule().with_expressions(ExpressionList::default().with_value(immediate));
 
     let mut buf = Vec::new();
     let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
-    assert_eq!(nlmsghdr.nlmsg_len, 104);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
 
     assert_eq!(
         raw_expr,
@@ -568,18 +690,1242 @@ fn verdict_expr_is_valid() {
                             vec![
                                 NetlinkExpr::Final(
                                     NFTA_IMMEDIATE_DREG,
-                                    NFT_REG_VERDICT.to_be_bytes().to_vec()
+                                    NFT_REG_1.to_be_bytes().to_vec()
                                 ),
                                 NetlinkExpr::Nested(
                                     NFTA_IMMEDIATE_DATA,
-                                    vec![NetlinkExpr::Nested(
-                                        NFTA_DATA_VERDICT,
-                                        vec![NetlinkExpr::Final(
-                                            NFTA_VERDICT_CODE,
-                                            NF_DROP.to_be_bytes().to_vec()
-                                        ),]
-                                    )],
-                                ),
+                                    vec![NetlinkExpr::Final(1u16, 42u8.to_be_bytes().to_vec())]
+                                )
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn inner_expr_is_valid() {
+    let inner = Inner::new(
+        Encapsulation::Vxlan,
+        HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Saddr)),
+    );
+    let mut rule = get_test_rule().with_expressions(vec![inner]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 160);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"inner".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                // NFTA_INNER_NUM
+                                NetlinkExpr::Final(1, 0u32.to_be_bytes().to_vec()),
+                                // NFTA_INNER_TYPE
+                                NetlinkExpr::Final(2, 1u32.to_be_bytes().to_vec()),
+                                // NFTA_INNER_FLAGS
+                                NetlinkExpr::Final(3, 0x0fu32.to_be_bytes().to_vec()),
+                                // NFTA_INNER_HDRSIZE
+                                NetlinkExpr::Final(4, 8u32.to_be_bytes().to_vec()),
+                                // NFTA_INNER_EXPR
+                                NetlinkExpr::Nested(
+                                    5,
+                                    vec![
+                                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"payload".to_vec()),
+                                        NetlinkExpr::Nested(
+                                            NFTA_EXPR_DATA,
+                                            vec![
+                                                NetlinkExpr::Final(
+                                                    NFTA_PAYLOAD_DREG,
+                                                    NFT_REG_1.to_be_bytes().to_vec()
+                                                ),
+                                                NetlinkExpr::Final(
+                                                    NFTA_PAYLOAD_BASE,
+                                                    NFT_PAYLOAD_NETWORK_HEADER
+                                                        .to_be_bytes()
+                                                        .to_vec()
+                                                ),
+                                                NetlinkExpr::Final(
+                                                    NFTA_PAYLOAD_OFFSET,
+                                                    12u32.to_be_bytes().to_vec()
+                                                ),
+                                                NetlinkExpr::Final(
+                                                    NFTA_PAYLOAD_LEN,
+                                                    4u32.to_be_bytes().to_vec()
+                                                ),
+                                            ]
+                                        ),
+                                    ]
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn last_expr_is_valid() {
+    let last = Last::default().with_set(1u32).with_msecs(1500u64);
+    let mut rule = get_test_rule().with_expressions(vec![last]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 92);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"last".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_LAST_SET, 1u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_LAST_MSECS, 1500u64.to_be_bytes().to_vec()),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn limit_expr_is_valid() {
+    let limit = Limit::new(10, LimitUnit::Minute).with_inverted(true);
+    let mut rule = get_test_rule().with_expressions(vec![limit]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 124);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"limit".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_LIMIT_RATE, 10u64.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_LIMIT_UNIT, 60u64.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_LIMIT_BURST, 5u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_LIMIT_TYPE,
+                                    NFT_LIMIT_PKTS.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_LIMIT_FLAGS,
+                                    NFT_LIMIT_F_INV.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn log_expr_is_valid() {
+    let log = Log::new(Some(1337), Some("mockprefix")).expect("Could not build a log expression");
+    let mut rule = get_test_rule().with_expressions(ExpressionList::default().with_value(log));
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"log".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_LOG_GROUP, 1337u16.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_LOG_PREFIX, b"mockprefix".to_vec()),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn nflog_expr_is_valid() {
+    let nflog = Log::new_nflog(2, Some(128), Some(16), None::<&str>).unwrap();
+    let mut rule = get_test_rule().with_expressions(vec![nflog]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"log".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_LOG_GROUP, 2u16.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_LOG_SNAPLEN, 128u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_LOG_QTHRESHOLD,
+                                    16u16.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn lookup_expr_is_valid() {
+    let table = get_test_table();
+    let mut set_builder = SetBuilder::new(SET_NAME, &table).unwrap();
+    let address: Ipv4Addr = [8, 8, 8, 8].into();
+    set_builder.add(&address);
+    let (set, _set_elements) = set_builder.finish();
+    let lookup = Lookup::new(&set).unwrap();
+
+    let mut rule = get_test_rule().with_expressions(ExpressionList::default().with_value(lookup));
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"lookup".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_LOOKUP_SET, b"mockset".to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_LOOKUP_SREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn masquerade_expr_is_valid() {
+    let masquerade = Masquerade::default();
+    let mut rule = get_test_rule().with_expressions(vec![masquerade]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 72);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"masq".to_vec()),
+                        NetlinkExpr::Nested(NFTA_EXPR_DATA, vec![]),
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn meta_expr_is_valid() {
+    let meta = Meta::default()
+        .with_key(MetaType::Protocol)
+        .with_dreg(Register::Reg1);
+    let mut rule = get_test_rule().with_expressions(vec![meta]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 88);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"meta".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_META_KEY,
+                                    NFT_META_PROTOCOL.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_META_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                )
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn meta_set_expr_is_valid() {
+    let meta_set = Meta::set(MetaType::Priority, Register::Reg2);
+    let mut rule = get_test_rule().with_expressions(vec![meta_set]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 88);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"meta".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_META_KEY,
+                                    NFT_META_PRIORITY.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_META_SREG,
+                                    NFT_REG_2.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn nat_expr_is_valid() {
+    let nat = Nat::default()
+        .with_nat_type(NatType::SNat)
+        .with_family(ProtocolFamily::Ipv4)
+        .with_ip_register(Register::Reg1);
+    let mut rule = get_test_rule().with_expressions(vec![nat]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"nat".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_NAT_TYPE,
+                                    NFT_NAT_SNAT.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_NAT_FAMILY,
+                                    (ProtocolFamily::Ipv4 as u32).to_be_bytes().to_vec(),
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_NAT_REG_ADDR_MIN,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                )
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn numgen_expr_is_valid() {
+    let numgen = Numgen::new(NumgenType::Incremental, 3);
+    let mut rule = get_test_rule().with_expressions(vec![numgen]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"numgen".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_NG_DREG, NFT_REG_1.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_NG_MODULUS, 3u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_NG_TYPE,
+                                    NFT_NG_INCREMENTAL.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn objref_expr_is_valid() {
+    let objref = Objref::new(ObjectType::Secmark, "http");
+    let mut rule = get_test_rule().with_expressions(vec![objref]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 92);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"objref".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_OBJREF_IMM_TYPE,
+                                    NFT_OBJECT_SECMARK.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_OBJREF_IMM_NAME, b"http".to_vec()),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn osf_expr_is_valid() {
+    let osf = Osf::new(OsfTtl::Loose, OsfFlags::VERSION);
+    let mut rule = get_test_rule().with_expressions(vec![osf]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"osf".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_OSF_DREG, NFT_REG_1.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_OSF_TTL, [1u8].to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_OSF_FLAGS,
+                                    NFT_OSF_F_VERSION.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn payload_expr_is_valid() {
+    let tcp_header_field = TCPHeaderField::Sport;
+    let transport_header_field = TransportHeaderField::Tcp(tcp_header_field);
+    let payload = HighLevelPayload::Transport(transport_header_field);
+    let mut rule = get_test_rule().with_expressions(vec![payload.build()]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 108);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"payload".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_BASE,
+                                    NFT_PAYLOAD_TRANSPORT_HEADER.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_OFFSET,
+                                    tcp_header_field.offset().to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_LEN,
+                                    tcp_header_field.len().to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn payload_set_expr_is_valid() {
+    let payload_set = HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Dport))
+        .set(Register::Reg1)
+        .unwrap();
+    let mut rule = get_test_rule().with_expressions(vec![payload_set]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 124);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"payload".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_BASE,
+                                    NFT_PAYLOAD_TRANSPORT_HEADER.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_OFFSET,
+                                    2u32.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_PAYLOAD_LEN, 2u32.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_SREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_CSUM_TYPE,
+                                    NFT_PAYLOAD_CSUM_INET.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_PAYLOAD_CSUM_OFFSET,
+                                    16u32.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn queue_expr_is_valid() {
+    let queue = Queue::new_range(3, 5, QueueFlags::FANOUT | QueueFlags::BYPASS).unwrap();
+    let mut rule = get_test_rule().with_expressions(vec![queue]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"queue".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_QUEUE_NUM, 3u16.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(NFTA_QUEUE_TOTAL, 3u16.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_QUEUE_FLAGS,
+                                    ((NFT_QUEUE_FLAG_BYPASS | NFT_QUEUE_FLAG_CPU_FANOUT) as u16)
+                                        .to_be_bytes()
+                                        .to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn queue_range_is_validated() {
+    assert!(matches!(
+        Queue::new_range(0, u16::MAX, QueueFlags::empty()),
+        Err(BuilderError::InvalidQueueRange)
+    ));
+    assert!(matches!(
+        Queue::new_range(5, 3, QueueFlags::empty()),
+        Err(BuilderError::InvalidQueueRange)
+    ));
+    assert_eq!(
+        Queue::new_range(1, u16::MAX, QueueFlags::empty())
+            .unwrap()
+            .get_total(),
+        Some(&u16::MAX)
+    );
+}
+
+#[test]
+fn quota_expr_is_valid() {
+    let quota = Quota::new_over(1 << 30);
+    let mut rule = get_test_rule().with_expressions(vec![quota]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"quota".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_QUOTA_BYTES,
+                                    (1u64 << 30).to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_QUOTA_FLAGS,
+                                    NFT_QUOTA_F_INV.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn rate_limit_exprs_round_trip() {
+    let rule = get_test_rule()
+        .limit(100, LimitUnit::Second)
+        .with_expr(Limit::new_bytes(1 << 20, LimitUnit::Hour, 4096))
+        .with_expr(Quota::new(1 << 30))
+        .with_expr(Connlimit::new(10, false))
+        .with_expr(Last::default().with_set(0u32));
+
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    rule.add_or_remove(&mut writer, MsgType::Add, 0)
+        .expect("Couldn't write the rule");
+    let (deserialized, _) = Rule::deserialize(&buf).expect("Couldn't deserialize the rule");
+    assert_eq!(deserialized.get_expressions(), rule.get_expressions());
+}
+
+#[test]
+fn range_expr_is_valid() {
+    let range = Range::new(RangeOp::Eq, 1000u16.to_be_bytes(), 2000u16.to_be_bytes()).unwrap();
+    let mut rule = get_test_rule().with_expressions(vec![range]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 116);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"range".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_RANGE_SREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_RANGE_OP,
+                                    NFT_RANGE_EQ.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Nested(
+                                    NFTA_RANGE_FROM_DATA,
+                                    vec![NetlinkExpr::Final(
+                                        NFTA_DATA_VALUE,
+                                        1000u16.to_be_bytes().to_vec()
+                                    )]
+                                ),
+                                NetlinkExpr::Nested(
+                                    NFTA_RANGE_TO_DATA,
+                                    vec![NetlinkExpr::Final(
+                                        NFTA_DATA_VALUE,
+                                        2000u16.to_be_bytes().to_vec()
+                                    )]
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn redir_expr_is_valid() {
+    let redir = Redir::default()
+        .with_reg_proto_min(Register::Reg1)
+        .with_reg_proto_max(Register::Reg2)
+        .with_flags(NatFlags::RANDOM.bits());
+    let mut rule = get_test_rule().with_expressions(vec![redir]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"redir".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_REDIR_REG_PROTO_MIN,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_REDIR_REG_PROTO_MAX,
+                                    NFT_REG_2.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_REDIR_FLAGS,
+                                    NatFlags::RANDOM.bits().to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn reject_expr_is_valid() {
+    let code = IcmpCode::NoRoute;
+    let reject = Reject::default()
+        .with_type(RejectType::IcmpxUnreach)
+        .with_icmp_code(code);
+    assert_eq!(reject.get_icmpx_code(), Some(code));
+    assert_eq!(
+        reject
+            .clone()
+            .with_type(RejectType::IcmpUnreach)
+            .get_icmpx_code(),
+        None
+    );
+    let mut rule = get_test_rule().with_expressions(vec![reject]);
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 92);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"reject".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_REJECT_TYPE,
+                                    NFT_REJECT_ICMPX_UNREACH.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_REJECT_ICMP_CODE,
+                                    (code as u8).to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn rt_expr_is_valid() {
+    let rt = Rt::new(RtKey::TcpMss);
+    let mut rule = get_test_rule().with_expressions(vec![rt]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 88);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"rt".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(NFTA_RT_DREG, NFT_REG_1.to_be_bytes().to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_RT_KEY,
+                                    NFT_RT_TCPMSS.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn socket_expr_is_valid() {
+    let socket = Socket::new_cgroupv2(2);
+    let mut rule = get_test_rule().with_expressions(vec![socket]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"socket".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_SOCKET_KEY,
+                                    NFT_SOCKET_CGROUPV2.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_SOCKET_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_SOCKET_LEVEL, 2u32.to_be_bytes().to_vec()),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn synproxy_expr_is_valid() {
+    let synproxy = Synproxy::new(1460, 7, SynproxyFlags::TIMESTAMP | SynproxyFlags::SACK_PERM);
+    let mut rule = get_test_rule().with_expressions(vec![synproxy]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"synproxy".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_SYNPROXY_MSS,
+                                    1460u16.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_SYNPROXY_WSCALE, [7u8].to_vec()),
+                                NetlinkExpr::Final(
+                                    NFTA_SYNPROXY_FLAGS,
+                                    0x0fu32.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn tproxy_expr_is_valid() {
+    let tproxy = Tproxy::new(ProtocolFamily::Ipv4)
+        .with_reg_addr(Register::Reg1)
+        .with_reg_port(Register::Reg2);
+    let mut rule = get_test_rule().with_expressions(vec![tproxy]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"tproxy".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_TPROXY_FAMILY,
+                                    (libc::NFPROTO_IPV4 as u32).to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_TPROXY_REG_ADDR,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_TPROXY_REG_PORT,
+                                    NFT_REG_2.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn tunnel_expr_is_valid() {
+    let tunnel = Tunnel::new(TunnelKey::Id, TunnelMode::Rx);
+    let mut rule = get_test_rule().with_expressions(vec![tunnel]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 100);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"tunnel".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_TUNNEL_KEY,
+                                    NFT_TUNNEL_ID.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_TUNNEL_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_TUNNEL_MODE,
+                                    NFT_TUNNEL_MODE_RX.to_be_bytes().to_vec()
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn verdict_expr_is_valid() {
+    let verdict = Immediate::new_verdict(VerdictKind::Drop);
+    let mut rule = get_test_rule().with_expressions(ExpressionList::default().with_value(verdict));
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 104);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"immediate".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_IMMEDIATE_DREG,
+                                    NFT_REG_VERDICT.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Nested(
+                                    NFTA_IMMEDIATE_DATA,
+                                    vec![NetlinkExpr::Nested(
+                                        NFTA_DATA_VERDICT,
+                                        vec![NetlinkExpr::Final(
+                                            NFTA_VERDICT_CODE,
+                                            NF_DROP.to_be_bytes().to_vec()
+                                        ),]
+                                    )],
+                                ),
+                            ]
+                        )
+                    ]
+                )]
+            )
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn xfrm_expr_is_valid() {
+    let xfrm = Xfrm::new(XfrmKey::Reqid, XfrmDirection::In).with_spnum(1u32);
+    let mut rule = get_test_rule().with_expressions(vec![xfrm]);
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut rule);
+    assert_eq!(nlmsghdr.nlmsg_len, 104);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_RULE_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_RULE_CHAIN, CHAIN_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_RULE_EXPRESSIONS,
+                vec![NetlinkExpr::Nested(
+                    NFTA_LIST_ELEM,
+                    vec![
+                        NetlinkExpr::Final(NFTA_EXPR_NAME, b"xfrm".to_vec()),
+                        NetlinkExpr::Nested(
+                            NFTA_EXPR_DATA,
+                            vec![
+                                NetlinkExpr::Final(
+                                    NFTA_XFRM_DREG,
+                                    NFT_REG_1.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(
+                                    NFTA_XFRM_KEY,
+                                    NFT_XFRM_KEY_REQID.to_be_bytes().to_vec()
+                                ),
+                                NetlinkExpr::Final(NFTA_XFRM_DIR, [0u8].to_vec()),
+                                NetlinkExpr::Final(NFTA_XFRM_SPNUM, 1u32.to_be_bytes().to_vec()),
                             ]
                         )
                     ]
@@ -589,3 +1935,723 @@ fn verdict_expr_is_valid() {
         .to_raw()
     );
 }
+
+#[test]
+fn rpfilter_rule_is_valid() {
+    let rule = get_test_rule().rpfilter();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs,
+        vec![
+            ExpressionVariant::Fib(Fib::new(
+                FibFlags::SADDR | FibFlags::IIF | FibFlags::PRESENT,
+                FibResult::Oif
+            )),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, [0u8])),
+            ExpressionVariant::Immediate(Immediate::new_verdict(VerdictKind::Drop)),
+        ]
+    );
+}
+
+#[test]
+fn clamp_mss_rule_is_valid() {
+    let rule = get_test_rule().clamp_mss_to_pmtu().unwrap();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        &exprs[2..],
+        &[
+            ExpressionVariant::Payload(
+                HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Flags))
+                    .build()
+            ),
+            ExpressionVariant::Bitwise(Bitwise::new([0x02u8], [0u8]).unwrap()),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Neq, [0u8])),
+            ExpressionVariant::Rt(Rt::new(RtKey::TcpMss)),
+            ExpressionVariant::Byteorder(Byteorder::new(ByteorderOp::Hton, Register::Reg1, 2, 2)),
+            ExpressionVariant::Exthdr(
+                ExthdrField::Tcp(TcpOptionField::MaxSegSize).build_write(Register::Reg1)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn synproxy_rules_are_valid() {
+    let notrack = rule_exprs(&get_test_rule().notrack_syn(80).unwrap());
+    assert_eq!(notrack.len(), 8);
+    assert_eq!(
+        notrack[3],
+        ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, 80u16.to_be_bytes()))
+    );
+    assert_eq!(
+        &notrack[5..],
+        &[
+            ExpressionVariant::Bitwise(Bitwise::new([0x02u8], [0u8]).unwrap()),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Neq, [0u8])),
+            ExpressionVariant::Notrack(Notrack::default()),
+        ]
+    );
+
+    let synproxy = Synproxy::new(1460, 7, SynproxyFlags::TIMESTAMP | SynproxyFlags::SACK_PERM);
+    let filter = rule_exprs(&get_test_rule().synproxy(80, synproxy.clone()).unwrap());
+    assert_eq!(
+        &filter[4..],
+        &[
+            ExpressionVariant::Conntrack(Conntrack::new(ConntrackKey::State)),
+            ExpressionVariant::Bitwise(
+                Bitwise::new(
+                    (ConnTrackState::INVALID | ConnTrackState::UNTRACKED)
+                        .bits()
+                        .to_ne_bytes(),
+                    0u32.to_be_bytes()
+                )
+                .unwrap()
+            ),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Neq, 0u32.to_be_bytes())),
+            ExpressionVariant::Synproxy(synproxy),
+        ]
+    );
+}
+
+#[test]
+fn inner_rule_round_trips() {
+    let field = HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Saddr));
+    let rule = get_test_rule()
+        .match_inner(Encapsulation::Geneve, field, [10, 0, 0, 1])
+        .unwrap();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs[3],
+        ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, 6081u16.to_be_bytes()))
+    );
+    assert_eq!(
+        &exprs[4..],
+        &[
+            ExpressionVariant::Inner(Inner::new_meta(
+                Encapsulation::Geneve,
+                Meta::new(MetaType::Protocol)
+            )),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, (libc::ETH_P_IP as u16).to_be_bytes())),
+            ExpressionVariant::Inner(Inner::new(Encapsulation::Geneve, field)),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, [10, 0, 0, 1])),
+        ]
+    );
+
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    rule.add_or_remove(&mut writer, MsgType::Add, 0)
+        .expect("Couldn't write the rule");
+    let (deserialized, _) = Rule::deserialize(&buf).expect("Couldn't deserialize the rule");
+    assert_eq!(deserialized.get_expressions(), rule.get_expressions());
+
+    // the transport fields depend on the layer 4 protocol of the inner packet
+    let field = HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Dport));
+    let rule = get_test_rule()
+        .match_inner(Encapsulation::Gre, field, 22u16.to_be_bytes())
+        .unwrap();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        &exprs[2..4],
+        &[
+            ExpressionVariant::Inner(Inner::new_meta(
+                Encapsulation::Gre,
+                Meta::new(MetaType::L4Proto)
+            )),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, [libc::IPPROTO_TCP as u8])),
+        ]
+    );
+
+    assert!(matches!(
+        get_test_rule().match_inner(Encapsulation::Vxlan, field, [0, 0, 22]),
+        Err(BuilderError::IncompatibleLength)
+    ));
+}
+
+#[test]
+fn secmark_rules_are_valid() {
+    let rule = get_test_rule()
+        .set_secmark("http")
+        .ct_save_secmark()
+        .ct_restore_secmark();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs,
+        vec![
+            ExpressionVariant::Objref(Objref::new(ObjectType::Secmark, "http")),
+            ExpressionVariant::Meta(Meta::new(MetaType::Secmark)),
+            ExpressionVariant::Conntrack(Conntrack::new_set(ConntrackKey::Secmark, Register::Reg1)),
+            ExpressionVariant::Conntrack(Conntrack::new(ConntrackKey::Secmark)),
+            ExpressionVariant::Meta(Meta::set(MetaType::Secmark, Register::Reg1)),
+        ]
+    );
+}
+
+#[test]
+fn dnat_jhash_rule_is_valid() {
+    let backends = [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)];
+    let (rule, map, elements) = get_test_rule().dnat_jhash("backends", &backends).unwrap();
+    assert_eq!(map.get_name().map(String::as_str), Some("backends"));
+    assert_eq!(map.get_table().map(String::as_str), Some(TABLE_NAME));
+    assert_eq!(elements.elements.unwrap().iter().count(), 2);
+
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        &exprs[3..],
+        &[
+            ExpressionVariant::Hash(Hash::new_jhash(Register::Reg1, 4, 2)),
+            ExpressionVariant::Lookup(Lookup::new(&map).unwrap().with_dreg(Register::Reg1)),
+            ExpressionVariant::Nat(
+                Nat::default()
+                    .with_nat_type(NatType::DNat)
+                    .with_family(ProtocolFamily::Ipv4)
+                    .with_ip_register(Register::Reg1)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn dnat_jhash_requires_backends() {
+    assert!(matches!(
+        get_test_rule().dnat_jhash("backends", &[]),
+        Err(BuilderError::InvalidBackendCount)
+    ));
+}
+
+#[test]
+fn tproxy_rules_are_valid() {
+    let set_mark = [
+        ExpressionVariant::Immediate(Immediate::new_data(
+            0x1u32.to_ne_bytes().to_vec(),
+            Register::Reg1,
+        )),
+        ExpressionVariant::Meta(
+            Meta::default()
+                .with_key(MetaType::Mark)
+                .with_sreg(Register::Reg1),
+        ),
+        ExpressionVariant::Immediate(Immediate::new_verdict(VerdictKind::Accept)),
+    ];
+
+    let socket_exprs = rule_exprs(&get_test_rule().transparent_socket(0x1));
+    assert_eq!(
+        socket_exprs[..2],
+        [
+            ExpressionVariant::Socket(Socket::new(SocketKey::Transparent)),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, [1u8])),
+        ]
+    );
+    assert_eq!(socket_exprs[2..], set_mark);
+
+    let tproxy_exprs = rule_exprs(&get_test_rule().tproxy_to(8080, Protocol::TCP, 0x1));
+    assert_eq!(
+        tproxy_exprs[2..4],
+        [
+            ExpressionVariant::Immediate(Immediate::new_data(
+                8080u16.to_be_bytes().to_vec(),
+                Register::Reg1
+            )),
+            ExpressionVariant::Tproxy(
+                Tproxy::new(ProtocolFamily::Unspec).with_reg_port(Register::Reg1)
+            ),
+        ]
+    );
+    assert_eq!(tproxy_exprs[4..], set_mark);
+}
+
+#[test]
+fn dup_rule_is_valid() {
+    let addr = Ipv4Addr::new(10, 0, 0, 1);
+    let lo = iface_index("lo").unwrap();
+    let rule = get_test_rule().dup_to(addr.into(), Some("lo")).unwrap();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs,
+        vec![
+            ExpressionVariant::Immediate(Immediate::new_data(
+                addr.octets().to_vec(),
+                Register::Reg1
+            )),
+            ExpressionVariant::Immediate(Immediate::new_data(
+                lo.to_ne_bytes().to_vec(),
+                Register::Reg2
+            )),
+            ExpressionVariant::Dup(
+                Dup::default()
+                    .with_sreg_addr(Register::Reg1)
+                    .with_sreg_dev(Register::Reg2)
+            ),
+        ]
+    );
+
+    assert!(matches!(
+        get_test_rule().fwd_to("does-not-exist"),
+        Err(BuilderError::InterfaceNotFound(_))
+    ));
+}
+
+#[test]
+fn ct_rules_are_valid() {
+    let states = ConnTrackState::ESTABLISHED | ConnTrackState::RELATED;
+    assert_eq!(
+        rule_exprs(&get_test_rule().ct_state(states).unwrap()),
+        vec![
+            ExpressionVariant::Conntrack(Conntrack::new(ConntrackKey::State)),
+            ExpressionVariant::Bitwise(
+                Bitwise::new(states.bits().to_ne_bytes(), 0u32.to_be_bytes()).unwrap()
+            ),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Neq, 0u32.to_be_bytes())),
+        ]
+    );
+
+    let mut label = [0u8; 16];
+    label[4..8].copy_from_slice(&(1u32 << 2).to_ne_bytes());
+    assert_eq!(
+        rule_exprs(&get_test_rule().ct_set_label(34).unwrap()),
+        vec![
+            ExpressionVariant::Immediate(Immediate::new_data(label.to_vec(), Register::Reg1)),
+            ExpressionVariant::Conntrack(Conntrack::new_set(ConntrackKey::Labels, Register::Reg1)),
+        ]
+    );
+    assert!(matches!(
+        get_test_rule().ct_set_label(128),
+        Err(BuilderError::InvalidCtLabel(128))
+    ));
+}
+
+#[test]
+fn meta_set_rules_are_valid() {
+    let rule = get_test_rule().set_priority(1, 2).nftrace();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs,
+        vec![
+            ExpressionVariant::Immediate(Immediate::new_data(
+                0x0001_0002u32.to_ne_bytes().to_vec(),
+                Register::Reg1
+            )),
+            ExpressionVariant::Meta(Meta::set(MetaType::Priority, Register::Reg1)),
+            ExpressionVariant::Immediate(Immediate::new_data(vec![1u8], Register::Reg1)),
+            ExpressionVariant::Meta(Meta::set(MetaType::NfTrace, Register::Reg1)),
+        ]
+    );
+}
+
+#[test]
+fn nat_rules_are_valid() {
+    let min = Ipv4Addr::new(10, 0, 0, 0);
+    let max = Ipv4Addr::new(10, 0, 0, 255);
+    let net = IpNetwork::new(min.into(), 24).unwrap();
+    let snat_exprs = rule_exprs(
+        &get_test_rule()
+            .snat_to(net, Some(1024..=2048), NatFlags::empty())
+            .unwrap(),
+    );
+    assert_eq!(
+        snat_exprs,
+        vec![
+            ExpressionVariant::Immediate(Immediate::new_data(
+                min.octets().to_vec(),
+                Register::Reg1
+            )),
+            ExpressionVariant::Immediate(Immediate::new_data(
+                max.octets().to_vec(),
+                Register::Reg2
+            )),
+            ExpressionVariant::Immediate(Immediate::new_data(
+                1024u16.to_be_bytes().to_vec(),
+                Register::Reg3
+            )),
+            ExpressionVariant::Immediate(Immediate::new_data(
+                2048u16.to_be_bytes().to_vec(),
+                Register::Reg4
+            )),
+            ExpressionVariant::Nat(
+                Nat::default()
+                    .with_nat_type(NatType::SNat)
+                    .with_family(ProtocolFamily::Ipv4)
+                    .with_ip_register(Register::Reg1)
+                    .with_ip_register_max(Register::Reg2)
+                    .with_port_register(Register::Reg3)
+                    .with_port_register_max(Register::Reg4)
+            ),
+        ]
+    );
+
+    let addr = Ipv6Addr::LOCALHOST;
+    let dnat_exprs = rule_exprs(
+        &get_test_rule()
+            .dnat_to(
+                IpAddr::from(addr),
+                Some(8080..=8080),
+                NatFlags::RANDOM | NatFlags::PERSISTENT,
+            )
+            .unwrap(),
+    );
+    assert_eq!(
+        dnat_exprs,
+        vec![
+            ExpressionVariant::Immediate(Immediate::new_data(
+                addr.octets().to_vec(),
+                Register::Reg1
+            )),
+            ExpressionVariant::Immediate(Immediate::new_data(
+                8080u16.to_be_bytes().to_vec(),
+                Register::Reg3
+            )),
+            ExpressionVariant::Nat(
+                Nat::default()
+                    .with_nat_type(NatType::DNat)
+                    .with_family(ProtocolFamily::Ipv6)
+                    .with_ip_register(Register::Reg1)
+                    .with_port_register(Register::Reg3)
+                    .with_flags((NatFlags::RANDOM | NatFlags::PERSISTENT).bits())
+            ),
+        ]
+    );
+
+    let mixed = IpAddr::from(min)..=IpAddr::from(addr);
+    assert!(matches!(
+        get_test_rule().snat_to(mixed, None, NatFlags::empty()),
+        Err(BuilderError::IncompatibleAddressFamilies)
+    ));
+    let reversed = IpAddr::from(max)..=IpAddr::from(min);
+    assert!(matches!(
+        get_test_rule().snat_to(reversed, None, NatFlags::empty()),
+        Err(BuilderError::InvalidRange)
+    ));
+
+    let redirect_exprs = rule_exprs(&get_test_rule().redirect_to(3128));
+    assert_eq!(
+        redirect_exprs,
+        vec![
+            ExpressionVariant::Immediate(Immediate::new_data(
+                3128u16.to_be_bytes().to_vec(),
+                Register::Reg1
+            )),
+            ExpressionVariant::Redir(Redir::default().with_reg_proto_min(Register::Reg1)),
+        ]
+    );
+}
+
+#[test]
+fn payload_set_rules_are_valid() {
+    let saddr = HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Saddr))
+        .set(Register::Reg1)
+        .unwrap();
+    assert_eq!(saddr.get_csum_type(), Some(&PayloadCsumType::Inet));
+    assert_eq!(saddr.get_csum_offset(), Some(&10));
+    assert_eq!(
+        saddr.get_csum_flags(),
+        Some(&PayloadCsumFlags::L4_PSEUDOHDR.bits())
+    );
+
+    // the checksums are updated by 16-bit words
+    assert!(matches!(
+        HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Protocol))
+            .set(Register::Reg1),
+        Err(BuilderError::UnalignedChecksumUpdate)
+    ));
+    assert!(matches!(
+        HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Flags))
+            .set(Register::Reg1),
+        Err(BuilderError::UnalignedChecksumUpdate)
+    ));
+    // without a checksum, the offset does not matter
+    assert!(
+        HighLevelPayload::Network(NetworkHeaderField::IPv6(IPv6HeaderField::HopLimit))
+            .set(Register::Reg1)
+            .is_ok()
+    );
+
+    let ttl_exprs = rule_exprs(&get_test_rule().set_ttl(64));
+    assert_eq!(
+        ttl_exprs[2..],
+        [
+            ExpressionVariant::Immediate(Immediate::new_data(vec![64u8], Register::Reg1)),
+            ExpressionVariant::Payload(
+                Payload::default()
+                    .with_base(NFT_PAYLOAD_NETWORK_HEADER)
+                    .with_offset(8u32)
+                    .with_len(1u32)
+                    .with_sreg(Register::Reg1)
+                    .with_csum_type(PayloadCsumType::Inet)
+                    .with_csum_offset(10u32)
+            ),
+        ]
+    );
+
+    let dscp_exprs = rule_exprs(&get_test_rule().set_dscp(8).unwrap());
+    assert_eq!(
+        dscp_exprs[3..],
+        [
+            ExpressionVariant::Bitwise(Bitwise::new([0xffu8, 0x03], [0u8, 0x20]).unwrap()),
+            ExpressionVariant::Payload(
+                Payload::default()
+                    .with_base(NFT_PAYLOAD_NETWORK_HEADER)
+                    .with_offset(0u32)
+                    .with_len(2u32)
+                    .with_sreg(Register::Reg1)
+                    .with_csum_type(PayloadCsumType::Inet)
+                    .with_csum_offset(10u32)
+            ),
+        ]
+    );
+
+    // the high bits of the value would be silently dropped
+    assert!(matches!(
+        get_test_rule().set_dscp(0x48),
+        Err(BuilderError::InvalidDscp(0x48))
+    ));
+}
+
+#[test]
+fn port_range_rule_is_valid() {
+    let rule = get_test_rule()
+        .dport_range(1000..=2000, Protocol::TCP)
+        .unwrap();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs[2..],
+        [
+            ExpressionVariant::Payload(
+                HighLevelPayload::Transport(TransportHeaderField::Tcp(TCPHeaderField::Dport))
+                    .build()
+            ),
+            ExpressionVariant::Range(
+                Range::new(RangeOp::Eq, 1000u16.to_be_bytes(), 2000u16.to_be_bytes()).unwrap()
+            ),
+        ]
+    );
+    assert!(matches!(
+        Range::new(RangeOp::Eq, [0u8], [0u8, 0]),
+        Err(BuilderError::IncompatibleLength)
+    ));
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 2000..=1000;
+    assert!(matches!(
+        get_test_rule().sport_range(reversed, Protocol::UDP),
+        Err(BuilderError::InvalidRange)
+    ));
+}
+
+#[test]
+fn header_fields_round_trip() {
+    fn check<F: HeaderField + Copy + PartialEq + std::fmt::Debug>(
+        fields: &[F],
+        decode: impl Fn(u32, u32, Option<&[u8]>) -> Result<F, DecodeError>,
+    ) {
+        for field in fields {
+            let decoded = decode(field.offset(), field.len(), field.mask()).unwrap();
+            assert_eq!(decoded, *field);
+        }
+    }
+
+    check(
+        &[
+            LLHeaderField::Daddr,
+            LLHeaderField::EtherType,
+            LLHeaderField::Vlan(VlanHeaderField::Pcp),
+            LLHeaderField::Vlan(VlanHeaderField::Dei),
+            LLHeaderField::Vlan(VlanHeaderField::Id),
+            LLHeaderField::Vlan(VlanHeaderField::Type),
+        ],
+        LLHeaderField::from_raw_data_with_mask,
+    );
+    check(
+        &[
+            IPv4HeaderField::Version,
+            IPv4HeaderField::HdrLength,
+            IPv4HeaderField::Dscp,
+            IPv4HeaderField::Ecn,
+            IPv4HeaderField::Length,
+            IPv4HeaderField::FragOff,
+            IPv4HeaderField::Protocol,
+            IPv4HeaderField::Daddr,
+        ],
+        IPv4HeaderField::from_raw_data_with_mask,
+    );
+    check(
+        &[
+            IPv6HeaderField::Version,
+            IPv6HeaderField::Dscp,
+            IPv6HeaderField::Ecn,
+            IPv6HeaderField::FlowLabel,
+            IPv6HeaderField::Length,
+            IPv6HeaderField::Saddr,
+        ],
+        IPv6HeaderField::from_raw_data_with_mask,
+    );
+    check(
+        &[ArpHeaderField::Operation, ArpHeaderField::DaddrIp],
+        |offset, len, _| ArpHeaderField::from_raw_data(offset, len),
+    );
+    check(
+        &[
+            TCPHeaderField::Sequence,
+            TCPHeaderField::DataOffset,
+            TCPHeaderField::Flags,
+            TCPHeaderField::UrgPtr,
+        ],
+        TCPHeaderField::from_raw_data_with_mask,
+    );
+    check(
+        &[ICMPHeaderField::Type, ICMPHeaderField::Sequence],
+        |offset, len, _| ICMPHeaderField::from_raw_data(offset, len),
+    );
+    check(
+        &[ICMPv6HeaderField::Code, ICMPv6HeaderField::Id],
+        |offset, len, _| ICMPv6HeaderField::from_raw_data(offset, len),
+    );
+    check(&[SctpHeaderField::Vtag], |offset, len, _| {
+        SctpHeaderField::from_raw_data(offset, len)
+    });
+    check(
+        &[DccpHeaderField::Dport, DccpHeaderField::Type],
+        DccpHeaderField::from_raw_data_with_mask,
+    );
+    check(&[AhHeaderField::Spi], |offset, len, _| {
+        AhHeaderField::from_raw_data(offset, len)
+    });
+    check(&[EspHeaderField::Sequence], |offset, len, _| {
+        EspHeaderField::from_raw_data(offset, len)
+    });
+    check(
+        &[GreHeaderField::Flags, GreHeaderField::Version],
+        GreHeaderField::from_raw_data_with_mask,
+    );
+
+    // the fields sharing the same bytes can't be told apart without their mask
+    assert!(matches!(
+        IPv4HeaderField::from_raw_data(1, 1),
+        Err(DecodeError::UnknownIPv4HeaderField(1, 1))
+    ));
+    assert_eq!(
+        IPv4HeaderField::from_raw_data(8, 1).unwrap(),
+        IPv4HeaderField::Ttl
+    );
+
+    let pcp = LLHeaderField::Vlan(VlanHeaderField::Pcp);
+    let payload = HighLevelPayload::LinkLayer(pcp).build();
+    assert!(PayloadType::parse_from_payload(&payload).is_err());
+    assert_eq!(
+        PayloadType::parse_from_payload_with_mask(&payload, pcp.mask()).unwrap(),
+        PayloadType::LinkLayer(pcp)
+    );
+}
+
+#[test]
+fn reject_rules_are_valid() {
+    let last_expr = |rule: &Rule| rule_exprs(rule).pop().unwrap();
+    let ip_table = Table::new(ProtocolFamily::Ipv4).with_name(TABLE_NAME);
+    let ip_rule = Rule::new(&Chain::new(&ip_table).with_name(CHAIN_NAME)).unwrap();
+
+    // icmpx is only understood in inet, bridge and netdev tables
+    let rule = ip_rule
+        .clone()
+        .reject(RejectKind::Icmpx(IcmpCode::AdminProhibited))
+        .unwrap();
+    assert_eq!(
+        last_expr(&rule),
+        ExpressionVariant::Reject(
+            Reject::default()
+                .with_type(RejectType::IcmpUnreach)
+                .with_icmp_code(Icmpv4Code::AdminProhibited)
+        )
+    );
+    let rule = get_test_rule()
+        .reject(RejectKind::Icmpx(IcmpCode::PortUnreach))
+        .unwrap();
+    assert_eq!(
+        last_expr(&rule),
+        ExpressionVariant::Reject(
+            Reject::default()
+                .with_type(RejectType::IcmpxUnreach)
+                .with_icmp_code(IcmpCode::PortUnreach)
+        )
+    );
+
+    // ICMPv6 errors in an inet table apply to the IPv6 packets only
+    let rule = get_test_rule()
+        .reject(RejectKind::Icmpv6(Icmpv6Code::AddrUnreach))
+        .unwrap();
+    let exprs = rule_exprs(&rule);
+    assert_eq!(
+        exprs,
+        vec![
+            ExpressionVariant::Meta(Meta::new(MetaType::NfProto)),
+            ExpressionVariant::Cmp(Cmp::new(CmpOp::Eq, [libc::NFPROTO_IPV6 as u8])),
+            ExpressionVariant::Reject(
+                Reject::default()
+                    .with_type(RejectType::IcmpUnreach)
+                    .with_icmp_code(Icmpv6Code::AddrUnreach)
+            ),
+        ]
+    );
+    assert!(matches!(
+        ip_rule
+            .clone()
+            .reject(RejectKind::Icmpv6(Icmpv6Code::NoRoute)),
+        Err(BuilderError::UnsupportedReject(_))
+    ));
+
+    // TCP resets need a TCP match
+    assert!(matches!(
+        get_test_rule().reject(RejectKind::TcpReset),
+        Err(BuilderError::UnsupportedReject(RejectKind::TcpReset))
+    ));
+    let rule = ip_rule
+        .clone()
+        .dport(22, Protocol::TCP)
+        .reject(RejectKind::TcpReset)
+        .unwrap();
+    assert_eq!(
+        last_expr(&rule),
+        ExpressionVariant::Reject(Reject::default().with_type(RejectType::TcpRst))
+    );
+    // `ip protocol tcp` is a TCP match as well
+    let rule = ip_rule
+        .with_expr(
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Protocol)).build(),
+        )
+        .with_expr(Cmp::new(CmpOp::Eq, [libc::IPPROTO_TCP as u8]))
+        .reject(RejectKind::TcpReset)
+        .unwrap();
+    assert_eq!(
+        last_expr(&rule),
+        ExpressionVariant::Reject(Reject::default().with_type(RejectType::TcpRst))
+    );
+}
+
+#[test]
+fn log_options_are_validated() {
+    let syslog = Log::new_syslog(
+        LogLevel::Warning,
+        LogFlags::TCP_SEQ | LogFlags::UID,
+        Some("dropped: "),
+    )
+    .unwrap();
+    assert_eq!(syslog.get_level(), Some(&LogLevel::Warning));
+    assert_eq!(syslog.get_flags(), Some(&0x09));
+    assert!(syslog.validate().is_ok());
+
+    assert!(matches!(
+        syslog.clone().with_group(2u16).validate(),
+        Err(BuilderError::IncompatibleLogOptions)
+    ));
+    assert!(matches!(
+        syslog.clone().with_snaplen(128u32).validate(),
+        Err(BuilderError::IncompatibleLogOptions)
+    ));
+    assert!(Log::new_nflog(2, Some(128), None, None::<&str>)
+        .unwrap()
+        .validate()
+        .is_ok());
+    // the options are checked when the rule is added to a batch
+    let mut rule = get_test_rule();
+    rule.add_expr(syslog.with_group(2u16));
+    assert!(matches!(
+        Batch::new().add(&rule, MsgType::Add),
+        Err(BuilderError::IncompatibleLogOptions)
+    ));
+}
@@ -1,4 +1,5 @@
 use crate::data_type::DataType;
+use crate::expr::ExpressionVariant;
 use crate::nlmsg::{NfNetlinkObject, NfNetlinkWriter};
 use crate::parser::{parse_nlmsg, NlMsg};
 use crate::set::{Set, SetBuilder};
@@ -7,9 +8,15 @@ use crate::{sys::*, Chain, MsgType, ProtocolFamily, Rule, Table};
 mod batch;
 mod chain;
 mod expr;
+mod nflog;
+mod nfqueue;
+mod object;
 mod rule;
+mod rule_builder;
+mod rule_decode;
 mod set;
 mod table;
+mod trace;
 
 pub const TABLE_NAME: &'static str = "mocktable";
 pub const CHAIN_NAME: &'static str = "mockchain";
@@ -154,6 +161,14 @@ pub fn get_test_rule() -> Rule {
     Rule::new(&get_test_chain()).unwrap()
 }
 
+pub fn rule_exprs(rule: &Rule) -> Vec<ExpressionVariant> {
+    rule.get_expressions()
+        .unwrap()
+        .iter()
+        .map(|expr| expr.get_data().unwrap().clone())
+        .collect()
+}
+
 pub fn get_test_set<K: DataType>() -> Set {
     SetBuilder::<K>::new(SET_NAME, &get_test_table())
         .expect("Couldn't create a set")
@@ -168,7 +183,8 @@ pub fn get_test_nlmsg_with_msg_type<'a>(
     msg_type: MsgType,
 ) -> (nlmsghdr, nfgenmsg, &'a [u8]) {
     let mut writer = NfNetlinkWriter::new(buf);
-    obj.add_or_remove(&mut writer, msg_type, 0);
+    obj.add_or_remove(&mut writer, msg_type, 0)
+        .expect("Couldn't write the object");
 
     let (hdr, msg) = parse_nlmsg(buf.as_slice()).expect("Couldn't parse the message");
 
@@ -0,0 +1,178 @@
+use crate::{
+    nflog::{
+        CopyMode, CopyParams, CtInfo, HardwareAddress, NflogConfig, NflogConfigFlags, NflogPacket,
+        NflogPacketHeader, PacketTimestamp, PacketVlan,
+    },
+    nlmsg::{get_operation_from_nlmsghdr_type, NfNetlinkAttribute, NfNetlinkWriter},
+    parser::{parse_subsystem_nlmsg, NlMsg},
+    query::process_subsystem_objects,
+    sys::{
+        NFNL_SUBSYS_ULOG, NFULA_CFG_CMD, NFULA_CFG_FLAGS, NFULA_CFG_MODE, NFULA_L2HDR,
+        NFULA_PACKET_HDR, NFULA_PAYLOAD, NFULA_VLAN, NFULA_VLAN_PROTO, NFULA_VLAN_TCI,
+        NFULNL_CFG_CMD_BIND, NFULNL_CFG_F_SEQ, NFULNL_COPY_PACKET, NFULNL_MSG_CONFIG,
+        NFULNL_MSG_PACKET,
+    },
+    ProtocolFamily,
+};
+
+use super::NetlinkExpr;
+
+fn write_test_packet(buf: &mut Vec<u8>, packet: &NflogPacket, group: u16) {
+    let mut writer = NfNetlinkWriter::new(buf);
+    writer.write_subsystem_header(
+        NFNL_SUBSYS_ULOG as u8,
+        NFULNL_MSG_PACKET as u16,
+        ProtocolFamily::Ipv4,
+        0,
+        0,
+        Some(group.to_be()),
+    );
+    let data = writer.add_data_zeroed(packet.get_size());
+    packet.write_payload(data);
+    writer.finalize_writing_object();
+}
+
+#[test]
+fn nflog_config_is_valid() {
+    let config = NflogConfig::bind(5)
+        .with_copy_params(CopyParams::new(CopyMode::Packet, 0xffff))
+        .with_flags(NflogConfigFlags::SEQ.bits());
+
+    let mut buf = Vec::new();
+    config
+        .write(&mut NfNetlinkWriter::new(&mut buf), 0)
+        .expect("Couldn't write the message");
+    let (hdr, msg) =
+        parse_subsystem_nlmsg(&buf, NFNL_SUBSYS_ULOG as u8).expect("Couldn't parse the message");
+    assert_eq!(
+        get_operation_from_nlmsghdr_type(hdr.nlmsg_type),
+        NFULNL_MSG_CONFIG as u8
+    );
+    assert_eq!(hdr.nlmsg_len, 48);
+
+    let (nfgenmsg, raw_expr) = match msg {
+        NlMsg::NfGenMsg(nfgenmsg, raw_expr) => (nfgenmsg, raw_expr),
+        _ => panic!("Invalid return value type, expected a valid message"),
+    };
+    assert_eq!(u16::from_be(nfgenmsg.res_id), 5);
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFULA_CFG_CMD, vec![NFULNL_CFG_CMD_BIND as u8]),
+            NetlinkExpr::Final(
+                NFULA_CFG_MODE,
+                vec![0, 0, 0xff, 0xff, NFULNL_COPY_PACKET as u8, 0]
+            ),
+            NetlinkExpr::Final(
+                NFULA_CFG_FLAGS,
+                (NFULNL_CFG_F_SEQ as u16).to_be_bytes().to_vec()
+            ),
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn nflog_packets_are_decoded() {
+    let packet = NflogPacket::default()
+        .with_header(NflogPacketHeader {
+            hw_protocol: 0x0800,
+            hook: 1,
+        })
+        .with_mark(42u32)
+        .with_timestamp(PacketTimestamp {
+            sec: 1700000000,
+            usec: 12,
+        })
+        .with_indev(3u32)
+        .with_hwaddr(HardwareAddress::new(&[2, 0, 0, 0, 0, 1]).unwrap())
+        .with_payload(vec![0x45, 0, 0, 20])
+        .with_prefix("dropped: ")
+        .with_ct_info(CtInfo::New);
+
+    // the kernel may batch several packets in a single datagram
+    let mut buf = Vec::new();
+    write_test_packet(&mut buf, &packet, 5);
+    write_test_packet(&mut buf, &packet.clone().with_mark(43u32), 5);
+
+    let mut packets = Vec::new();
+    process_subsystem_objects(&buf, NFNL_SUBSYS_ULOG as u8, &mut |packet: NflogPacket| {
+        packets.push(packet);
+        Ok(())
+    })
+    .expect("Couldn't decode the packets");
+
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0].get_group(), 5);
+    assert_eq!(packets[0].get_family(), ProtocolFamily::Ipv4);
+    assert_eq!(packets[0].get_header().unwrap().hw_protocol, 0x0800);
+    assert_eq!(packets[0].get_mark(), Some(&42));
+    assert_eq!(packets[1].get_mark(), Some(&43));
+    assert_eq!(packets[0].get_timestamp().unwrap().sec, 1700000000);
+    assert_eq!(packets[0].get_indev(), Some(&3));
+    assert_eq!(
+        packets[0].get_hwaddr().unwrap().address(),
+        &[2, 0, 0, 0, 0, 1]
+    );
+    assert_eq!(packets[0].get_payload(), Some(&vec![0x45, 0, 0, 20]));
+    assert_eq!(
+        packets[0].get_prefix().map(String::as_str),
+        Some("dropped: ")
+    );
+    assert_eq!(packets[0].get_ct_info(), Some(&CtInfo::New));
+    assert_eq!(packets[0].get_outdev(), None);
+}
+
+#[test]
+fn nflog_bridge_packets_are_decoded() {
+    // the attributes of a tagged packet logged in the bridge family, as sent by the kernel
+    let attrs = NetlinkExpr::List(vec![
+        NetlinkExpr::Final(NFULA_PACKET_HDR, vec![0x08, 0x00, 1, 0]),
+        NetlinkExpr::Nested(
+            NFULA_VLAN,
+            vec![
+                NetlinkExpr::Final(NFULA_VLAN_PROTO, 0x88a8u16.to_be_bytes().to_vec()),
+                NetlinkExpr::Final(NFULA_VLAN_TCI, 0x000au16.to_be_bytes().to_vec()),
+            ],
+        ),
+        NetlinkExpr::Final(NFULA_L2HDR, vec![2, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 2]),
+        NetlinkExpr::Final(NFULA_PAYLOAD, vec![0x45, 0, 0, 20]),
+    ])
+    .to_raw();
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    writer.write_subsystem_header(
+        NFNL_SUBSYS_ULOG as u8,
+        NFULNL_MSG_PACKET as u16,
+        ProtocolFamily::Bridge,
+        0,
+        0,
+        Some(2u16.to_be()),
+    );
+    writer.add_data_zeroed(attrs.len()).copy_from_slice(&attrs);
+    writer.finalize_writing_object();
+
+    let mut packets = Vec::new();
+    process_subsystem_objects(&buf, NFNL_SUBSYS_ULOG as u8, &mut |packet: NflogPacket| {
+        packets.push(packet);
+        Ok(())
+    })
+    .expect("Couldn't decode the packets");
+
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].get_group(), 2);
+    assert_eq!(packets[0].get_family(), ProtocolFamily::Bridge);
+    assert_eq!(
+        packets[0].get_vlan(),
+        Some(
+            &PacketVlan::default()
+                .with_protocol(0x88a8u16)
+                .with_tci(0x000au16)
+        )
+    );
+    assert_eq!(
+        packets[0].get_l2hdr(),
+        Some(&vec![2, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 2])
+    );
+    assert_eq!(packets[0].get_payload(), Some(&vec![0x45, 0, 0, 20]));
+}
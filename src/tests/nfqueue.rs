@@ -0,0 +1,208 @@
+use crate::{
+    error::DecodeError,
+    nflog::{ConfigCommand, CopyMode, CopyParams, NflogPacket, PacketVlan},
+    nfqueue::{
+        NfqueueConfig, NfqueueConfigFlags, NfqueuePacket, NfqueuePacketHeader, NfqueueVerdict,
+        QueueVerdict,
+    },
+    nlmsg::{
+        get_operation_from_nlmsghdr_type, NfNetlinkAttribute, NfNetlinkDeserializable,
+        NfNetlinkWriter,
+    },
+    parser::{parse_subsystem_nlmsg, NlMsg},
+    sys::{
+        NFNL_SUBSYS_QUEUE, NFQA_CFG_CMD, NFQA_CFG_FLAGS, NFQA_CFG_F_FAIL_OPEN, NFQA_CFG_F_GSO,
+        NFQA_CFG_MASK, NFQA_CFG_PARAMS, NFQA_CFG_QUEUE_MAXLEN, NFQA_IFINDEX_INDEV, NFQA_L2HDR,
+        NFQA_MARK, NFQA_PACKET_HDR, NFQA_PAYLOAD, NFQA_PRIORITY, NFQA_VERDICT_HDR, NFQA_VLAN,
+        NFQA_VLAN_PROTO, NFQA_VLAN_TCI, NFQNL_CFG_CMD_BIND, NFQNL_COPY_META, NFQNL_MSG_CONFIG,
+        NFQNL_MSG_PACKET, NFQNL_MSG_VERDICT,
+    },
+    ProtocolFamily,
+};
+
+use super::NetlinkExpr;
+
+fn get_raw_message(buf: &[u8], msg_type: u32) -> (u16, &[u8]) {
+    let (hdr, msg) =
+        parse_subsystem_nlmsg(buf, NFNL_SUBSYS_QUEUE as u8).expect("Couldn't parse the message");
+    assert_eq!(
+        get_operation_from_nlmsghdr_type(hdr.nlmsg_type),
+        msg_type as u8
+    );
+    match msg {
+        NlMsg::NfGenMsg(nfgenmsg, raw_expr) => (u16::from_be(nfgenmsg.res_id), raw_expr),
+        _ => panic!("Invalid return value type, expected a valid message"),
+    }
+}
+
+#[test]
+fn nfqueue_config_is_valid() {
+    let config = NfqueueConfig::bind(3)
+        .with_copy_params(CopyParams::new(CopyMode::Meta, 0))
+        .with_queue_maxlen(1024u32)
+        .with_config_flags(
+            NfqueueConfigFlags::FAIL_OPEN | NfqueueConfigFlags::GSO,
+            NfqueueConfigFlags::FAIL_OPEN,
+        );
+
+    let mut buf = Vec::new();
+    config
+        .write(&mut NfNetlinkWriter::new(&mut buf), 0)
+        .expect("Couldn't write the message");
+    let (queue_num, raw_expr) = get_raw_message(&buf, NFQNL_MSG_CONFIG);
+    assert_eq!(queue_num, 3);
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFQA_CFG_CMD, vec![NFQNL_CFG_CMD_BIND as u8, 0, 0, 0]),
+            NetlinkExpr::Final(NFQA_CFG_PARAMS, vec![0, 0, 0, 0, NFQNL_COPY_META as u8, 0]),
+            NetlinkExpr::Final(NFQA_CFG_QUEUE_MAXLEN, 1024u32.to_be_bytes().to_vec()),
+            NetlinkExpr::Final(
+                NFQA_CFG_MASK,
+                (NFQA_CFG_F_FAIL_OPEN | NFQA_CFG_F_GSO)
+                    .to_be_bytes()
+                    .to_vec()
+            ),
+            NetlinkExpr::Final(NFQA_CFG_FLAGS, NFQA_CFG_F_FAIL_OPEN.to_be_bytes().to_vec()),
+        ])
+        .to_raw()
+    );
+    assert_eq!(config.get_command().unwrap().command, ConfigCommand::Bind);
+}
+
+#[test]
+fn nfqueue_verdict_is_valid() {
+    let packet = NfqueuePacket::default()
+        .with_header(NfqueuePacketHeader {
+            packet_id: 42,
+            hw_protocol: 0x86dd,
+            hook: 3,
+        })
+        .with_payload(vec![0x60, 0, 0, 0]);
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    writer.write_subsystem_header(
+        NFNL_SUBSYS_QUEUE as u8,
+        NFQNL_MSG_PACKET as u16,
+        ProtocolFamily::Ipv6,
+        0,
+        0,
+        Some(3u16.to_be()),
+    );
+    packet.write_payload(writer.add_data_zeroed(packet.get_size()));
+    writer.finalize_writing_object();
+
+    // packets of other subsystems are refused
+    assert!(matches!(
+        NflogPacket::deserialize(&buf),
+        Err(DecodeError::InvalidSubsystem(x)) if x == NFNL_SUBSYS_QUEUE as u8
+    ));
+
+    let (packet, remaining) = NfqueuePacket::deserialize(&buf).expect("Couldn't decode the packet");
+    assert_eq!(remaining.len(), 0);
+    assert_eq!(packet.get_queue_num(), 3);
+    assert_eq!(packet.get_family(), ProtocolFamily::Ipv6);
+
+    let verdict = packet
+        .verdict(QueueVerdict::Accept)
+        .expect("Missing packet header")
+        .with_mark(7u32)
+        .with_payload(vec![0x60, 0, 0, 1]);
+    let mut buf = Vec::new();
+    verdict
+        .write(&mut NfNetlinkWriter::new(&mut buf), 0)
+        .expect("Couldn't write the message");
+    let (queue_num, raw_expr) = get_raw_message(&buf, NFQNL_MSG_VERDICT);
+    assert_eq!(queue_num, 3);
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFQA_VERDICT_HDR, vec![0, 0, 0, 1, 0, 0, 0, 42]),
+            NetlinkExpr::Final(NFQA_MARK, 7u32.to_be_bytes().to_vec()),
+            NetlinkExpr::Final(NFQA_PAYLOAD, vec![0x60, 0, 0, 1]),
+        ])
+        .to_raw()
+    );
+
+    // the queue number is stored in the upper 16 bits of the verdict
+    let verdict = NfqueueVerdict::new(3, 42, QueueVerdict::Queue(2));
+    let mut buf = Vec::new();
+    verdict
+        .write(&mut NfNetlinkWriter::new(&mut buf), 0)
+        .expect("Couldn't write the message");
+    let (_queue_num, raw_expr) = get_raw_message(&buf, NFQNL_MSG_VERDICT);
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::Final(NFQA_VERDICT_HDR, vec![0, 2, 0, 3, 0, 0, 0, 42]).to_raw()
+    );
+}
+
+#[test]
+fn nfqueue_packets_are_decoded() {
+    // the attributes of a tagged packet queued in the bridge family, as sent by the kernel
+    let attrs = NetlinkExpr::List(vec![
+        // the packet header is packed, hence only 7 bytes long
+        NetlinkExpr::Final(NFQA_PACKET_HDR, vec![0, 0, 0, 42, 0x08, 0x00, 1]),
+        NetlinkExpr::Final(NFQA_MARK, 7u32.to_be_bytes().to_vec()),
+        NetlinkExpr::Final(NFQA_PRIORITY, 6u32.to_be_bytes().to_vec()),
+        NetlinkExpr::Final(NFQA_IFINDEX_INDEV, 2u32.to_be_bytes().to_vec()),
+        NetlinkExpr::Nested(
+            NFQA_VLAN,
+            vec![
+                NetlinkExpr::Final(NFQA_VLAN_PROTO, 0x8100u16.to_be_bytes().to_vec()),
+                NetlinkExpr::Final(NFQA_VLAN_TCI, 0x2005u16.to_be_bytes().to_vec()),
+            ],
+        ),
+        NetlinkExpr::Final(NFQA_L2HDR, vec![2, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 2]),
+        NetlinkExpr::Final(NFQA_PAYLOAD, vec![0x45, 0, 0, 20]),
+    ])
+    .to_raw();
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    writer.write_subsystem_header(
+        NFNL_SUBSYS_QUEUE as u8,
+        NFQNL_MSG_PACKET as u16,
+        ProtocolFamily::Bridge,
+        0,
+        0,
+        Some(4u16.to_be()),
+    );
+    writer.add_data_zeroed(attrs.len()).copy_from_slice(&attrs);
+    writer.finalize_writing_object();
+
+    let (packet, remaining) = NfqueuePacket::deserialize(&buf).expect("Couldn't decode the packet");
+    assert_eq!(remaining.len(), 0);
+    assert_eq!(packet.get_queue_num(), 4);
+    assert_eq!(packet.get_family(), ProtocolFamily::Bridge);
+    assert_eq!(
+        packet.get_header(),
+        Some(&NfqueuePacketHeader {
+            packet_id: 42,
+            hw_protocol: 0x0800,
+            hook: 1,
+        })
+    );
+    assert_eq!(packet.get_mark(), Some(&7));
+    assert_eq!(packet.get_priority(), Some(&6));
+    assert_eq!(packet.get_indev(), Some(&2));
+    assert_eq!(
+        packet.get_vlan(),
+        Some(
+            &PacketVlan::default()
+                .with_protocol(0x8100u16)
+                .with_tci(0x2005u16)
+        )
+    );
+    assert_eq!(
+        packet.get_l2hdr(),
+        Some(&vec![2, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 2])
+    );
+    assert_eq!(packet.get_payload(), Some(&vec![0x45, 0, 0, 20]));
+
+    let verdict = packet
+        .verdict(QueueVerdict::Drop)
+        .expect("Missing packet header");
+    assert_eq!(verdict.get_queue_num(), 4);
+    assert_eq!(verdict.get_header().unwrap().packet_id, 42);
+    assert_eq!(verdict.get_header().unwrap().verdict, QueueVerdict::Drop);
+}
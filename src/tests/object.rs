@@ -0,0 +1,113 @@
+use crate::{
+    error::BuilderError,
+    expr::{Synproxy, SynproxyFlags},
+    nlmsg::{
+        get_operation_from_nlmsghdr_type, NfNetlinkDeserializable, NfNetlinkObject, NfNetlinkWriter,
+    },
+    sys::{
+        NFTA_OBJ_DATA, NFTA_OBJ_NAME, NFTA_OBJ_TABLE, NFTA_OBJ_TYPE, NFTA_SECMARK_CTX,
+        NFTA_SYNPROXY_FLAGS, NFTA_SYNPROXY_MSS, NFTA_SYNPROXY_WSCALE, NFT_MSG_NEWOBJ,
+        NFT_OBJECT_SECMARK, NFT_OBJECT_SYNPROXY, NFT_SECMARK_CTX_MAXLEN,
+    },
+    MsgType, Object, ObjectData, ObjectType, Secmark,
+};
+
+use super::{get_test_nlmsg, get_test_table, NetlinkExpr, TABLE_NAME};
+
+fn get_test_synproxy_object() -> Object {
+    Object::new(&get_test_table())
+        .with_name("web")
+        .with_object_data(Synproxy::new(1460, 7, SynproxyFlags::TIMESTAMP))
+}
+
+#[test]
+fn new_synproxy_object() {
+    let mut obj = get_test_synproxy_object();
+    assert_eq!(obj.get_type(), Some(&ObjectType::Synproxy));
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut obj);
+    assert_eq!(
+        get_operation_from_nlmsghdr_type(nlmsghdr.nlmsg_type),
+        NFT_MSG_NEWOBJ as u8
+    );
+    assert_eq!(nlmsghdr.nlmsg_len, 80);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_OBJ_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_OBJ_NAME, b"web".to_vec()),
+            NetlinkExpr::Final(NFTA_OBJ_TYPE, NFT_OBJECT_SYNPROXY.to_be_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_OBJ_DATA,
+                vec![
+                    NetlinkExpr::Final(NFTA_SYNPROXY_MSS, 1460u16.to_be_bytes().to_vec()),
+                    NetlinkExpr::Final(NFTA_SYNPROXY_WSCALE, vec![7u8]),
+                    NetlinkExpr::Final(NFTA_SYNPROXY_FLAGS, 0x0bu32.to_be_bytes().to_vec()),
+                ]
+            ),
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn objects_round_trip() {
+    let obj = get_test_synproxy_object().with_handle(3u64);
+    let raw = Object::new(&get_test_table())
+        .with_name("helper")
+        .with_type(ObjectType::CtHelper)
+        .with_data(ObjectData::Raw(vec![8, 0, 1, 0, b'f', b't', b'p', 0]));
+
+    for original in [obj, raw] {
+        let mut buf = Vec::new();
+        let mut writer = NfNetlinkWriter::new(&mut buf);
+        original
+            .add_or_remove(&mut writer, MsgType::Add, 0)
+            .expect("Couldn't write the object");
+        let (decoded, remaining) = Object::deserialize(&buf).expect("Couldn't decode the object");
+        assert!(remaining.is_empty());
+        assert_eq!(decoded, original);
+    }
+}
+
+#[test]
+fn new_secmark_object() {
+    let ctx = "system_u:object_r:http_packet_t:s0";
+    let mut obj = Object::new(&get_test_table())
+        .with_name("http")
+        .with_object_data(Secmark::new(ctx).unwrap());
+    assert_eq!(obj.get_type(), Some(&ObjectType::Secmark));
+
+    let mut buf = Vec::new();
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut obj);
+    assert_eq!(nlmsghdr.nlmsg_len, 96);
+
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_OBJ_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_OBJ_NAME, b"http".to_vec()),
+            NetlinkExpr::Final(NFTA_OBJ_TYPE, NFT_OBJECT_SECMARK.to_be_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_OBJ_DATA,
+                vec![NetlinkExpr::Final(
+                    NFTA_SECMARK_CTX,
+                    ctx.as_bytes().to_vec()
+                )]
+            ),
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn secmark_context_length_is_validated() {
+    let max_len = NFT_SECMARK_CTX_MAXLEN as usize;
+    assert!(Secmark::new("a".repeat(max_len)).is_ok());
+    assert!(matches!(
+        Secmark::new("a".repeat(max_len + 1)),
+        Err(BuilderError::TooLongSecmarkContext)
+    ));
+}
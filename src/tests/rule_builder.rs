@@ -0,0 +1,183 @@
+use std::net::Ipv4Addr;
+
+use crate::{
+    error::BuilderError,
+    expr::{
+        Bitwise, CmpOp, ExpressionVariant, HighLevelPayload, IPv4HeaderField, MetaType,
+        NetworkHeaderField, Register, RegisterAllocator, TCPHeaderField, TransportHeaderField,
+    },
+    nlmsg::{NfNetlinkDeserializable, NfNetlinkObject, NfNetlinkWriter},
+    set::SetBuilder,
+    sys::NFT_REG32_07,
+    LoadSource, MsgType, Rule, RuleBuilder,
+};
+
+use super::{get_test_chain, get_test_table, rule_exprs, SET_NAME};
+
+fn get_cmp_registers(builder: RuleBuilder) -> Vec<Register> {
+    builder
+        .finish()
+        .get_expressions()
+        .unwrap()
+        .iter()
+        .filter_map(|expr| match expr.get_data() {
+            Some(ExpressionVariant::Cmp(cmp)) => cmp.get_sreg().copied(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn rule_builder_allocates_registers() {
+    let mut builder = RuleBuilder::new(&get_test_chain()).unwrap();
+    let iifname = builder.load_meta(MetaType::IifName).unwrap();
+    let saddr = builder
+        .load_payload(HighLevelPayload::Network(NetworkHeaderField::IPv4(
+            IPv4HeaderField::Saddr,
+        )))
+        .unwrap();
+    assert_eq!(iifname.get_reg(), Register::Reg1);
+    assert_eq!(iifname.get_len(), libc::IFNAMSIZ as u32);
+    assert_eq!(saddr.get_reg(), Register::Reg2);
+    assert_eq!(saddr.get_len(), 4);
+
+    builder
+        .cmp(saddr, CmpOp::Eq, Ipv4Addr::new(10, 0, 0, 1).octets())
+        .unwrap();
+    builder
+        .cmp(iifname, CmpOp::Eq, *b"lo\0\0\0\0\0\0\0\0\0\0\0\0\0\0")
+        .unwrap();
+    builder.free(iifname);
+    // the freed register is reused
+    let mark = builder.load_meta(MetaType::Mark).unwrap();
+    assert_eq!(mark.get_reg(), Register::Reg1);
+    builder.cmp(mark, CmpOp::Eq, 1u32.to_ne_bytes()).unwrap();
+
+    assert_eq!(
+        get_cmp_registers(builder),
+        vec![Register::Reg2, Register::Reg1, Register::Reg1]
+    );
+}
+
+#[test]
+fn rule_builder_checks_lengths() {
+    let mut builder = RuleBuilder::new(&get_test_chain()).unwrap();
+    let l4proto = builder.load_meta(MetaType::L4Proto).unwrap();
+    assert!(matches!(
+        builder.cmp(l4proto, CmpOp::Eq, 6u16.to_be_bytes()),
+        Err(BuilderError::IncompatibleLength)
+    ));
+    assert!(matches!(
+        builder.bitwise(l4proto, [0xffu8, 0xff], [0u8, 0]),
+        Err(BuilderError::IncompatibleLength)
+    ));
+
+    let (set, _) = SetBuilder::<Ipv4Addr>::new(SET_NAME, &get_test_table())
+        .unwrap()
+        .finish();
+    assert!(matches!(
+        builder.lookup(l4proto, &set),
+        Err(BuilderError::IncompatibleLength)
+    ));
+    let addr = builder
+        .load_immediate(Ipv4Addr::LOCALHOST.octets())
+        .unwrap();
+    builder.lookup(addr, &set).unwrap();
+}
+
+#[test]
+fn register_allocator_spans_registers() {
+    let mut regs = RegisterAllocator::new();
+    // 20 bytes need five 32 bits registers
+    let first = regs.allocate(20).unwrap();
+    assert_eq!(first.get_reg(), Register::Reg1);
+    assert_eq!(regs.allocate(2).unwrap().get_reg(), Register::Reg32_05);
+    assert_eq!(regs.allocate(16).unwrap().get_reg(), Register::Reg32_06);
+    assert!(matches!(
+        regs.allocate(28),
+        Err(BuilderError::NoRegisterAvailable)
+    ));
+    assert_eq!(regs.allocate(24).unwrap().get_reg(), Register::Reg32_10);
+    regs.free(first);
+    assert_eq!(regs.allocate(16).unwrap().get_reg(), Register::Reg1);
+    // legacy registers are preferred when they designate the same location
+    assert_eq!(regs.allocate(4).unwrap().get_reg(), Register::Reg2);
+    assert!(matches!(
+        regs.allocate(1),
+        Err(BuilderError::NoRegisterAvailable)
+    ));
+}
+
+#[test]
+fn register_file_offsets() {
+    assert_eq!(Register::Reg1.data_offset(), Some(0));
+    assert_eq!(Register::Reg3.data_offset(), Some(32));
+    assert_eq!(Register::Reg32_09.data_offset(), Some(36));
+    assert_eq!(Register::Verdict.data_offset(), None);
+    assert_eq!(Register::from_data_offset(16), Some(Register::Reg2));
+    assert_eq!(Register::from_data_offset(60), Some(Register::Reg32_15));
+    assert_eq!(Register::from_data_offset(64), None);
+    assert!(Register::Reg2.same_location(&Register::Reg32_04));
+    assert_eq!(
+        Register::try_from(NFT_REG32_07).unwrap(),
+        Register::Reg32_07
+    );
+}
+
+#[test]
+fn rule_builder_loads_concatenations() {
+    let mut builder = RuleBuilder::new(&get_test_chain()).unwrap();
+    let concat = builder
+        .load_concat(&[
+            LoadSource::Meta(MetaType::L4Proto),
+            LoadSource::Payload(HighLevelPayload::Network(NetworkHeaderField::IPv4(
+                IPv4HeaderField::Saddr,
+            ))),
+            LoadSource::Payload(HighLevelPayload::Transport(TransportHeaderField::Tcp(
+                TCPHeaderField::Dport,
+            ))),
+        ])
+        .unwrap();
+    assert_eq!(concat.get_reg(), Register::Reg1);
+    assert_eq!(concat.get_len(), 12);
+    let rule = builder.finish();
+
+    // the rule must survive a round trip through the kernel representation
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    rule.add_or_remove(&mut writer, MsgType::Add, 0)
+        .expect("Couldn't write the rule");
+    let (rule, _) = Rule::deserialize(&buf).expect("Couldn't deserialize the rule");
+
+    let dregs: Vec<Register> = rule
+        .get_expressions()
+        .unwrap()
+        .iter()
+        .filter_map(|expr| match expr.get_data() {
+            Some(ExpressionVariant::Meta(meta)) => meta.get_dreg().copied(),
+            Some(ExpressionVariant::Payload(payload)) => payload.get_dreg().copied(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        dregs,
+        vec![Register::Reg1, Register::Reg32_01, Register::Reg32_02]
+    );
+}
+
+#[test]
+fn rule_builder_masks_sub_byte_fields() {
+    let mut builder = RuleBuilder::new(&get_test_chain()).unwrap();
+    let dscp = HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Dscp));
+    let value = builder.load_payload(dscp).unwrap();
+    builder.cmp(value, CmpOp::Eq, [0x20u8]).unwrap();
+
+    let exprs = rule_exprs(&builder.finish());
+    assert_eq!(
+        exprs[..2],
+        [
+            ExpressionVariant::Payload(dscp.build()),
+            ExpressionVariant::Bitwise(Bitwise::new([0xfcu8], [0u8]).unwrap()),
+        ]
+    );
+}
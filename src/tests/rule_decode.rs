@@ -0,0 +1,175 @@
+use std::net::Ipv4Addr;
+
+use ipnetwork::{IpNetwork, Ipv4Network};
+
+use crate::{
+    expr::{
+        ct::ConnTrackState, Cmp, CmpOp, Counter, HighLevelPayload, IPv4HeaderField,
+        IPv6HeaderField, LLHeaderField, NetworkHeaderField, VerdictKind, VlanHeaderField,
+    },
+    nlmsg::{NfNetlinkDeserializable, NfNetlinkObject, NfNetlinkWriter},
+    Chain, Match, MsgType, Protocol, ProtocolFamily, Rule, RuleBuilder, Table,
+};
+
+use super::{get_test_rule, CHAIN_NAME, TABLE_NAME};
+
+/// Returns `rule` as we would get it when listing the ruleset.
+fn round_trip(rule: &Rule) -> Rule {
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    rule.add_or_remove(&mut writer, MsgType::Add, 0)
+        .expect("Couldn't write the rule");
+    Rule::deserialize(&buf)
+        .expect("Couldn't deserialize the rule")
+        .0
+}
+
+#[test]
+fn decode_rule_matches() {
+    let local_net = IpNetwork::new([192, 168, 1, 0].into(), 24).unwrap();
+    let rule = get_test_rule()
+        .established()
+        .unwrap()
+        .iface("lo")
+        .unwrap()
+        .iface_id(3)
+        .snetwork(local_net)
+        .unwrap()
+        .dport(22, Protocol::TCP)
+        .accept();
+
+    let decoded = round_trip(&rule).decode();
+    assert_eq!(
+        decoded.matches,
+        vec![
+            Match::CtState(ConnTrackState::ESTABLISHED),
+            Match::IifName("lo".to_string()),
+            Match::Iif(3),
+            Match::NfProto(libc::NFPROTO_IPV4 as u8),
+            Match::Ipv4Saddr(Ipv4Network::new(Ipv4Addr::new(192, 168, 1, 0), 24).unwrap()),
+            Match::L4Proto(libc::IPPROTO_TCP as u8),
+            Match::TcpDport(22),
+        ]
+    );
+    assert_eq!(decoded.verdict, Some(VerdictKind::Accept));
+}
+
+#[test]
+fn decode_rule_ports() {
+    let rule = get_test_rule()
+        .sport(1024, Protocol::TCP)
+        .dport(22, Protocol::TCP)
+        .sport(53, Protocol::UDP);
+
+    let decoded = round_trip(&rule).decode();
+    assert_eq!(
+        decoded.matches,
+        vec![
+            Match::L4Proto(libc::IPPROTO_TCP as u8),
+            Match::TcpSport(1024),
+            Match::L4Proto(libc::IPPROTO_TCP as u8),
+            Match::TcpDport(22),
+            Match::L4Proto(libc::IPPROTO_UDP as u8),
+            Match::UdpSport(53),
+        ]
+    );
+}
+
+#[test]
+fn decode_rule_unknown_expressions() {
+    let rule = get_test_rule()
+        .saddr(Ipv4Addr::new(10, 0, 0, 1).into())
+        .with_expr(Counter::default())
+        .drop();
+
+    let decoded = rule.decode();
+    assert_eq!(decoded.matches.len(), 3);
+    assert_eq!(decoded.matches[0], Match::NfProto(libc::NFPROTO_IPV4 as u8));
+    assert_eq!(
+        decoded.matches[1],
+        Match::Ipv4Saddr(Ipv4Network::new(Ipv4Addr::new(10, 0, 0, 1), 32).unwrap())
+    );
+    assert!(matches!(decoded.matches[2], Match::Other(_)));
+    assert_eq!(decoded.verdict, Some(VerdictKind::Drop));
+}
+
+#[test]
+fn decode_rule_network_header_protocol() {
+    for (family, field) in [
+        (
+            ProtocolFamily::Ipv4,
+            NetworkHeaderField::IPv4(IPv4HeaderField::Protocol),
+        ),
+        (
+            ProtocolFamily::Ipv6,
+            NetworkHeaderField::IPv6(IPv6HeaderField::NextHeader),
+        ),
+    ] {
+        let table = Table::new(family).with_name(TABLE_NAME);
+        let chain = Chain::new(&table).with_name(CHAIN_NAME);
+        // `ip protocol tcp tcp dport 22` and `ip6 nexthdr tcp tcp dport 22`
+        let rule = Rule::new(&chain)
+            .unwrap()
+            .with_expr(HighLevelPayload::Network(field).build())
+            .with_expr(Cmp::new(CmpOp::Eq, [libc::IPPROTO_TCP as u8]))
+            .dport(22, Protocol::TCP);
+
+        let decoded = rule.decode();
+        assert_eq!(
+            decoded.matches,
+            vec![
+                Match::L4Proto(libc::IPPROTO_TCP as u8),
+                Match::L4Proto(libc::IPPROTO_TCP as u8),
+                Match::TcpDport(22),
+            ]
+        );
+    }
+}
+
+#[test]
+fn decode_rule_masked_fields() {
+    let cases = [
+        (
+            ProtocolFamily::Ipv4,
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Dscp)),
+            vec![0x2e << 2],
+        ),
+        (
+            ProtocolFamily::Ipv4,
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Ecn)),
+            vec![0x03],
+        ),
+        (
+            ProtocolFamily::Ipv4,
+            HighLevelPayload::Network(NetworkHeaderField::IPv4(IPv4HeaderField::Version)),
+            vec![0x40],
+        ),
+        (
+            ProtocolFamily::Ipv6,
+            HighLevelPayload::Network(NetworkHeaderField::IPv6(IPv6HeaderField::FlowLabel)),
+            vec![0x01, 0x23, 0x45],
+        ),
+        (
+            ProtocolFamily::Inet,
+            HighLevelPayload::LinkLayer(LLHeaderField::Vlan(VlanHeaderField::Pcp)),
+            vec![5 << 5],
+        ),
+        (
+            ProtocolFamily::Inet,
+            HighLevelPayload::LinkLayer(LLHeaderField::Vlan(VlanHeaderField::Id)),
+            100u16.to_be_bytes().to_vec(),
+        ),
+    ];
+    for (family, field, value) in cases {
+        let table = Table::new(family).with_name(TABLE_NAME);
+        let chain = Chain::new(&table).with_name(CHAIN_NAME);
+        let mut builder = RuleBuilder::new(&chain).unwrap();
+        let loaded = builder.load_payload(field).unwrap();
+        builder.cmp(loaded, CmpOp::Eq, value.clone()).unwrap();
+        let rule = builder.finish().accept();
+
+        let decoded = round_trip(&rule).decode();
+        assert_eq!(decoded.matches, vec![Match::Payload(field, value)]);
+        assert_eq!(decoded.verdict, Some(VerdictKind::Accept));
+    }
+}
@@ -2,15 +2,17 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 
 use crate::{
     data_type::DataType,
-    nlmsg::get_operation_from_nlmsghdr_type,
-    set::SetBuilder,
+    error::BuilderError,
+    nlmsg::{get_operation_from_nlmsghdr_type, pad_netlink_object_with_variable_size},
+    parser::{check_attribute_size, parse_nlmsg},
+    set::{MapBuilder, SetBuilder},
     sys::{
-        NFTA_DATA_VALUE, NFTA_LIST_ELEM, NFTA_SET_ELEM_KEY, NFTA_SET_ELEM_LIST_ELEMENTS,
-        NFTA_SET_ELEM_LIST_SET, NFTA_SET_ELEM_LIST_TABLE, NFTA_SET_KEY_LEN, NFTA_SET_KEY_TYPE,
-        NFTA_SET_NAME, NFTA_SET_TABLE, NFTA_SET_USERDATA, NFT_MSG_DELSET, NFT_MSG_NEWSET,
-        NFT_MSG_NEWSETELEM,
+        NFTA_DATA_VALUE, NFTA_LIST_ELEM, NFTA_SET_ELEM_DATA, NFTA_SET_ELEM_KEY,
+        NFTA_SET_ELEM_LIST_ELEMENTS, NFTA_SET_ELEM_LIST_SET, NFTA_SET_ELEM_LIST_TABLE,
+        NFTA_SET_KEY_LEN, NFTA_SET_KEY_TYPE, NFTA_SET_NAME, NFTA_SET_TABLE, NFTA_SET_USERDATA,
+        NFT_MSG_DELSET, NFT_MSG_NEWSET, NFT_MSG_NEWSETELEM, NFT_SET_MAP,
     },
-    MsgType,
+    Batch, MsgType,
 };
 
 use super::{
@@ -117,3 +119,103 @@ fn new_set_with_data() {
         .to_raw()
     );
 }
+
+#[test]
+fn new_map_with_data() {
+    let ip1 = Ipv4Addr::new(10, 0, 0, 1);
+    let ip2 = Ipv4Addr::new(10, 0, 0, 2);
+    let mut map_builder = MapBuilder::<u32, Ipv4Addr>::new(SET_NAME.to_string(), &get_test_table())
+        .expect("Couldn't create a map");
+
+    map_builder.add(&0, &ip1);
+    map_builder.add(&1, &ip2);
+    let (map, mut elem_list) = map_builder.finish();
+    assert_eq!(map.get_flags(), Some(&NFT_SET_MAP));
+    assert_eq!(map.get_key_len(), Some(&u32::LEN));
+    assert_eq!(map.get_data_type(), Some(&Ipv4Addr::TYPE));
+    assert_eq!(map.get_data_len(), Some(&Ipv4Addr::LEN));
+
+    let mut buf = Vec::new();
+
+    let (nlmsghdr, _nfgenmsg, raw_expr) = get_test_nlmsg(&mut buf, &mut elem_list);
+    assert_eq!(nlmsghdr.nlmsg_len, 108);
+
+    let elem = |key: u32, value: Ipv4Addr| {
+        NetlinkExpr::Nested(
+            NFTA_LIST_ELEM,
+            vec![
+                NetlinkExpr::Nested(
+                    NFTA_SET_ELEM_KEY,
+                    vec![NetlinkExpr::Final(NFTA_DATA_VALUE, key.data())],
+                ),
+                NetlinkExpr::Nested(
+                    NFTA_SET_ELEM_DATA,
+                    vec![NetlinkExpr::Final(NFTA_DATA_VALUE, value.data())],
+                ),
+            ],
+        )
+    };
+    assert_eq!(
+        raw_expr,
+        NetlinkExpr::List(vec![
+            NetlinkExpr::Final(NFTA_SET_ELEM_LIST_TABLE, TABLE_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Final(NFTA_SET_ELEM_LIST_SET, SET_NAME.as_bytes().to_vec()),
+            NetlinkExpr::Nested(
+                NFTA_SET_ELEM_LIST_ELEMENTS,
+                vec![elem(0, ip1), elem(1, ip2)]
+            ),
+        ])
+        .to_raw()
+    );
+}
+
+#[test]
+fn split_large_set_element_list() {
+    let mut set_builder = SetBuilder::<Ipv4Addr>::new(SET_NAME.to_string(), &get_test_table())
+        .expect("Couldn't create a set");
+    let nb_elements = 20_000u32;
+    for i in 0..nb_elements {
+        set_builder.add(&Ipv4Addr::from(i));
+    }
+    let (_set, elem_list) = set_builder.finish();
+
+    // a single message cannot hold that many elements
+    let mut batch = Batch::new();
+    assert!(matches!(
+        batch.add(&elem_list, MsgType::Add),
+        Err(BuilderError::AttributeTooLarge)
+    ));
+
+    let chunks = elem_list.split();
+    assert!(chunks.len() > 1);
+    let mut next_element = 0;
+    for chunk in &chunks {
+        assert_eq!(chunk.table, elem_list.table);
+        assert_eq!(chunk.set, elem_list.set);
+        assert!(check_attribute_size(chunk.elements.as_ref().unwrap()).is_ok());
+        for element in chunk.elements.as_ref().unwrap().iter() {
+            assert_eq!(
+                element.key.as_ref().unwrap().get_value(),
+                Some(&Ipv4Addr::from(next_element).data())
+            );
+            next_element += 1;
+        }
+    }
+    assert_eq!(next_element, nb_elements);
+
+    batch
+        .add_set_elements(&elem_list, MsgType::Add)
+        .expect("Couldn't add the set elements to the batch");
+    let buf = batch.finalize();
+
+    // the batch begin message, one message per chunk and the batch end message
+    let mut nb_messages = 0;
+    let mut remaining_data = buf.as_slice();
+    while !remaining_data.is_empty() {
+        let (hdr, _msg) = parse_nlmsg(remaining_data).expect("Invalid nlmsg message");
+        nb_messages += 1;
+        remaining_data =
+            &remaining_data[pad_netlink_object_with_variable_size(hdr.nlmsg_len as usize)..];
+    }
+    assert_eq!(nb_messages, chunks.len() + 2);
+}
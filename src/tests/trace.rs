@@ -0,0 +1,114 @@
+use libc::NF_DROP;
+
+use crate::{
+    expr::{Verdict, VerdictType},
+    nlmsg::{NfNetlinkAttribute, NfNetlinkDeserializable, NfNetlinkObject, NfNetlinkWriter},
+    sys::NFT_MSG_TRACE,
+    trace::{Trace, TraceCollector, TraceType},
+    ChainPolicy, ProtocolFamily,
+};
+
+use super::{get_test_rule, CHAIN_NAME, TABLE_NAME};
+
+fn get_test_trace(id: u32, trace_type: TraceType) -> Trace {
+    Trace::default()
+        .with_table(TABLE_NAME)
+        .with_chain(CHAIN_NAME)
+        .with_type(trace_type)
+        .with_id(id)
+}
+
+fn serialize_trace(trace: &Trace, family: ProtocolFamily) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut writer = NfNetlinkWriter::new(&mut buf);
+    writer.write_header(NFT_MSG_TRACE as u16, family, 0, 0, None);
+    trace.write_payload(writer.add_data_zeroed(trace.get_size()));
+    writer.finalize_writing_object();
+    buf
+}
+
+#[test]
+fn parse_trace() {
+    let trace = get_test_trace(0xdeadbeef, TraceType::Policy)
+        .with_network_header(vec![0x45, 0, 0, 20])
+        .with_iif(2u32)
+        .with_iif_type(1u16)
+        .with_mark(7u32)
+        .with_nfproto(libc::NFPROTO_IPV4 as u32)
+        .with_policy(ChainPolicy::Drop)
+        .with_verdict(Verdict::default().with_code(VerdictType::Drop));
+    let buf = serialize_trace(&trace, ProtocolFamily::Inet);
+
+    let (decoded, remaining) = Trace::deserialize(&buf).expect("Couldn't decode the trace");
+    assert_eq!(remaining.len(), 0);
+    assert_eq!(decoded.get_family(), ProtocolFamily::Inet);
+    assert_eq!(decoded.get_table().map(String::as_str), Some(TABLE_NAME));
+    assert_eq!(decoded.get_type(), Some(&TraceType::Policy));
+    assert_eq!(decoded.get_id(), Some(&0xdeadbeef));
+    assert_eq!(decoded.get_network_header(), Some(&vec![0x45, 0, 0, 20]));
+    assert_eq!(decoded.get_iif(), Some(&2));
+    assert_eq!(decoded.get_mark(), Some(&7));
+    assert_eq!(decoded.get_policy(), Some(&ChainPolicy::Drop));
+    assert_eq!(
+        decoded.get_verdict().and_then(Verdict::get_code),
+        Some(&VerdictType::Drop)
+    );
+    assert_eq!(decoded.get_oif(), None);
+}
+
+#[test]
+fn traces_are_correlated_with_rules() {
+    let rule = get_test_rule().with_handle(12u64);
+    let other_rule = get_test_rule().with_handle(13u64);
+    let rules = vec![other_rule, rule.clone()];
+
+    let mut collector = TraceCollector::new();
+    for (id, handle) in [(1, Some(12u64)), (2, None), (1, None)] {
+        let mut trace = get_test_trace(id, TraceType::Rule);
+        if let Some(handle) = handle {
+            trace.set_rule_handle(handle);
+        }
+        let buf = serialize_trace(&trace, rule.get_family());
+        collector.add(Trace::deserialize(&buf).unwrap().0);
+    }
+
+    let traces: Vec<_> = collector.traces().collect();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].get_id(), 1);
+    assert_eq!(traces[0].events().len(), 2);
+    assert_eq!(traces[1].events().len(), 1);
+
+    let path = traces[0].with_rules(&rules);
+    assert_eq!(path[0].1, Some(&rule));
+    assert_eq!(path[1].1, None);
+
+    // a rule with the same handle in another family is a different rule
+    let trace = get_test_trace(3, TraceType::Rule).with_rule_handle(12u64);
+    let buf = serialize_trace(&trace, ProtocolFamily::Ipv6);
+    assert_eq!(Trace::deserialize(&buf).unwrap().0.find_rule(&rules), None);
+
+    assert_eq!(collector.take(1).map(|t| t.events().len()), Some(2));
+    assert_eq!(collector.traces().count(), 1);
+}
+
+#[test]
+fn trace_collector_evicts_oldest_paths() {
+    let mut collector = TraceCollector::with_capacity(2);
+    assert!(collector
+        .add(Trace::default().with_type(TraceType::Rule))
+        .is_none());
+
+    for id in [1, 2, 1, 3] {
+        let path = collector.add(get_test_trace(id, TraceType::Rule)).unwrap();
+        assert_eq!(path.get_id(), id);
+    }
+    let ids: Vec<u32> = collector.traces().map(|t| t.get_id()).collect();
+    assert_eq!(ids, [2, 3]);
+    assert_eq!(collector.take(1), None);
+
+    // a path removed with take does not count towards the capacity anymore
+    assert!(collector.take(2).is_some());
+    collector.add(get_test_trace(4, TraceType::Rule));
+    let ids: Vec<u32> = collector.traces().map(|t| t.get_id()).collect();
+    assert_eq!(ids, [3, 4]);
+}
@@ -0,0 +1,267 @@
+//! Decoding of the trace events sent by the kernel for the packets with the `nftrace` flag set
+//! (see [`Rule::nftrace`]), as shown by `nft monitor trace`.
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+
+use netlink_sys::{AsyncSocket, Socket};
+use rustables_macros::{nfnetlink_enum, nfnetlink_struct};
+
+use crate::{
+    error::{DecodeError, QueryError},
+    expr::Verdict,
+    list_rules_for_chain, list_rules_for_chain_async,
+    nlmsg::{NfNetlinkDeserializable, NfNetlinkObject},
+    parser::parse_subsystem_object,
+    query::{recv_subsystem_objects, recv_subsystem_objects_async},
+    sys::{
+        NFNLGRP_NFTRACE, NFNL_SUBSYS_NFTABLES, NFTA_TRACE_CHAIN, NFTA_TRACE_ID, NFTA_TRACE_IIF,
+        NFTA_TRACE_IIFTYPE, NFTA_TRACE_LL_HEADER, NFTA_TRACE_MARK, NFTA_TRACE_NETWORK_HEADER,
+        NFTA_TRACE_NFPROTO, NFTA_TRACE_OIF, NFTA_TRACE_OIFTYPE, NFTA_TRACE_POLICY,
+        NFTA_TRACE_RULE_HANDLE, NFTA_TRACE_TABLE, NFTA_TRACE_TRANSPORT_HEADER, NFTA_TRACE_TYPE,
+        NFTA_TRACE_VERDICT, NFT_MSG_TRACE, NFT_TRACETYPE_POLICY, NFT_TRACETYPE_RETURN,
+        NFT_TRACETYPE_RULE,
+    },
+    Chain, ChainPolicy, ProtocolFamily, Rule, Table,
+};
+
+/// The step of the traversal of the ruleset a trace event reports.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[nfnetlink_enum(u32)]
+pub enum TraceType {
+    /// The packet reached the end of a base chain, and the policy of the chain applied.
+    Policy = NFT_TRACETYPE_POLICY,
+    /// The packet reached the end of a regular chain, and returned to the calling chain.
+    Return = NFT_TRACETYPE_RETURN,
+    /// The packet matched a rule.
+    Rule = NFT_TRACETYPE_RULE,
+}
+
+/// A trace event, sent for a packet evaluated in a chain.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+#[nfnetlink_struct(derive_deserialize = false)]
+pub struct Trace {
+    family: ProtocolFamily,
+    #[field(NFTA_TRACE_TABLE)]
+    table: String,
+    #[field(NFTA_TRACE_CHAIN)]
+    chain: String,
+    /// Handle of the rule the packet matched, see [`Trace::find_rule`].
+    #[field(NFTA_TRACE_RULE_HANDLE)]
+    rule_handle: u64,
+    #[field(NFTA_TRACE_TYPE, name_in_functions = "type")]
+    trace_type: TraceType,
+    #[field(NFTA_TRACE_VERDICT)]
+    verdict: Verdict,
+    /// Identifier shared by all the events of a packet.
+    #[field(NFTA_TRACE_ID)]
+    id: u32,
+    #[field(NFTA_TRACE_LL_HEADER)]
+    ll_header: Vec<u8>,
+    #[field(NFTA_TRACE_NETWORK_HEADER)]
+    network_header: Vec<u8>,
+    #[field(NFTA_TRACE_TRANSPORT_HEADER)]
+    transport_header: Vec<u8>,
+    /// Index of the input interface.
+    #[field(NFTA_TRACE_IIF)]
+    iif: u32,
+    /// Type of the input interface (`ARPHRD_*` constants).
+    #[field(NFTA_TRACE_IIFTYPE)]
+    iif_type: u16,
+    /// Index of the output interface.
+    #[field(NFTA_TRACE_OIF)]
+    oif: u32,
+    /// Type of the output interface (`ARPHRD_*` constants).
+    #[field(NFTA_TRACE_OIFTYPE)]
+    oif_type: u16,
+    #[field(NFTA_TRACE_MARK)]
+    mark: u32,
+    /// Protocol family of the packet (`NFPROTO_*` constants), which differs from the family of
+    /// the table for `inet` tables.
+    #[field(NFTA_TRACE_NFPROTO)]
+    nfproto: u32,
+    /// Policy of the base chain, for the `TraceType::Policy` events.
+    #[field(NFTA_TRACE_POLICY)]
+    policy: ChainPolicy,
+}
+
+impl Trace {
+    /// Family of the table the packet was evaluated in.
+    pub fn get_family(&self) -> ProtocolFamily {
+        self.family
+    }
+
+    /// Looks up the rule the packet matched in `rules`, which should hold the rules of the chain
+    /// of the event, as retrieved with [`list_rules_for_chain`].
+    pub fn find_rule<'a>(&self, rules: &'a [Rule]) -> Option<&'a Rule> {
+        let handle = self.get_rule_handle()?;
+        rules.iter().find(|rule| {
+            rule.get_family() == self.family
+                && rule.get_table() == self.get_table()
+                && rule.get_chain() == self.get_chain()
+                && rule.get_handle() == Some(handle)
+        })
+    }
+
+    fn get_chain_object(&self) -> Option<Chain> {
+        let table = Table::new(self.family).with_name(self.get_table()?);
+        Some(Chain::new(&table).with_name(self.get_chain()?))
+    }
+}
+
+impl NfNetlinkDeserializable for Trace {
+    fn deserialize(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        let (mut obj, nfgenmsg, remaining_data) =
+            parse_subsystem_object::<Self>(buf, NFNL_SUBSYS_NFTABLES as u8, &[NFT_MSG_TRACE])?;
+        obj.family = ProtocolFamily::try_from(nfgenmsg.nfgen_family as i32)?;
+
+        Ok((obj, remaining_data))
+    }
+}
+
+/// The path of a packet through the ruleset: the trace events sharing the same id, in the order
+/// they were received.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PacketTrace {
+    id: u32,
+    events: Vec<Trace>,
+}
+
+impl PacketTrace {
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn events(&self) -> &[Trace] {
+        &self.events
+    }
+
+    /// Pairs each event with the rule it refers to, looked up in `rules` (see
+    /// [`PacketTrace::list_rules`]).
+    pub fn with_rules<'a>(&'a self, rules: &'a [Rule]) -> Vec<(&'a Trace, Option<&'a Rule>)> {
+        self.events
+            .iter()
+            .map(|event| (event, event.find_rule(rules)))
+            .collect()
+    }
+
+    fn get_chains(&self) -> Vec<Chain> {
+        let mut chains: Vec<Chain> = Vec::new();
+        for chain in self.events.iter().filter_map(Trace::get_chain_object) {
+            if !chains.contains(&chain) {
+                chains.push(chain);
+            }
+        }
+        chains
+    }
+
+    /// Retrieves the rules of all the chains the packet went through.
+    pub fn list_rules(&self, sock: &mut Socket) -> anyhow::Result<Vec<Rule>> {
+        let mut rules = Vec::new();
+        for chain in self.get_chains() {
+            rules.extend(list_rules_for_chain(&chain, sock)?);
+        }
+        Ok(rules)
+    }
+
+    pub async fn list_rules_async<S: AsyncSocket>(
+        &self,
+        sock: &mut S,
+    ) -> anyhow::Result<Vec<Rule>> {
+        let mut rules = Vec::new();
+        for chain in self.get_chains() {
+            rules.extend(list_rules_for_chain_async(&chain, sock).await?);
+        }
+        Ok(rules)
+    }
+}
+
+/// Number of packets whose paths a [`TraceCollector`] keeps by default.
+pub const DEFAULT_TRACE_CAPACITY: usize = 1024;
+
+/// Correlates trace events into per-packet paths, according to their id.
+///
+/// The kernel does not report the end of a path, so the collector only keeps the paths of the
+/// last packets seen: once it holds `capacity` paths, starting a new one evicts the oldest.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraceCollector {
+    traces: HashMap<u32, PacketTrace>,
+    // the ids of the paths, in the order their first event was received
+    order: VecDeque<u32>,
+    capacity: usize,
+}
+
+impl Default for TraceCollector {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_TRACE_CAPACITY)
+    }
+}
+
+impl TraceCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a collector keeping the paths of (at least one and) at most `capacity` packets.
+    pub fn with_capacity(capacity: usize) -> Self {
+        TraceCollector {
+            traces: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Appends `trace` to the path of its packet, and returns that path. The events without an
+    /// id cannot be correlated, they are dropped and None is returned.
+    pub fn add(&mut self, trace: Trace) -> Option<&PacketTrace> {
+        let id = *trace.get_id()?;
+        if !self.traces.contains_key(&id) {
+            if self.order.len() >= self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.traces.remove(&oldest);
+                }
+            }
+            self.order.push_back(id);
+        }
+        let path = self.traces.entry(id).or_insert_with(|| PacketTrace {
+            id,
+            events: Vec::new(),
+        });
+        path.events.push(trace);
+        Some(path)
+    }
+
+    /// The paths of the packets, in the order their first event was received.
+    pub fn traces(&self) -> impl Iterator<Item = &PacketTrace> {
+        self.order.iter().map(move |id| &self.traces[id])
+    }
+
+    /// Removes the path of the packet `id`, e.g. once it has been displayed.
+    pub fn take(&mut self, id: u32) -> Option<PacketTrace> {
+        let trace = self.traces.remove(&id)?;
+        self.order.retain(|other| *other != id);
+        Some(trace)
+    }
+}
+
+/// Subscribes `sock` to the trace events. With an `AsyncSocket`, call this on its
+/// `socket_mut()`.
+pub fn subscribe(sock: &mut Socket) -> std::io::Result<()> {
+    sock.add_membership(NFNLGRP_NFTRACE)
+}
+
+/// Waits for trace events on a socket subscribed with [`subscribe`], and calls `cb` on each of
+/// them.
+pub fn recv_traces(
+    sock: &mut Socket,
+    cb: &mut dyn FnMut(Trace) -> Result<(), QueryError>,
+) -> anyhow::Result<()> {
+    recv_subsystem_objects(sock, NFNL_SUBSYS_NFTABLES as u8, cb)
+}
+
+pub async fn recv_traces_async<S: AsyncSocket>(
+    sock: &mut S,
+    cb: &mut (dyn FnMut(Trace) -> Result<(), QueryError> + Send),
+) -> anyhow::Result<()> {
+    recv_subsystem_objects_async(sock, NFNL_SUBSYS_NFTABLES as u8, cb).await
+}